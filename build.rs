@@ -0,0 +1,145 @@
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(asygnal_probe)");
+
+    #[cfg(feature = "probe")]
+    probe::run();
+}
+
+/// Probes the target's C headers for the raw value of each signal.
+///
+/// This runs the target's C preprocessor rather than a compiled program. The
+/// values are checked against `libc` at compile time within `signals!`.
+///
+/// The check is skipped with a warning if the headers can't be expanded, or if
+/// cross-compiling without a C compiler configured for the target, since the
+/// host's headers may not match the target's.
+#[cfg(feature = "probe")]
+mod probe {
+    use std::{env, fmt::Write, fs, path::PathBuf};
+
+    // Keep in sync with `signals!` in `src/signal/signal.rs`.
+    const SIGNALS: &[&str] = &[
+        "SIGABRT",
+        "SIGALRM",
+        "SIGBUS",
+        "SIGCHLD",
+        "SIGCONT",
+        "SIGFPE",
+        "SIGHUP",
+        "SIGILL",
+        "SIGINFO",
+        "SIGINT",
+        "SIGIO",
+        "SIGPIPE",
+        "SIGPOLL",
+        "SIGPROF",
+        "SIGQUIT",
+        "SIGSEGV",
+        "SIGTSTP",
+        "SIGSYS",
+        "SIGTERM",
+        "SIGTRAP",
+        "SIGTTIN",
+        "SIGTTOU",
+        "SIGURG",
+        "SIGVTALRM",
+        "SIGUSR1",
+        "SIGUSR2",
+        "SIGXCPU",
+        "SIGXFSZ",
+        "SIGWINCH",
+    ];
+
+    const PREFIX: &str = "asygnal_probe_";
+
+    pub fn run() {
+        // Only Unix-like targets have signal constants worth checking.
+        if env::var_os("CARGO_CFG_UNIX").is_none() {
+            return;
+        }
+
+        println!("cargo:rerun-if-changed=build.rs");
+
+        let target = env::var("TARGET").unwrap();
+        let host = env::var("HOST").unwrap();
+
+        let target_cc = [
+            format!("CC_{}", target),
+            format!("CC_{}", target.replace('-', "_")),
+            String::from("TARGET_CC"),
+        ];
+        for var in &target_cc {
+            println!("cargo:rerun-if-env-changed={}", var);
+        }
+
+        if target != host
+            && !target_cc.iter().any(|var| env::var_os(var).is_some())
+        {
+            println!(
+                "cargo:warning=skipping signal probe: no C compiler configured \
+                 for {} (set CC_{})",
+                target, target,
+            );
+            return;
+        }
+
+        let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+
+        let mut source = String::from("#include <signal.h>\n");
+        for name in SIGNALS {
+            writeln!(source, "{}{} {}", PREFIX, name, name).unwrap();
+        }
+
+        let source_path = out_dir.join("probe_signals.c");
+        fs::write(&source_path, source).unwrap();
+
+        let expanded = match cc::Build::new()
+            .file(&source_path)
+            .cargo_metadata(false)
+            // Avoid line markers splitting expansions across lines.
+            .flag("-P")
+            .try_expand()
+        {
+            Ok(expanded) => expanded,
+            Err(error) => {
+                println!("cargo:warning=skipping signal probe: {}", error);
+                return;
+            }
+        };
+        let expanded = String::from_utf8_lossy(&expanded);
+
+        let mut probed = String::from("macro_rules! probed_signal {\n");
+
+        for line in expanded.lines() {
+            let (name, value) = match line.trim().strip_prefix(PREFIX) {
+                Some(line) => match line.split_once(' ') {
+                    Some(pair) => pair,
+                    None => continue,
+                },
+                None => continue,
+            };
+
+            // Values may be parenthesized or cast, e.g. `((int) 29)`. Names
+            // left unexpanded are not defined by the headers.
+            let value = value.replace("(int)", "");
+            let value = value.trim_matches(|c: char| c == '(' || c == ')');
+
+            match value.trim().parse::<i32>().ok() {
+                Some(value) => {
+                    let arm =
+                        format!("    ({}) => {{ Some({}) }};", name, value);
+                    probed.push_str(&arm);
+                    probed.push('\n');
+                }
+                // The header doesn't define it.
+                None => continue,
+            }
+        }
+
+        probed.push_str("    ($other:ident) => { None::<libc::c_int> };\n}\n");
+
+        fs::write(out_dir.join("probed_signals.rs"), probed).unwrap();
+
+        println!("cargo:rustc-cfg=asygnal_probe");
+    }
+}
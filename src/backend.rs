@@ -0,0 +1,168 @@
+//! Information about the backend used for signal handling.
+//!
+//! What can be observed about a signal depends on the target and on which
+//! features are enabled. [`capabilities`] reports this at runtime, so that
+//! higher-level code can check for e.g. sender identification rather than
+//! repeating the `#[cfg]`s of this crate.
+//!
+//! # Examples
+//!
+//! ```
+//! let caps = asygnal::backend::capabilities();
+//!
+//! if caps.sender() {
+//!     println!("signals can be attributed to their sender");
+//! }
+//! ```
+//!
+//! [`capabilities`]: fn.capabilities.html
+
+/// The mechanism that reports received signals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Backend {
+    /// The handler wakes up listeners through pipes registered with the tokio
+    /// reactor.
+    ///
+    /// This is used with the `once-tokio` feature.
+    Tokio,
+    /// The handler wakes up listeners through pipes watched by a single
+    /// background thread.
+    ///
+    /// This is used with the `once-core` feature alone.
+    Thread,
+    /// Console control events are reported by a handler installed via
+    /// `SetConsoleCtrlHandler`.
+    ///
+    /// This is used on Windows.
+    Console,
+}
+
+/// What can be observed about signals with the active [`Backend`].
+///
+/// This is returned by [`capabilities`].
+///
+/// [`Backend`]:      enum.Backend.html
+/// [`capabilities`]: fn.capabilities.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    backend: Backend,
+    siginfo: bool,
+    queued_realtime: bool,
+    sender: bool,
+    kqueue: bool,
+}
+
+impl Capabilities {
+    /// Returns the active backend.
+    #[inline]
+    pub const fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Returns whether handlers can be installed with `SA_SIGINFO`, which
+    /// provides metadata such as the `si_code` of each delivery.
+    ///
+    /// See [`SignalInfo`] for more info.
+    ///
+    /// [`SignalInfo`]: ../once/signal/struct.SignalInfo.html
+    #[inline]
+    pub const fn siginfo(&self) -> bool {
+        self.siginfo
+    }
+
+    /// Returns whether realtime signals are supported, with each delivery
+    /// queued along with its payload rather than coalesced.
+    ///
+    /// See [`RealtimeSignalOnce`] for more info.
+    ///
+    /// [`RealtimeSignalOnce`]: ../once/signal/struct.RealtimeSignalOnce.html
+    #[inline]
+    pub const fn queued_realtime(&self) -> bool {
+        self.queued_realtime
+    }
+
+    /// Returns whether signals can be observed in the order they were
+    /// received.
+    ///
+    /// Standard signals received in between polls are coalesced and reported
+    /// in order of integer value on every backend, so this only holds for
+    /// realtime signals.
+    #[inline]
+    pub const fn ordered_delivery(&self) -> bool {
+        self.queued_realtime
+    }
+
+    /// Returns whether the process and user that sent a signal can be
+    /// identified.
+    #[inline]
+    pub const fn sender(&self) -> bool {
+        self.sender
+    }
+
+    /// Returns whether signals can be observed through a kqueue
+    /// `EVFILT_SIGNAL` filter, without installing a handler.
+    ///
+    /// See [`KqueueSignalSetOnce`] for more info.
+    ///
+    /// [`KqueueSignalSetOnce`]: ../once/signal/struct.KqueueSignalSetOnce.html
+    #[inline]
+    pub const fn kqueue(&self) -> bool {
+        self.kqueue
+    }
+}
+
+/// Returns what can be observed about signals with the active backend.
+pub const fn capabilities() -> Capabilities {
+    Capabilities {
+        backend: if cfg!(windows) {
+            Backend::Console
+        } else if cfg!(feature = "once-tokio") {
+            Backend::Tokio
+        } else {
+            Backend::Thread
+        },
+        siginfo: cfg!(unix),
+        queued_realtime: cfg!(any(target_os = "linux", target_os = "android")),
+        sender: cfg!(unix),
+        kqueue: cfg!(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "openbsd",
+            target_os = "netbsd",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities_match_target() {
+        let caps = capabilities();
+
+        assert_eq!(caps.siginfo(), cfg!(unix));
+        assert_eq!(caps.sender(), cfg!(unix));
+        assert_eq!(
+            caps.queued_realtime(),
+            cfg!(any(target_os = "linux", target_os = "android"))
+        );
+        assert_eq!(caps.ordered_delivery(), caps.queued_realtime());
+        assert_eq!(
+            caps.kqueue(),
+            cfg!(any(target_os = "macos", target_os = "freebsd"))
+        );
+
+        #[cfg(windows)]
+        assert_eq!(caps.backend(), Backend::Console);
+
+        #[cfg(all(unix, feature = "once-tokio"))]
+        assert_eq!(caps.backend(), Backend::Tokio);
+
+        #[cfg(all(unix, not(feature = "once-tokio")))]
+        assert_eq!(caps.backend(), Backend::Thread);
+    }
+}
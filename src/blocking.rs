@@ -0,0 +1,266 @@
+//! Blocking signal handling for programs without an async executor.
+//!
+//! [`SignalIter`] yields each signal received, while [`wait`] and
+//! [`wait_timeout`] block until a single one is. These are also available as
+//! [`SignalSet::blocking_iter`], [`SignalSet::wait`], and
+//! [`SignalSet::wait_timeout`].
+//!
+//! [`SignalIter`]:   struct.SignalIter.html
+//! [`wait`]:         fn.wait.html
+//! [`wait_timeout`]: fn.wait_timeout.html
+//! [`SignalSet::blocking_iter`]: ../signal/struct.SignalSet.html#method.blocking_iter
+//! [`SignalSet::wait`]:          ../signal/struct.SignalSet.html#method.wait
+//! [`SignalSet::wait_timeout`]:  ../signal/struct.SignalSet.html#method.wait_timeout
+
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    once::signal::table::Table,
+    signal::{Signal, SignalArray, SignalSet},
+    unix::pipe::{self, Reader, Writer},
+};
+
+/// An iterator that blocks until the next [`Signal`] in a [`SignalSet`] is
+/// received.
+///
+/// This is the synchronous counterpart to the futures in [`once`]. Signals
+/// received in between calls to [`next`] are coalesced, with each pending
+/// signal yielded once in order of its integer value.
+///
+/// The iterator only ends if waiting fails, in which case the error can be
+/// retrieved via [`take_error`].
+///
+/// Dropping the iterator restores the previous action of each signal that has
+/// no other listeners.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::SignalSet;
+///
+/// let signals = SignalSet::new().hangup().terminate();
+///
+/// for signal in signals.blocking_iter()? {
+///     if signal == asygnal::Signal::Terminate {
+///         break;
+///     }
+///     println!("reloading config");
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`Signal`]:     ../signal/enum.Signal.html
+/// [`SignalSet`]:  ../signal/struct.SignalSet.html
+/// [`once`]:       ../once/index.html
+/// [`next`]:       #method.next
+/// [`take_error`]: #method.take_error
+#[derive(Debug)]
+pub struct SignalIter {
+    signals: SignalSet,
+    reader: Reader,
+    writer: Writer,
+    /// Signals observed but not yet yielded.
+    pending: SignalSet,
+    /// The catch counts last observed for each signal.
+    counts: SignalArray<usize>,
+    error: Option<io::Error>,
+}
+
+impl Iterator for SignalIter {
+    type Item = Signal;
+
+    fn next(&mut self) -> Option<Signal> {
+        match self.next_before(None) {
+            Ok(signal) => signal,
+            Err(error) => {
+                self.error = Some(error);
+                None
+            }
+        }
+    }
+}
+
+impl Drop for SignalIter {
+    fn drop(&mut self) {
+        // There's no reasonable way to report failing to restore an action.
+        let _ = crate::once::signal::unregister_set(self.signals, self.writer);
+
+        unsafe {
+            libc::close(self.reader.0);
+            libc::close(self.writer.0);
+        }
+    }
+}
+
+impl SignalIter {
+    /// Registers a handler for `signals` and returns an iterator over them.
+    pub fn register(signals: SignalSet) -> io::Result<Self> {
+        let (reader, writer) = pipe::pipe()?;
+
+        let mut iter = Self {
+            signals,
+            reader,
+            writer,
+            pending: SignalSet::new(),
+            counts: [0; Signal::NUM],
+            error: None,
+        };
+
+        // Signals caught before registering are not yielded.
+        iter.take_caught();
+
+        // Dropping `iter` upon failure closes the pipe.
+        crate::once::signal::register_set(signals, writer)?;
+        Ok(iter)
+    }
+
+    /// Returns the signals handled by `self`.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.signals
+    }
+
+    /// Blocks until the next signal is received or `timeout` elapses,
+    /// returning `None` in the latter case.
+    ///
+    /// Unlike [`next`](#method.next), failing to wait does not end `self`.
+    pub fn next_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> io::Result<Option<Signal>> {
+        self.next_before(Instant::now().checked_add(timeout))
+    }
+
+    /// Takes the error that ended `self`, if any.
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Blocks until the next signal is received or `deadline` passes.
+    fn next_before(
+        &mut self,
+        deadline: Option<Instant>,
+    ) -> io::Result<Option<Signal>> {
+        loop {
+            if let Some(signal) = self.pending.pop_first() {
+                return Ok(Some(signal));
+            }
+
+            if !Table::global().is_paused() {
+                self.pending = self.take_caught();
+                if !self.pending.is_empty() {
+                    continue;
+                }
+            }
+
+            let timeout = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Ok(None);
+                    }
+                    Some(deadline - now)
+                }
+                None => None,
+            };
+
+            if self.reader.wait_readable(timeout)? {
+                Table::global().drain(self.reader, self.writer);
+            }
+        }
+    }
+
+    /// Returns the signals caught since last called, updating the counts.
+    fn take_caught(&mut self) -> SignalSet {
+        Table::global().take_caught(self.signals, &mut self.counts)
+    }
+}
+
+/// Registers a handler for `signals` and blocks until one of them is received.
+///
+/// Signals received before calling this are not returned. Once this returns,
+/// the previous action of each signal that has no other listeners is restored.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::SignalSet;
+///
+/// let signal = asygnal::blocking::wait(SignalSet::termination())?;
+/// println!("received {}, exiting", signal);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn wait(signals: SignalSet) -> io::Result<Signal> {
+    let mut iter = SignalIter::register(signals)?;
+    loop {
+        if let Some(signal) = iter.next_before(None)? {
+            return Ok(signal);
+        }
+    }
+}
+
+/// Like [`wait`], but returns `None` if no signal is received before `timeout`
+/// elapses.
+///
+/// [`wait`]: fn.wait.html
+pub fn wait_timeout(
+    signals: SignalSet,
+    timeout: Duration,
+) -> io::Result<Option<Signal>> {
+    SignalIter::register(signals)?.next_timeout(timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::thread;
+
+    #[test]
+    fn wait_timeout() {
+        test_util::run_exclusive(wait_timeout_impl);
+    }
+
+    fn wait_timeout_impl() {
+        let never = SignalSet::new().user_def_2();
+
+        assert_eq!(super::wait_timeout(never, Duration::ZERO).unwrap(), None);
+
+        let start = Instant::now();
+        let timeout = Duration::from_millis(50);
+        assert_eq!(super::wait_timeout(never, timeout).unwrap(), None);
+        assert!(start.elapsed() >= timeout);
+
+        let raiser = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(50));
+            unsafe { libc::kill(libc::getpid(), libc::SIGURG) };
+        });
+        let received =
+            super::wait_timeout(Signal::Urgent.into(), Duration::from_secs(10));
+        raiser.join().unwrap();
+
+        assert_eq!(received.unwrap(), Some(Signal::Urgent));
+    }
+
+    #[test]
+    fn blocking_iter_coalesces() {
+        let signals = Signal::Urgent | Signal::WindowChange;
+
+        test_util::run_exclusive(|| {
+            let mut iter = signals.blocking_iter().unwrap();
+
+            // Deliveries between calls are merged and yielded in order.
+            Signal::WindowChange.raise().unwrap();
+            Signal::Urgent.raise().unwrap();
+            Signal::Urgent.raise().unwrap();
+
+            assert_eq!(iter.next(), Some(signals.first().unwrap()));
+            assert_eq!(iter.next(), Some(signals.last().unwrap()));
+            assert_eq!(iter.next_timeout(Duration::ZERO).unwrap(), None);
+        });
+    }
+}
@@ -0,0 +1,286 @@
+//! Reaping child processes upon [`Child`] signals.
+//!
+//! `SIGCHLD` is coalesced, so a single signal may stand for several exited
+//! children, and a child may exit between registering for the signal and
+//! checking on it. The types here get this right by registering first and then
+//! calling `waitpid` with `WNOHANG` until nothing is left each time the signal
+//! is received.
+//!
+//! - [`ChildExit`] waits for a specific process, like
+//!   [`ChildExt::wait_via_asygnal`] does for [`std::process::Child`].
+//!
+//! - [`ChildEvents`] reaps every child of the current process, as process
+//!   supervisors do.
+//!
+//! [`Child`]:       ../signal/enum.Signal.html#variant.Child
+//! [`ChildExit`]:   struct.ChildExit.html
+//! [`ChildEvents`]: struct.ChildEvents.html
+//! [`ChildExt::wait_via_asygnal`]: ../process/trait.ChildExt.html#tymethod.wait_via_asygnal
+//! [`std::process::Child`]: https://doc.rust-lang.org/std/process/struct.Child.html
+
+use libc::pid_t;
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    once::{signal::listener::Listener, CancelSafe},
+    process::WaitStatus,
+    Signal,
+};
+
+/// A future that resolves to the wait status of a child process once it
+/// exits, reaping it.
+///
+/// The future fails with the error of `waitpid`, such as if `pid` is not a
+/// child of the current process or has already been reaped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::child::ChildExit;
+/// use std::process::Command;
+///
+/// # async fn example() -> std::io::Result<()> {
+/// let child = Command::new("worker").spawn()?;
+///
+/// let status = ChildExit::new(child.id() as libc::pid_t)?.await?;
+/// println!("worker {}", status);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct ChildExit {
+    pid: pid_t,
+    listener: Listener,
+    status: Option<WaitStatus>,
+}
+
+impl Future for ChildExit {
+    type Output = io::Result<WaitStatus>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        if let Some(status) = this.status {
+            return Poll::Ready(Ok(status));
+        }
+
+        let pid = this.pid;
+        let poll = poll_reap(&mut this.listener, cx, || try_wait(pid));
+        poll.map_ok(|(_, status)| {
+            this.status = Some(status);
+            status
+        })
+    }
+}
+
+// SAFETY: A child is only reaped in the same poll that returns its status.
+unsafe impl CancelSafe for ChildExit {}
+
+impl ChildExit {
+    /// Registers a handler for [`Child`] to wait for the process `pid`.
+    ///
+    /// [`Child`]: ../signal/enum.Signal.html#variant.Child
+    #[inline]
+    pub fn new(pid: pid_t) -> io::Result<Self> {
+        Ok(Self {
+            pid,
+            listener: Listener::register(Signal::Child.into())?,
+            status: None,
+        })
+    }
+
+    /// Returns the process ID being waited for.
+    #[inline]
+    pub fn pid(&self) -> pid_t {
+        self.pid
+    }
+}
+
+/// A source of the exits of every child of the current process, reaping each.
+///
+/// This takes over reaping for the whole process, since it calls `waitpid`
+/// for any child rather than for specific ones. Children that are waited for
+/// elsewhere, such as through [`Child::wait`], [`ChildExit`] or
+/// [`ChildExt::wait_via_asygnal`], may be reaped by `self` first, in which case
+/// those waits fail or never see the exit. It should therefore be the only
+/// means of waiting for children in the process while registered.
+///
+/// Having no children is not an error, since they may be spawned later.
+///
+/// With the `stream` feature, this implements `Stream` and `FusedStream`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::child::ChildEvents;
+/// use std::future::poll_fn;
+///
+/// # async fn example() -> std::io::Result<()> {
+/// let mut events = ChildEvents::register()?;
+///
+/// loop {
+///     let (pid, status) = poll_fn(|cx| events.poll_next(cx)).await?;
+///     println!("child {} {}", pid, status);
+/// }
+/// # }
+/// ```
+///
+/// [`Child::wait`]: https://doc.rust-lang.org/std/process/struct.Child.html#method.wait
+/// [`ChildExit`]:   struct.ChildExit.html
+/// [`ChildExt::wait_via_asygnal`]: ../process/trait.ChildExt.html#tymethod.wait_via_asygnal
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct ChildEvents {
+    listener: Listener,
+}
+
+// SAFETY: A child is only reaped in the same poll that returns its status.
+unsafe impl CancelSafe for ChildEvents {}
+
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::Stream for ChildEvents {
+    type Item = io::Result<(pid_t, WaitStatus)>;
+
+    #[inline]
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Self::Item>> {
+        ChildEvents::poll_next(&mut self, cx).map(Some)
+    }
+}
+
+/// Never terminated, since `self` never ends.
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::stream::FusedStream for ChildEvents {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
+
+impl ChildEvents {
+    /// Registers a handler for [`Child`] to reap every child.
+    ///
+    /// [`Child`]: ../signal/enum.Signal.html#variant.Child
+    #[inline]
+    pub fn register() -> io::Result<Self> {
+        Ok(Self {
+            listener: Listener::register(Signal::Child.into())?,
+        })
+    }
+
+    /// Polls for the next child to exit, returning its process ID and wait
+    /// status.
+    pub fn poll_next(
+        &mut self,
+        cx: &mut Context,
+    ) -> Poll<io::Result<(pid_t, WaitStatus)>> {
+        poll_reap(&mut self.listener, cx, || match try_wait(-1) {
+            Err(error) if error.raw_os_error() == Some(libc::ECHILD) => {
+                Ok(None)
+            }
+            result => result,
+        })
+    }
+}
+
+/// Polls `listener` until `reap` returns a result, calling it again upon every
+/// [`Child`] signal.
+///
+/// This is the loop behind every future of this crate that waits on children.
+/// Since `listener` is registered before `reap` is first called, no exit is
+/// missed in between.
+///
+/// [`Child`]: ../signal/enum.Signal.html#variant.Child
+pub(crate) fn poll_reap<T>(
+    listener: &mut Listener,
+    cx: &mut Context,
+    mut reap: impl FnMut() -> io::Result<Option<T>>,
+) -> Poll<io::Result<T>> {
+    loop {
+        if let Some(exit) = reap()? {
+            return Poll::Ready(Ok(exit));
+        }
+
+        match listener.poll_next(cx) {
+            Poll::Ready(Ok(_)) => continue,
+            Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+            Poll::Pending => return Poll::Pending,
+        }
+    }
+}
+
+/// Reaps `pid` without blocking, returning `None` if it has not exited.
+fn try_wait(pid: pid_t) -> io::Result<Option<(pid_t, WaitStatus)>> {
+    loop {
+        let mut status = 0;
+        match unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) } {
+            0 => return Ok(None),
+            -1 => {
+                let error = io::Error::last_os_error();
+                if error.kind() != io::ErrorKind::Interrupted {
+                    return Err(error);
+                }
+            }
+            pid => return Ok(Some((pid, WaitStatus::from_raw(status)))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::{process::Command, thread, time::Duration};
+
+    #[test]
+    fn child_exit() {
+        let _children = test_util::children();
+
+        let pid = Command::new("sh")
+            .args(["-c", "sleep 0.05; exit 7"])
+            .spawn()
+            .unwrap()
+            .id() as pid_t;
+
+        let status =
+            test_util::block_on(async { ChildExit::new(pid).unwrap().await });
+
+        assert_eq!(status.unwrap().code(), Some(7));
+    }
+
+    #[test]
+    fn child_events() {
+        test_util::run_reaping(|| {
+            let mut events = ChildEvents::register().unwrap();
+
+            let pid = Command::new("sh")
+                .args(["-c", "exit 7"])
+                .spawn()
+                .unwrap()
+                .id() as pid_t;
+
+            let mut next = std::future::poll_fn(|cx| events.poll_next(cx));
+            let (reaped, status) = loop {
+                match test_util::poll(&mut next) {
+                    Poll::Ready(exit) => break exit.unwrap(),
+                    Poll::Pending => thread::sleep(Duration::from_millis(10)),
+                }
+            };
+            assert_eq!(reaped, pid);
+            assert_eq!(status.code(), Some(7));
+
+            // Having no children left is not an error.
+            assert!(test_util::poll(&mut next).is_pending());
+        });
+    }
+}
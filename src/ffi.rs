@@ -0,0 +1,293 @@
+//! C bindings for reusing this crate's signal handler.
+//!
+//! This allows mixed C/Rust applications to share a single handler rather than
+//! installing competing ones. The functions here notify a process-wide pipe,
+//! whose reading end can be added to any event loop:
+//!
+//! ```c
+//! uint32_t mask = asygnal_signal_mask(SIGINT) | asygnal_signal_mask(SIGTERM);
+//! int registration = asygnal_register_set(mask);
+//! if (registration < 0) {
+//!     perror("asygnal_register_set");
+//! }
+//!
+//! int fd = asygnal_notification_fd();
+//! // Wait for `fd` to become readable, drain it, then take the signals caught
+//! // since the last check with `asygnal_caught_mask(registration)`.
+//!
+//! asygnal_unregister(registration);
+//! ```
+//!
+//! Masks use the same bit layout as [`SignalSet`], which may vary between
+//! targets. Use [`asygnal_signal_mask`] to get the bit for a raw signal.
+//!
+//! [`SignalSet`]:           ../signal/struct.SignalSet.html
+//! [`asygnal_signal_mask`]: fn.asygnal_signal_mask.html
+
+use libc::c_int;
+use std::{
+    io,
+    sync::{Mutex, MutexGuard},
+};
+
+use crate::{
+    once::signal::table::Table,
+    signal::SignalArray,
+    unix::pipe::{self, Reader, Writer},
+    Signal, SignalSet,
+};
+
+/// The registrations made through these bindings, which share a pipe.
+static STATE: Mutex<State> = Mutex::new(State {
+    pipe: None,
+    registrations: Vec::new(),
+    next_id: 1,
+});
+
+struct State {
+    /// Like the pipes used by futures, this lives for the rest of the program.
+    pipe: Option<(Reader, Writer)>,
+    registrations: Vec<Registration>,
+    next_id: c_int,
+}
+
+struct Registration {
+    id: c_int,
+    signals: SignalSet,
+    /// The catch counts when the caught signals were last taken.
+    counts: SignalArray<usize>,
+}
+
+impl State {
+    fn lock() -> MutexGuard<'static, Self> {
+        STATE.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn pipe(&mut self) -> io::Result<(Reader, Writer)> {
+        match self.pipe {
+            Some(pipe) => Ok(pipe),
+            None => {
+                let new = pipe::pipe()?;
+                self.pipe = Some(new);
+                Ok(new)
+            }
+        }
+    }
+
+    fn registration(&mut self, id: c_int) -> Option<&mut Registration> {
+        self.registrations.iter_mut().find(|r| r.id == id)
+    }
+}
+
+/// Returns the mask bit for the raw signal value `signal`, or 0 if it is not
+/// supported.
+#[no_mangle]
+pub extern "C" fn asygnal_signal_mask(signal: c_int) -> u32 {
+    match Signal::from_raw(signal) {
+        Some(signal) => SignalSet::from_signal(signal).0,
+        None => 0,
+    }
+}
+
+/// Installs the handler for every signal in `mask`.
+///
+/// Returns a positive ID for the registration on success, which is passed to
+/// [`asygnal_caught_mask`] and [`asygnal_unregister`]. On failure, returns -1
+/// and sets `errno`, in which case no handlers in `mask` were installed. Bits
+/// that do not correspond to a supported signal result in `EINVAL`.
+///
+/// [`asygnal_caught_mask`]: fn.asygnal_caught_mask.html
+/// [`asygnal_unregister`]:  fn.asygnal_unregister.html
+#[no_mangle]
+pub extern "C" fn asygnal_register_set(mask: u32) -> c_int {
+    if mask & !SignalSet::all().0 != 0 {
+        set_errno(libc::EINVAL);
+        return -1;
+    }
+
+    let signals = SignalSet(mask);
+    let mut state = State::lock();
+    let counts = Table::global().counts(signals);
+
+    let result = state.pipe().and_then(|(_, writer)| {
+        crate::once::signal::register_set(signals, writer)
+    });
+
+    match result {
+        Ok(()) => {
+            let id = state.next_id;
+            state.next_id += 1;
+            state.registrations.push(Registration {
+                id,
+                signals,
+                counts,
+            });
+            id
+        }
+        Err(error) => {
+            set_errno(error.raw_os_error().unwrap_or(libc::EIO));
+            -1
+        }
+    }
+}
+
+/// Releases the registration `id` returned by [`asygnal_register_set`].
+///
+/// The handler of each of its signals is uninstalled unless another
+/// registration, including ones made from Rust, still uses it.
+///
+/// Returns 0 on success. On failure, returns -1 and sets `errno`. An unknown
+/// `id` results in `EINVAL`.
+///
+/// [`asygnal_register_set`]: fn.asygnal_register_set.html
+#[no_mangle]
+pub extern "C" fn asygnal_unregister(id: c_int) -> c_int {
+    let mut state = State::lock();
+
+    let index = match state.registrations.iter().position(|r| r.id == id) {
+        Some(index) => index,
+        None => {
+            set_errno(libc::EINVAL);
+            return -1;
+        }
+    };
+    let removed = state.registrations.remove(index);
+
+    // Keep listening for the signals of other registrations.
+    let unused = state
+        .registrations
+        .iter()
+        .fold(removed.signals, |unused, r| unused.without_all(r.signals));
+
+    let result = match state.pipe {
+        Some((_, writer)) => {
+            crate::once::signal::unregister_set(unused, writer)
+        }
+        None => Ok(()),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            set_errno(error.raw_os_error().unwrap_or(libc::EIO));
+            -1
+        }
+    }
+}
+
+/// Returns the file descriptor that becomes readable when a signal registered
+/// via [`asygnal_register_set`] is caught, or -1 if none were registered.
+///
+/// Callers should drain the descriptor before waiting on it again. It must not
+/// be closed.
+///
+/// [`asygnal_register_set`]: fn.asygnal_register_set.html
+#[no_mangle]
+pub extern "C" fn asygnal_notification_fd() -> c_int {
+    match State::lock().pipe {
+        Some((reader, _)) => reader.0,
+        None => -1,
+    }
+}
+
+/// Takes the mask of signals caught for the registration `id` since the last
+/// call, or since registering.
+///
+/// Each registration observes every delivery of its signals, regardless of
+/// what other registrations have taken. Multiple deliveries of the same signal
+/// between calls are merged. Returns 0 if `id` is unknown.
+#[no_mangle]
+pub extern "C" fn asygnal_caught_mask(id: c_int) -> u32 {
+    match State::lock().registration(id) {
+        Some(registration) => {
+            Table::global()
+                .take_caught(registration.signals, &mut registration.counts)
+                .0
+        }
+        None => 0,
+    }
+}
+
+fn set_errno(errno: c_int) {
+    cfg_if::cfg_if! {
+        if #[cfg(any(
+            target_os = "linux",
+            target_os = "redox",
+            target_os = "fuchsia",
+        ))] {
+            unsafe { *libc::__errno_location() = errno };
+        } else if #[cfg(any(
+            target_os = "android",
+            target_os = "openbsd",
+            target_os = "netbsd",
+        ))] {
+            unsafe { *libc::__errno() = errno };
+        } else if #[cfg(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+        ))] {
+            unsafe { *libc::__error() = errno };
+        } else if #[cfg(any(target_os = "solaris", target_os = "illumos"))] {
+            unsafe { *libc::___errno() = errno };
+        } else {
+            let _ = errno;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn rejects_unsupported_bits() {
+        assert_eq!(asygnal_signal_mask(0), 0);
+        assert_eq!(asygnal_register_set(!SignalSet::all().0), -1);
+        assert_eq!(
+            io::Error::last_os_error().raw_os_error(),
+            Some(libc::EINVAL)
+        );
+        assert_eq!(asygnal_unregister(0), -1);
+        assert_eq!(asygnal_caught_mask(0), 0);
+    }
+
+    #[test]
+    fn takes_caught_per_registration() {
+        let signal = Signal::WindowChange;
+        let mask = asygnal_signal_mask(signal.into_raw());
+        assert_eq!(mask, SignalSet::from_signal(signal).0);
+
+        test_util::run_exclusive(|| {
+            let first = asygnal_register_set(mask);
+            let second = asygnal_register_set(mask);
+            assert!(first > 0 && second > 0 && first != second);
+
+            let fd = asygnal_notification_fd();
+            assert_ne!(fd, -1);
+
+            signal.raise().unwrap();
+            let mut poll_fd = libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            assert_eq!(unsafe { libc::poll(&mut poll_fd, 1, 0) }, 1);
+
+            // Taking from one registration leaves the other unaffected.
+            assert_eq!(asygnal_caught_mask(first), mask);
+            assert_eq!(asygnal_caught_mask(first), 0);
+            assert_eq!(asygnal_caught_mask(second), mask);
+
+            assert_eq!(asygnal_unregister(first), 0);
+            assert_eq!(asygnal_unregister(first), -1);
+
+            // The handler stays installed for the other registration.
+            signal.raise().unwrap();
+            assert_eq!(asygnal_caught_mask(second), mask);
+            assert_eq!(asygnal_unregister(second), 0);
+        });
+    }
+}
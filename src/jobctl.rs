@@ -0,0 +1,139 @@
+//! Job control for shells and REPLs.
+//!
+//! These wrap the fiddly parts of managing foreground and background process
+//! groups on a controlling terminal. In particular, changing the foreground
+//! process group from a background process raises `SIGTTOU`, which stops the
+//! process by default. The functions here block `SIGTTOU` and `SIGTTIN` on the
+//! calling thread for the duration of the change.
+
+use libc::pid_t;
+use std::{io, mem, os::unix::io::RawFd};
+
+use crate::Signal;
+
+/// Returns the foreground process group of the terminal `tty`.
+pub fn foreground_group(tty: RawFd) -> io::Result<pid_t> {
+    match unsafe { libc::tcgetpgrp(tty) } {
+        -1 => Err(io::Error::last_os_error()),
+        pgid => Ok(pgid),
+    }
+}
+
+/// Makes `pgid` the foreground process group of the terminal `tty`.
+pub fn set_foreground_group(tty: RawFd, pgid: pid_t) -> io::Result<()> {
+    with_tty_signals_blocked(|| {
+        if unsafe { libc::tcsetpgrp(tty, pgid) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    })
+}
+
+/// Returns `true` if the current process group is the foreground process group
+/// of the terminal `tty`.
+pub fn is_foreground(tty: RawFd) -> io::Result<bool> {
+    Ok(foreground_group(tty)? == unsafe { libc::getpgrp() })
+}
+
+/// Moves the process `pid` into the process group `pgid`.
+///
+/// If `pgid` is 0, a new group is created with `pid` as its leader. Shells
+/// typically call this in both the parent and the child after forking to avoid
+/// racing on which runs first.
+pub fn set_process_group(pid: pid_t, pgid: pid_t) -> io::Result<()> {
+    if unsafe { libc::setpgid(pid, pgid) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Sends `SIGCONT` to every process in the group `pgid`, e.g. when resuming a
+/// stopped job in the foreground or background.
+pub fn continue_group(pgid: pid_t) -> io::Result<()> {
+    signal_group(pgid, Signal::Cont)
+}
+
+/// Sends `signal` to every process in the group `pgid`.
+pub fn signal_group(pgid: pid_t, signal: Signal) -> io::Result<()> {
+    if unsafe { libc::kill(-pgid, signal.into_raw()) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Makes `pgid` the foreground process group of the terminal `tty` until the
+/// returned guard is dropped, at which point the previous foreground process
+/// group is restored.
+///
+/// This is what a shell does when running a job in the foreground.
+pub fn foreground(tty: RawFd, pgid: pid_t) -> io::Result<ForegroundGuard> {
+    let previous = foreground_group(tty)?;
+    set_foreground_group(tty, pgid)?;
+    Ok(ForegroundGuard { tty, previous })
+}
+
+/// Restores the previous foreground process group of a terminal when dropped.
+///
+/// This is created by [`foreground`](fn.foreground.html).
+#[derive(Debug)]
+#[must_use = "the previous foreground group is restored immediately if dropped"]
+pub struct ForegroundGuard {
+    tty: RawFd,
+    previous: pid_t,
+}
+
+impl Drop for ForegroundGuard {
+    fn drop(&mut self) {
+        // There's no reasonable way to report an error here. Use `restore` to
+        // handle it instead.
+        let _ = set_foreground_group(self.tty, self.previous);
+    }
+}
+
+impl ForegroundGuard {
+    /// Returns the process group that will be restored.
+    #[inline]
+    pub fn previous(&self) -> pid_t {
+        self.previous
+    }
+
+    /// Restores the previous foreground process group, returning any error.
+    pub fn restore(self) -> io::Result<()> {
+        let result = set_foreground_group(self.tty, self.previous);
+        mem::forget(self);
+        result
+    }
+}
+
+/// Calls `f` with `SIGTTOU` and `SIGTTIN` blocked on the current thread.
+fn with_tty_signals_blocked<T>(
+    f: impl FnOnce() -> io::Result<T>,
+) -> io::Result<T> {
+    let _guard = crate::SignalSet::new().tt_out().tt_in().block_scoped()?;
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_a_tty() {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+
+        let error = foreground_group(fds[0]).unwrap_err();
+        assert_eq!(error.raw_os_error(), Some(libc::ENOTTY));
+
+        let error = set_foreground_group(fds[0], unsafe { libc::getpgrp() });
+        assert_eq!(error.unwrap_err().raw_os_error(), Some(libc::ENOTTY));
+
+        unsafe {
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+}
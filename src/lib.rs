@@ -33,14 +33,84 @@
 #[macro_use]
 mod macros;
 
-#[cfg(any(docsrs, feature = "once"))]
-#[cfg_attr(docsrs, doc(cfg(feature = "once")))]
+#[cfg(any(docsrs, feature = "once-core"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
 pub mod once;
+#[cfg(any(docsrs, feature = "once-core"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub use once::{ctrl_c, terminate};
+
+#[cfg(all(unix, any(docsrs, feature = "once-core")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub use once::signal::{
+    is_paused, pause, register_all, resume, ErrorPolicy, ForceExit, PauseGuard,
+    SignalRouter,
+};
 
 pub mod signal;
 pub use signal::{Signal, SignalSet};
 
+#[cfg(any(docsrs, feature = "once-core"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub mod backend;
+
+#[cfg(all(unix, any(docsrs, feature = "once-core")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub mod blocking;
+
+#[cfg(all(unix, any(docsrs, feature = "once-core")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub mod child;
+
+#[cfg(all(unix, feature = "ffi"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "ffi"))))]
+pub mod ffi;
+
+#[cfg(unix)]
+pub mod jobctl;
+
+#[cfg(unix)]
+pub mod process;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg_attr(docsrs, doc(cfg(any(target_os = "linux", target_os = "android"))))]
+pub mod profiler;
+
+#[cfg(all(unix, any(docsrs, feature = "once-core")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub mod policy;
+#[cfg(all(unix, any(docsrs, feature = "once-core")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub use policy::SignalPolicy;
+
+pub mod replay;
+
+#[cfg(all(unix, any(docsrs, feature = "once-core")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub mod resume;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg_attr(docsrs, doc(cfg(any(target_os = "linux", target_os = "android"))))]
+pub mod seccomp;
+
+#[cfg(all(unix, any(docsrs, feature = "once-core")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub mod shutdown;
+#[cfg(all(unix, any(docsrs, feature = "once-core")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub use shutdown::{default_shutdown, set_default_shutdown};
+
+#[cfg(all(unix, any(docsrs, feature = "stream")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub mod stream;
+
+#[cfg(unix)]
+pub mod terminal;
+
+#[cfg(all(test, unix, feature = "once-core"))]
+mod test_util;
+
 #[cfg(target_family = "unix")]
-mod unix;
-#[cfg(target_family = "windows")]
+pub mod unix;
+#[cfg(all(target_family = "windows", feature = "once-core"))]
 mod windows;
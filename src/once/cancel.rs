@@ -0,0 +1,148 @@
+use std::{ops::DerefMut, pin::Pin};
+
+/// A marker for futures and streams that are cancel-safe.
+///
+/// A type implementing this guarantees that dropping it, or no longer polling
+/// it, at any point never loses a caught signal for anything else. In
+/// particular:
+///
+/// - Caught signals are recorded process-wide rather than handed to a single
+///   listener, so every other future and stream for the same signal still
+///   observes it.
+///
+/// - A future or stream only ever takes a signal in the same poll that returns
+///   it. Dropping one that returned [`Pending`] discards nothing, and streams
+///   keep signals not yet yielded within themselves, so a `select!` branch or
+///   `next()` call that loses a race can simply be polled again.
+///
+/// This lets frameworks accept cancel-safe sources in APIs such as `select!`
+/// loops by bounding on this trait.
+///
+/// # Safety
+///
+/// Implementations must uphold the guarantee above, which code relying on this
+/// trait may depend on for correctness.
+///
+/// [`Pending`]: https://doc.rust-lang.org/std/task/enum.Poll.html#variant.Pending
+pub unsafe trait CancelSafe {}
+
+unsafe impl<T: CancelSafe + ?Sized> CancelSafe for &mut T {}
+
+unsafe impl<T: CancelSafe + ?Sized> CancelSafe for Box<T> {}
+
+unsafe impl<P> CancelSafe for Pin<P>
+where
+    P: DerefMut,
+    P::Target: CancelSafe,
+{
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use crate::{once::signal::SignalSetOnce, Signal};
+    use std::{
+        future::Future,
+        sync::Arc,
+        task::{Context, Poll, Wake},
+    };
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn assert_cancel_safe<T: CancelSafe>(_: &T) {}
+
+    #[cfg(feature = "stream")]
+    fn poll_next(
+        stream: &mut crate::stream::SignalStream,
+        cx: &mut Context,
+    ) -> Poll<Option<Signal>> {
+        futures_core::Stream::poll_next(Pin::new(stream), cx)
+    }
+
+    fn raise(signal: Signal) {
+        unsafe { libc::raise(signal.into_raw()) };
+    }
+
+    #[test]
+    fn drop_at_every_point() {
+        // The resume tests also send this signal.
+        test_util::run_exclusive(drop_at_every_point_impl);
+    }
+
+    fn drop_at_every_point_impl() {
+        let signal = Signal::Cont;
+
+        let waker = Arc::new(NoopWaker).into();
+        let mut cx = Context::from_waker(&waker);
+
+        #[cfg(feature = "stream")]
+        let (mut survivor, mut dropped) = {
+            use crate::stream::SignalStream;
+            (
+                SignalStream::register(signal).unwrap(),
+                SignalStream::register(signal).unwrap(),
+            )
+        };
+
+        let mut pending = SignalSetOnce::register(signal.into()).unwrap();
+        let mut fulfilled = SignalSetOnce::register(signal.into()).unwrap();
+        let mut survivor_once = SignalSetOnce::register(signal.into()).unwrap();
+        assert_cancel_safe(&pending);
+
+        // Dropped before any poll.
+        drop(SignalSetOnce::register(signal.into()).unwrap());
+
+        // Dropped after returning `Pending`.
+        assert!(Pin::new(&mut pending).poll(&mut cx).is_pending());
+        drop(pending);
+
+        #[cfg(feature = "stream")]
+        {
+            assert_cancel_safe(&survivor);
+            assert!(poll_next(&mut dropped, &mut cx).is_pending());
+        }
+
+        raise(signal);
+
+        // Dropped with the signal pending.
+        #[cfg(feature = "stream")]
+        drop(dropped);
+
+        // Dropped after returning it.
+        assert!(!fulfilled.is_fulfilled());
+        assert_eq!(
+            Pin::new(&mut fulfilled).poll(&mut cx),
+            Poll::Ready(Some(signal))
+        );
+        assert!(fulfilled.is_fulfilled());
+        drop(fulfilled);
+
+        assert_eq!(
+            Pin::new(&mut survivor_once).poll(&mut cx),
+            Poll::Ready(Some(signal))
+        );
+
+        #[cfg(feature = "stream")]
+        {
+            assert_eq!(
+                poll_next(&mut survivor, &mut cx),
+                Poll::Ready(Some(signal))
+            );
+            assert!(poll_next(&mut survivor, &mut cx).is_pending());
+
+            // Signals caught between polls are coalesced, not lost.
+            raise(signal);
+            raise(signal);
+            assert_eq!(
+                poll_next(&mut survivor, &mut cx),
+                Poll::Ready(Some(signal))
+            );
+            assert!(poll_next(&mut survivor, &mut cx).is_pending());
+        }
+    }
+}
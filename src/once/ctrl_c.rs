@@ -0,0 +1,374 @@
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(unix)]
+use super::signal;
+
+#[cfg(unix)]
+type CtrlCOnceInner = signal::SignalSetOnce;
+#[cfg(windows)]
+type CtrlCOnceInner = crate::windows::CtrlOnce;
+
+/// Returns a future that is fulfilled once upon receiving `CTRL` + `C`.
+///
+/// This is a shorthand for [`CtrlCOnce::register`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> std::io::Result<()> {
+/// asygnal::ctrl_c()?.await;
+/// println!("received CTRL + C");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`CtrlCOnce::register`]: struct.CtrlCOnce.html#method.register
+#[inline]
+pub fn ctrl_c() -> io::Result<CtrlCOnce> {
+    Ok(CtrlCOnce::register()?)
+}
+
+/// Returns a future that is fulfilled once upon a request to terminate the
+/// process.
+///
+/// # Unix Behavior
+///
+/// On Unix-like systems, this corresponds to the [default shutdown signals],
+/// which are [`Interrupt`], [`Quit`], and [`Terminate`] unless configured
+/// otherwise.
+///
+/// [default shutdown signals]: ../shutdown/fn.default_shutdown.html
+/// [`Interrupt`]: ../signal/enum.Signal.html#variant.Interrupt
+/// [`Quit`]:      ../signal/enum.Signal.html#variant.Quit
+/// [`Terminate`]: ../signal/enum.Signal.html#variant.Terminate
+///
+/// # Windows Behavior
+///
+/// On Windows, this corresponds to `CTRL` + `C` and `CTRL` + `BREAK`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> std::io::Result<()> {
+/// asygnal::terminate()?.await;
+/// println!("shutting down");
+/// # Ok(())
+/// # }
+/// ```
+#[inline]
+pub fn terminate() -> io::Result<CtrlCOnce> {
+    #[cfg(unix)]
+    let inner = crate::shutdown::default_shutdown()
+        .register_once()
+        .map_err(io::Error::from)?;
+
+    #[cfg(windows)]
+    let inner = CtrlCOnceInner::register(
+        crate::windows::CTRL_C | crate::windows::CTRL_BREAK,
+    )?;
+
+    Ok(CtrlCOnce::new(inner))
+}
+
+/// A future that is fulfilled once upon receiving `CTRL` + `C`.
+///
+/// After an instance is fulfilled, all subsequent polls will return `Ready`.
+///
+/// Cloning an instance is cheap and shares its registration, so that every
+/// task in an application can wait for the same `CTRL` + `C`.
+///
+/// With the `stream` feature, this implements `FusedFuture` for use in
+/// `futures::select!`.
+#[derive(Clone, Debug)]
+pub struct CtrlCOnce {
+    inner: CtrlCOnceInner,
+    fulfilled: bool,
+}
+
+impl Future for CtrlCOnce {
+    type Output = ();
+
+    #[inline]
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        let poll = Pin::new(&mut self.inner).poll(cx).map(|_| ());
+        if poll.is_ready() {
+            self.fulfilled = true;
+        }
+        poll
+    }
+}
+
+// SAFETY: This only wraps a cancel-safe future.
+unsafe impl super::CancelSafe for CtrlCOnce {}
+
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::future::FusedFuture for CtrlCOnce {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.fulfilled
+    }
+}
+
+impl CtrlCOnce {
+    #[inline]
+    fn new(inner: CtrlCOnceInner) -> Self {
+        Self {
+            inner,
+            fulfilled: false,
+        }
+    }
+
+    /// Registers the `CTRL` + `C` handler.
+    #[inline]
+    pub fn register() -> Result<Self, RegisterCtrlCOnceError> {
+        // Register via `Signal` instead of `SignalSet` since it's slightly more
+        // efficient.
+        #[cfg(unix)]
+        let inner: signal::SignalSetOnce =
+            crate::Signal::Interrupt.register_once()?.into();
+
+        #[cfg(windows)]
+        let inner = CtrlCOnceInner::register(crate::windows::CTRL_C)?;
+
+        Ok(Self::new(inner))
+    }
+
+    /// Registers the handler for all signals that would otherwise terminate.
+    ///
+    /// # Unix Behavior
+    ///
+    /// On Unix-like systems, this corresponds to
+    /// [`SignalSet::termination`]: [`Alarm`], [`Hangup`], [`Interrupt`],
+    /// [`Pipe`], [`Profile`], [`Quit`], [`Terminate`], [`UserDef1`],
+    /// [`UserDef2`], and [`VtAlarm`].
+    ///
+    /// [`SignalSet::termination`]: ../signal/struct.SignalSet.html#method.termination
+    /// [`Alarm`]:     ../signal/enum.Signal.html#variant.Alarm
+    /// [`Hangup`]:    ../signal/enum.Signal.html#variant.Hangup
+    /// [`Interrupt`]: ../signal/enum.Signal.html#variant.Interrupt
+    /// [`Pipe`]:      ../signal/enum.Signal.html#variant.Pipe
+    /// [`Profile`]:   ../signal/enum.Signal.html#variant.Profile
+    /// [`Quit`]:      ../signal/enum.Signal.html#variant.Quit
+    /// [`Terminate`]: ../signal/enum.Signal.html#variant.Terminate
+    /// [`UserDef1`]:  ../signal/enum.Signal.html#variant.UserDef1
+    /// [`UserDef2`]:  ../signal/enum.Signal.html#variant.UserDef2
+    /// [`VtAlarm`]:   ../signal/enum.Signal.html#variant.VtAlarm
+    ///
+    /// # Windows Behavior
+    ///
+    /// On Windows, this corresponds to `CTRL` + `C` and `CTRL` + `BREAK`.
+    #[inline]
+    pub fn register_termination() -> Result<Self, RegisterCtrlCOnceError> {
+        #[cfg(unix)]
+        let inner = crate::SignalSet::termination().register_once()?;
+
+        #[cfg(windows)]
+        let inner = CtrlCOnceInner::register(
+            crate::windows::CTRL_C | crate::windows::CTRL_BREAK,
+        )?;
+
+        Ok(Self::new(inner))
+    }
+
+    /// Returns `self` with `policy` used for handling internal errors instead
+    /// of the [global policy](signal/enum.ErrorPolicy.html#method.global).
+    #[cfg(unix)]
+    #[inline]
+    pub fn with_error_policy(mut self, policy: signal::ErrorPolicy) -> Self {
+        self.inner = self.inner.with_error_policy(policy);
+        self
+    }
+
+    /// Returns whether `self` has been fulfilled, after which every poll
+    /// returns `Ready`.
+    #[inline]
+    pub fn is_fulfilled(&self) -> bool {
+        self.fulfilled
+    }
+
+    /// Takes the internal error that fulfilled `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: signal/enum.ErrorPolicy.html#variant.ResolveWithError
+    #[cfg(unix)]
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.inner.take_error()
+    }
+}
+
+/// A future that is fulfilled once upon receiving `CTRL` + `BREAK`.
+///
+/// After an instance is fulfilled, all subsequent polls will return `Ready`.
+#[cfg(windows)]
+#[cfg_attr(docsrs, doc(cfg(windows)))]
+#[derive(Clone, Debug)]
+pub struct CtrlBreakOnce(CtrlCOnceInner);
+
+#[cfg(windows)]
+impl Future for CtrlBreakOnce {
+    type Output = ();
+
+    #[inline]
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+// SAFETY: This only wraps a cancel-safe future.
+#[cfg(windows)]
+unsafe impl super::CancelSafe for CtrlBreakOnce {}
+
+#[cfg(windows)]
+impl CtrlBreakOnce {
+    /// Registers the `CTRL` + `BREAK` handler.
+    #[inline]
+    pub fn register() -> Result<Self, RegisterCtrlCOnceError> {
+        let inner = CtrlCOnceInner::register(crate::windows::CTRL_BREAK)?;
+        Ok(Self(inner))
+    }
+}
+
+#[cfg(unix)]
+type RegisterCtrlCOnceErrorInner = signal::RegisterOnceError;
+#[cfg(windows)]
+type RegisterCtrlCOnceErrorInner = io::Error;
+
+/// An error returned when registering a [`CtrlCOnce`] fails.
+///
+/// On Unix-like systems, this wraps a [`RegisterOnceError`], which can be
+/// retrieved via [`into_inner`](#method.into_inner).
+///
+/// [`CtrlCOnce`]:         struct.CtrlCOnce.html
+/// [`RegisterOnceError`]: signal/enum.RegisterOnceError.html
+#[derive(Debug)]
+pub struct RegisterCtrlCOnceError(RegisterCtrlCOnceErrorInner);
+
+impl From<RegisterCtrlCOnceErrorInner> for RegisterCtrlCOnceError {
+    #[inline]
+    fn from(error: RegisterCtrlCOnceErrorInner) -> Self {
+        Self(error)
+    }
+}
+
+impl From<RegisterCtrlCOnceError> for io::Error {
+    #[inline]
+    fn from(error: RegisterCtrlCOnceError) -> Self {
+        #[cfg(unix)]
+        return error.0.into();
+
+        #[cfg(windows)]
+        return error.0;
+    }
+}
+
+impl std::fmt::Display for RegisterCtrlCOnceError {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for RegisterCtrlCOnceError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl RegisterCtrlCOnceError {
+    /// Returns the underlying registration error.
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    #[inline]
+    pub fn into_inner(self) -> signal::RegisterOnceError {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use crate::{test_util, Signal};
+
+    #[cfg(unix)]
+    #[test]
+    fn waits_again_after_delivery() {
+        // Other tests handle some of the termination signals.
+        test_util::run_exclusive(|| {
+            for (register, signal) in [
+                (ctrl_c as fn() -> io::Result<CtrlCOnce>, Signal::Interrupt),
+                (terminate, Signal::Terminate),
+            ] {
+                let mut first = register().unwrap();
+                assert!(test_util::poll(&mut first).is_pending());
+
+                signal.raise().unwrap();
+                assert!(test_util::poll(&mut first).is_ready());
+
+                // Registering again waits for the next delivery.
+                let mut second = register().unwrap();
+                assert!(test_util::poll(&mut second).is_pending());
+
+                signal.raise().unwrap();
+                assert!(test_util::poll(&mut second).is_ready());
+            }
+        });
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn waits_again_after_event() {
+        use std::{
+            sync::Arc,
+            task::{Wake, Waker},
+        };
+        use winapi::um::wincon::{CTRL_BREAK_EVENT, CTRL_C_EVENT};
+
+        struct NoopWaker;
+
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let poll = |once: &mut CtrlCOnce| {
+            let waker = Waker::from(Arc::new(NoopWaker));
+            Pin::new(once).poll(&mut Context::from_waker(&waker))
+        };
+
+        for (register, event) in [
+            (ctrl_c as fn() -> io::Result<CtrlCOnce>, CTRL_C_EVENT),
+            (terminate, CTRL_BREAK_EVENT),
+        ] {
+            let mut first = register().unwrap();
+            assert!(poll(&mut first).is_pending());
+
+            // Invoke the handler like the system does, without an actual
+            // console event.
+            unsafe { crate::windows::handler(event) };
+            assert!(poll(&mut first).is_ready());
+
+            // Registering again waits for the next event.
+            let mut second = register().unwrap();
+            assert!(poll(&mut second).is_pending());
+
+            unsafe { crate::windows::handler(event) };
+            assert!(poll(&mut second).is_ready());
+            assert!(poll(&mut first).is_ready());
+        }
+    }
+}
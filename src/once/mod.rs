@@ -1,85 +1,35 @@
 //! Futures that are fulfilled once.
-
-use std::{
-    future::Future,
-    pin::Pin,
-    task::{Context, Poll},
-};
-
-pub mod signal;
+//!
+//! These futures are provided by the `once-core` feature and work on any
+//! executor. Without a reactor, readiness is reported by a single background
+//! thread that watches the pipes of all pending futures. The `once-tokio`
+//! feature instead registers them with the tokio reactor, which avoids the
+//! extra thread.
+//!
+//! [`RawSignalSetOnce`] leaves waiting for readiness entirely to the caller.
+//!
+//! Every future here is [`CancelSafe`], so it can be dropped or raced in a
+//! `select!` without losing signals for other listeners.
+//!
+//! [`RawSignalSetOnce`]: signal/struct.RawSignalSetOnce.html
+//! [`CancelSafe`]:       trait.CancelSafe.html
 
 #[cfg(unix)]
-type CtrlCOnceInner = signal::SignalSetOnce;
-
-/// A future that is fulfilled once upon receiving `CTRL` + `C`.
-///
-/// After an instance is fulfilled, all subsequent polls will return `Ready`.
-#[derive(Debug)]
-pub struct CtrlCOnce(CtrlCOnceInner);
-
-impl Future for CtrlCOnce {
-    type Output = ();
-
-    #[inline]
-    fn poll(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-    ) -> Poll<Self::Output> {
-        Pin::new(&mut self.0).poll(cx)
-    }
-}
-
-impl CtrlCOnce {
-    /// Registers the `CTRL` + `C` handler.
-    #[inline]
-    pub fn register() -> Result<Self, RegisterCtrlCOnceError> {
-        // Register via `Signal` instead of `SignalSet` since it's slightly more
-        // efficient.
-        #[cfg(unix)]
-        let inner: signal::SignalSetOnce =
-            crate::Signal::Interrupt.register_once()?.into();
-
-        Ok(Self(inner))
-    }
-
-    /// Registers the handler for all signals that would otherwise terminate.
-    ///
-    /// # Unix Behavior
-    ///
-    /// On Unix-like systems, this corresponds to: [`Alarm`], [`Hangup`],
-    /// [`Interrupt`], [`Pipe`], [`Quit`], [`Terminate`], [`UserDefined1`], and
-    /// [`UserDefined2`].
-    ///
-    /// [`Alarm`]:        ../unix/enum.Signal.html#variant.Alarm
-    /// [`Hangup`]:       ../unix/enum.Signal.html#variant.Hangup
-    /// [`Interrupt`]:    ../unix/enum.Signal.html#variant.Interrupt
-    /// [`Pipe`]:         ../unix/enum.Signal.html#variant.Pipe
-    /// [`Quit`]:         ../unix/enum.Signal.html#variant.Quit
-    /// [`Terminate`]:    ../unix/enum.Signal.html#variant.Terminate
-    /// [`UserDefined1`]: ../unix/enum.Signal.html#variant.UserDefined1
-    /// [`UserDefined2`]: ../unix/enum.Signal.html#variant.UserDefined2
-    #[inline]
-    pub fn register_termination() -> Result<Self, RegisterCtrlCOnceError> {
-        #[cfg(unix)]
-        let inner = crate::SignalSet::termination().register_once()?;
+pub mod signal;
 
-        Ok(Self(inner))
-    }
-}
+#[cfg(any(docsrs, feature = "once-core"))]
+mod cancel;
+#[cfg(any(docsrs, feature = "once-core"))]
+mod ctrl_c;
 
-#[cfg(unix)]
-type RegisterCtrlCOnceErrorInner = signal::RegisterOnceError;
+#[cfg(any(docsrs, feature = "once-core"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub use cancel::CancelSafe;
 
-/// An error returned when registering a [`Signal`] or [`SignalSet`] fails.
-///
-/// [`Signal`]:    ../../unix/enum.Signal.html
-/// [`SignalSet`]: ../../unix/struct.SignalSet.html
-#[derive(Debug)]
-pub struct RegisterCtrlCOnceError(RegisterCtrlCOnceErrorInner);
+#[cfg(any(docsrs, feature = "once-core"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub use ctrl_c::{ctrl_c, terminate, CtrlCOnce, RegisterCtrlCOnceError};
 
-impl From<RegisterCtrlCOnceErrorInner> for RegisterCtrlCOnceError {
-    #[inline]
-    fn from(error: RegisterCtrlCOnceErrorInner) -> Self {
-        Self(error)
-    }
-}
+#[cfg(all(windows, feature = "once-core"))]
+#[cfg_attr(docsrs, doc(cfg(windows)))]
+pub use ctrl_c::CtrlBreakOnce;
@@ -0,0 +1,145 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::SignalSetOnce;
+use crate::{once::CancelSafe, SignalSet};
+
+/// A future that is fulfilled once a [`Signal`] in a [`SignalSet`] is received
+/// and a window for receiving others has elapsed.
+///
+/// This is created by [`SignalSetOnce::coalesce`].
+///
+/// The future resolves with every signal in the set received by the end of
+/// the window, or with an empty set if an internal error fulfilled it under
+/// [`ErrorPolicy::ResolveWithError`].
+///
+/// [`Signal`]:    ../../signal/enum.Signal.html
+/// [`SignalSet`]: ../../signal/struct.SignalSet.html
+/// [`SignalSetOnce::coalesce`]: struct.SignalSetOnce.html#method.coalesce
+/// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Coalesce {
+    once: SignalSetOnce,
+    window: Duration,
+    state: State,
+}
+
+#[derive(Debug)]
+enum State {
+    /// No signal has been received yet.
+    Waiting,
+    /// A signal was received and the window ends at the deadline, upon which
+    /// the timer thread wakes the stored waker.
+    Coalescing {
+        deadline: Instant,
+        waker: Arc<Mutex<Waker>>,
+    },
+    Done(SignalSet),
+}
+
+impl Future for Coalesce {
+    type Output = SignalSet;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<SignalSet> {
+        let this = &mut *self;
+
+        if let State::Waiting = this.state {
+            match Pin::new(&mut this.once).poll(cx) {
+                Poll::Ready(Some(_)) => {}
+                Poll::Ready(None) => {
+                    this.state = State::Done(SignalSet::new());
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+
+            if let State::Waiting = this.state {
+                this.state = match start_timer(this.window, cx) {
+                    Some((deadline, waker)) => {
+                        State::Coalescing { deadline, waker }
+                    }
+                    None => State::Done(this.caught()),
+                };
+            }
+        }
+
+        match &this.state {
+            State::Coalescing { deadline, waker } => {
+                if Instant::now() < *deadline {
+                    let mut waker =
+                        waker.lock().unwrap_or_else(|error| error.into_inner());
+                    if !waker.will_wake(cx.waker()) {
+                        *waker = cx.waker().clone();
+                    }
+                    return Poll::Pending;
+                }
+                let caught = this.caught();
+                this.state = State::Done(caught);
+                Poll::Ready(caught)
+            }
+            State::Done(caught) => Poll::Ready(*caught),
+            State::Waiting => Poll::Pending,
+        }
+    }
+}
+
+// SAFETY: Caught signals are only read from the process-wide catch counts.
+unsafe impl CancelSafe for Coalesce {}
+
+impl Coalesce {
+    #[inline]
+    pub(super) fn new(once: SignalSetOnce, window: Duration) -> Self {
+        Self {
+            once,
+            window,
+            state: State::Waiting,
+        }
+    }
+
+    /// Returns the window for receiving other signals after the first.
+    #[inline]
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Returns the signals in the set caught so far.
+    fn caught(&self) -> SignalSet {
+        self.once.caught_since_registered()
+    }
+}
+
+/// Spawns a thread that wakes the returned waker once `window` elapses.
+///
+/// Returns `None` if there's no window to wait for or the thread could not be
+/// spawned, in which case the future resolves immediately.
+fn start_timer(
+    window: Duration,
+    cx: &Context,
+) -> Option<(Instant, Arc<Mutex<Waker>>)> {
+    if window == Duration::from_secs(0) {
+        return None;
+    }
+
+    let deadline = Instant::now() + window;
+    let waker = Arc::new(Mutex::new(cx.waker().clone()));
+    let timer_waker = waker.clone();
+
+    thread::Builder::new()
+        .name("asygnal-coalesce".into())
+        .spawn(move || {
+            thread::sleep(window);
+            timer_waker
+                .lock()
+                .unwrap_or_else(|error| error.into_inner())
+                .wake_by_ref();
+        })
+        .ok()?;
+
+    Some((deadline, waker))
+}
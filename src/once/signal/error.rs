@@ -0,0 +1,166 @@
+use std::{error::Error, fmt, io};
+
+use crate::{Signal, SignalSet};
+
+/// An error returned when registering a [`Signal`] or [`SignalSet`] fails.
+///
+/// [`Signal`]:    ../../signal/enum.Signal.html
+/// [`SignalSet`]: ../../signal/struct.SignalSet.html
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RegisterOnceError {
+    /// Signals were already registered.
+    Registered(SignalSet),
+    /// Creating the pipe for waking up the listener failed.
+    Pipe(io::Error),
+    /// Installing the handler for a signal via `sigaction` failed.
+    Sigaction(Signal, io::Error),
+    /// Another I/O error.
+    Io(io::Error),
+}
+
+impl fmt::Display for RegisterOnceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Registered(signals) => {
+                write!(f, "signals already registered: {:?}", signals)
+            }
+            Self::Pipe(_) => f.write_str("failed to create signal pipe"),
+            Self::Sigaction(signal, _) => {
+                write!(f, "failed to install handler for {:?}", signal)
+            }
+            Self::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for RegisterOnceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Registered(_) => None,
+            Self::Pipe(error) | Self::Sigaction(_, error) => Some(error),
+            Self::Io(error) => error.source(),
+        }
+    }
+}
+
+// Recovers the variant of errors marked via `pipe` and `sigaction` below.
+impl From<io::Error> for RegisterOnceError {
+    fn from(error: io::Error) -> Self {
+        let is_marked = error.get_ref().map_or(false, |inner| {
+            inner.is::<PipeError>() || inner.is::<SigactionError>()
+        });
+        if !is_marked {
+            return Self::Io(error);
+        }
+
+        let inner = error.into_inner().expect("marked error without inner");
+        match inner.downcast::<PipeError>() {
+            Ok(inner) => Self::Pipe(inner.0),
+            Err(inner) => match inner.downcast::<SigactionError>() {
+                Ok(inner) => Self::Sigaction(inner.0, inner.1),
+                Err(_) => unreachable!(),
+            },
+        }
+    }
+}
+
+impl From<RegisterOnceError> for io::Error {
+    fn from(error: RegisterOnceError) -> Self {
+        let kind = match &error {
+            RegisterOnceError::Io(_) => match error {
+                RegisterOnceError::Io(error) => return error,
+                _ => unreachable!(),
+            },
+            RegisterOnceError::Pipe(inner)
+            | RegisterOnceError::Sigaction(_, inner) => inner.kind(),
+            RegisterOnceError::Registered(_) => io::ErrorKind::AlreadyExists,
+        };
+        io::Error::new(kind, error)
+    }
+}
+
+impl RegisterOnceError {
+    /// Returns the signal whose handler failed to be installed, if any.
+    #[inline]
+    pub fn signal(&self) -> Option<Signal> {
+        match *self {
+            Self::Sigaction(signal, _) => Some(signal),
+            _ => None,
+        }
+    }
+}
+
+/// Marks `error` as having come from creating a pipe.
+///
+/// The result is still an `io::Error` with the same kind, so that it can pass
+/// through code that is unaware of `RegisterOnceError`.
+pub(crate) fn pipe(error: io::Error) -> io::Error {
+    io::Error::new(error.kind(), PipeError(error))
+}
+
+/// Marks `error` as having come from installing the handler for `signal`.
+///
+/// See `pipe` for more info.
+pub(crate) fn sigaction(signal: Signal, error: io::Error) -> io::Error {
+    io::Error::new(error.kind(), SigactionError(signal, error))
+}
+
+#[derive(Debug)]
+struct PipeError(io::Error);
+
+impl fmt::Display for PipeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to create signal pipe: {}", self.0)
+    }
+}
+
+impl Error for PipeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[derive(Debug)]
+struct SigactionError(Signal, io::Error);
+
+impl fmt::Display for SigactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to install handler for {:?}: {}", self.0, self.1)
+    }
+}
+
+impl Error for SigactionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marked_variants() {
+        let os_error = || io::Error::from_raw_os_error(libc::EINVAL);
+
+        let error = RegisterOnceError::from(pipe(os_error()));
+        assert!(matches!(error, RegisterOnceError::Pipe(_)));
+        assert!(error.source().is_some());
+
+        let marked = sigaction(Signal::Terminate, os_error());
+        assert_eq!(marked.kind(), os_error().kind());
+        let error = RegisterOnceError::from(marked);
+        assert_eq!(error.signal(), Some(Signal::Terminate));
+        match &error {
+            RegisterOnceError::Sigaction(_, inner) => {
+                assert_eq!(inner.raw_os_error(), Some(libc::EINVAL))
+            }
+            error => panic!("unexpected error: {:?}", error),
+        }
+
+        let error = RegisterOnceError::from(os_error());
+        assert!(matches!(error, RegisterOnceError::Io(_)));
+        assert_eq!(io::Error::from(error).raw_os_error(), Some(libc::EINVAL));
+    }
+}
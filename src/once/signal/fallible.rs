@@ -0,0 +1,93 @@
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::{ErrorPolicy, SignalSetOnce};
+use crate::{once::CancelSafe, Signal};
+
+/// A future that is fulfilled once upon receiving a [`Signal`] in a
+/// [`SignalSet`], or upon an internal error.
+///
+/// This is created by [`SignalSetOnce::fallible`]. Unlike [`SignalSetOnce`],
+/// internal errors such as failing to poll the pipe are always returned rather
+/// than handled according to an [`ErrorPolicy`], so they never panic or leave
+/// the future pending forever.
+///
+/// [`Signal`]:        ../../signal/enum.Signal.html
+/// [`SignalSet`]:     ../../signal/struct.SignalSet.html
+/// [`SignalSetOnce`]: struct.SignalSetOnce.html
+/// [`SignalSetOnce::fallible`]: struct.SignalSetOnce.html#method.fallible
+/// [`ErrorPolicy`]:   enum.ErrorPolicy.html
+#[derive(Clone, Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Fallible {
+    once: SignalSetOnce,
+}
+
+impl Future for Fallible {
+    type Output = io::Result<Signal>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        match Pin::new(&mut self.once).poll(cx) {
+            Poll::Ready(Some(signal)) => Poll::Ready(Ok(signal)),
+            Poll::Ready(None) => {
+                Poll::Ready(Err(self.once.take_error().unwrap_or_else(|| {
+                    // The error was already returned by an earlier poll.
+                    io::Error::new(io::ErrorKind::Other, "signal future failed")
+                })))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+// SAFETY: This only wraps `SignalSetOnce`.
+unsafe impl CancelSafe for Fallible {}
+
+impl Fallible {
+    #[inline]
+    pub(super) fn new(once: SignalSetOnce) -> Self {
+        Self {
+            once: once.with_error_policy(ErrorPolicy::ResolveWithError),
+        }
+    }
+
+    /// Returns the inner future.
+    ///
+    /// It keeps using [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn into_inner(self) -> SignalSetOnce {
+        self.once
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn resolves_with_signal() {
+        // Errors don't panic unless opted into.
+        assert_eq!(ErrorPolicy::global(), ErrorPolicy::LogAndPend);
+
+        let signal = Signal::WindowChange;
+
+        test_util::run_exclusive(|| {
+            let mut fallible =
+                SignalSetOnce::register(signal.into()).unwrap().fallible();
+            assert!(test_util::poll(&mut fallible).is_pending());
+
+            signal.raise().unwrap();
+            match test_util::poll(&mut fallible) {
+                Poll::Ready(Ok(caught)) => assert_eq!(caught, signal),
+                other => panic!("unexpected poll result: {:?}", other),
+            }
+        });
+    }
+}
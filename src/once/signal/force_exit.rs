@@ -0,0 +1,252 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    signal::{AtomicSignalSet, Signal, SignalSet},
+    unix::pipe::{self, Writer},
+};
+
+/// The signals that trigger the policy, or empty if it's not installed.
+static SIGNALS: AtomicSignalSet = AtomicSignalSet::new();
+
+/// Whether a signal in `SIGNALS` has been caught since installing.
+static ARMED: AtomicBool = AtomicBool::new(false);
+
+/// The number of milliseconds to wait after the second signal.
+static DELAY_MS: AtomicU64 = AtomicU64::new(0);
+
+/// The writing end of the pipe read by the watchdog thread, or -1 if it has
+/// not been spawned.
+static WATCHDOG_FD: AtomicI32 = AtomicI32::new(-1);
+
+/// Serializes spawning the watchdog thread.
+static WATCHDOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// A process-wide policy that terminates the process if it is still running
+/// some time after receiving a second termination signal.
+///
+/// Upon the second signal, the default action is restored and the signal is
+/// raised again, which terminates the process. This is a safety net for when
+/// graceful shutdown code misbehaves, and applies to every signal handled by
+/// this crate regardless of how it was registered.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::ForceExit;
+/// use std::time::Duration;
+///
+/// ForceExit::after(Duration::from_secs(3)).install()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ForceExit {
+    signals: SignalSet,
+    delay: Duration,
+}
+
+impl ForceExit {
+    /// Creates a policy that terminates the process once `delay` has elapsed
+    /// after a second termination signal.
+    ///
+    /// By default, the policy is triggered by [`interrupt`], [`quit`], and
+    /// [`terminate`]. Other signals in [`SignalSet::termination`], such as
+    /// [`hangup`], are commonly handled repeatedly and are thus left out.
+    ///
+    /// [`interrupt`]: ../../signal/struct.SignalSet.html#method.interrupt
+    /// [`quit`]:      ../../signal/struct.SignalSet.html#method.quit
+    /// [`terminate`]: ../../signal/struct.SignalSet.html#method.terminate
+    /// [`hangup`]:    ../../signal/struct.SignalSet.html#method.hangup
+    /// [`SignalSet::termination`]: ../../signal/struct.SignalSet.html#method.termination
+    #[inline]
+    pub fn after(delay: Duration) -> Self {
+        let signals = SignalSet::from_signal(Signal::Interrupt)
+            .with(Signal::Quit)
+            .with(Signal::Terminate);
+        Self { signals, delay }
+    }
+
+    /// Creates a policy that terminates the process immediately upon a second
+    /// termination signal.
+    #[inline]
+    pub fn immediately() -> Self {
+        Self::after(Duration::from_secs(0))
+    }
+
+    /// Returns `self` triggered by `signals` instead of the defaults.
+    #[inline]
+    pub fn with_signals(mut self, signals: SignalSet) -> Self {
+        self.signals = signals;
+        self
+    }
+
+    /// Installs `self` as the process-wide policy, replacing any previous one.
+    ///
+    /// This spawns a watchdog thread the first time a non-zero delay is used.
+    ///
+    /// The policy only applies to signals registered through this crate.
+    pub fn install(self) -> io::Result<()> {
+        if self.delay != Duration::from_secs(0) {
+            spawn_watchdog()?;
+        }
+
+        let delay_ms = self.delay.as_millis().try_into().unwrap_or(u64::MAX);
+        DELAY_MS.store(delay_ms, Ordering::SeqCst);
+        ARMED.store(false, Ordering::SeqCst);
+        SIGNALS.store(self.signals, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Installs `self` like [`install`](#method.install), unless a policy is
+    /// already installed.
+    pub(crate) fn install_if_absent(self) -> io::Result<()> {
+        if installed().is_empty() {
+            self.install()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Uninstalls the process-wide policy, if any.
+    #[inline]
+    pub fn uninstall() {
+        SIGNALS.store(SignalSet::new(), Ordering::SeqCst);
+    }
+}
+
+/// Returns the signals that trigger the installed policy, or empty if none is.
+#[inline]
+pub(crate) fn installed() -> SignalSet {
+    SIGNALS.load(Ordering::SeqCst)
+}
+
+/// Called from within the signal handler.
+///
+/// It is imperative that this function is signal-safe.
+pub(super) fn on_signal(signal: Signal) {
+    if !SIGNALS.load(Ordering::SeqCst).contains(signal) {
+        return;
+    }
+
+    if !ARMED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    if DELAY_MS.load(Ordering::SeqCst) == 0 {
+        terminate(signal);
+    } else {
+        let fd = WATCHDOG_FD.load(Ordering::SeqCst);
+        if fd >= 0 {
+            let buf = [signal as u8];
+            unsafe {
+                libc::write(fd, buf.as_ptr() as *const _, buf.len());
+            }
+        }
+    }
+}
+
+/// Restores the default action for `signal` and raises it.
+///
+/// If called from within the handler for `signal`, the signal is delivered
+/// once the handler returns.
+pub(crate) fn terminate(signal: Signal) {
+    let raw_signal = signal.into_raw();
+    unsafe {
+        libc::signal(raw_signal, libc::SIG_DFL);
+        libc::kill(libc::getpid(), raw_signal);
+    }
+}
+
+fn spawn_watchdog() -> io::Result<()> {
+    let _lock = WATCHDOG_LOCK
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+
+    if WATCHDOG_FD.load(Ordering::SeqCst) >= 0 {
+        return Ok(());
+    }
+
+    let (reader, Writer(writer)) = pipe::pipe()?;
+
+    thread::Builder::new()
+        .name("asygnal-force-exit".into())
+        .spawn(move || loop {
+            if reader.wait_readable(None).is_err() {
+                return;
+            }
+
+            let mut buf = [0u8; 1];
+            let n =
+                unsafe { libc::read(reader.0, buf.as_mut_ptr() as *mut _, 1) };
+            let signal = match Signal::from_u8(buf[0]) {
+                Some(signal) if n == 1 => signal,
+                _ => continue,
+            };
+
+            let delay_ms = DELAY_MS.load(Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(delay_ms));
+
+            // The policy may have been uninstalled in the meantime.
+            if SIGNALS.load(Ordering::SeqCst).contains(signal) {
+                terminate(signal);
+            }
+        })?;
+
+    WATCHDOG_FD.store(writer, Ordering::SeqCst);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{once::signal::SignalOnce, test_util};
+    use std::{env, os::unix::process::ExitStatusExt, process::Command};
+
+    /// Set for the copy of the test binary that installs the policy, since it
+    /// terminates the process it's installed in.
+    const CHILD_VAR: &str = "ASYGNAL_FORCE_EXIT_CHILD";
+
+    #[test]
+    fn terminates_on_second_signal() {
+        let _children = test_util::children();
+
+        let status = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "once::signal::force_exit::tests::child"])
+            .args(["--ignored", "--test-threads=1"])
+            .env(CHILD_VAR, "1")
+            .output()
+            .unwrap()
+            .status;
+
+        assert_eq!(status.signal(), Some(libc::SIGUSR1));
+    }
+
+    #[test]
+    #[ignore = "run by `terminates_on_second_signal`"]
+    fn child() {
+        if env::var_os(CHILD_VAR).is_none() {
+            return;
+        }
+
+        let signal = Signal::UserDef1;
+        test_util::run(|| {
+            let _once = SignalOnce::register(signal).unwrap();
+            ForceExit::immediately()
+                .with_signals(signal.into())
+                .install()
+                .unwrap();
+
+            // The first signal is handled as usual.
+            signal.raise().unwrap();
+            signal.raise().unwrap();
+        });
+        unreachable!("the second signal should terminate the process");
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::{ErrorPolicy, SignalSetOnce};
+use crate::{once::CancelSafe, Signal, SignalSet};
+
+/// A future that registers a handler for a [`SignalSet`] upon first being
+/// polled and is then fulfilled once upon receiving a [`Signal`] in it.
+///
+/// This is returned when awaiting a [`Signal`] or [`SignalSet`] directly:
+///
+/// ```no_run
+/// use asygnal::SignalSet;
+///
+/// # async fn run() {
+/// if let Some(signal) = SignalSet::termination().await {
+///     println!("received {:?}", signal);
+/// }
+/// # }
+/// ```
+///
+/// Like [`SignalSetOnce`], it resolves with the signal that was received, or
+/// with `None` if an error fulfilled it.
+///
+/// If registration fails, the error is handled according to the
+/// [`ErrorPolicy`].
+///
+/// [`Signal`]:      ../../signal/enum.Signal.html
+/// [`SignalSet`]:   ../../signal/struct.SignalSet.html
+/// [`ErrorPolicy`]: enum.ErrorPolicy.html
+/// [`SignalSetOnce`]: struct.SignalSetOnce.html
+#[derive(Debug)]
+pub struct SignalSetFuture {
+    state: State,
+    policy: Option<ErrorPolicy>,
+    error: Option<io::Error>,
+}
+
+#[derive(Debug)]
+enum State {
+    Unregistered(SignalSet),
+    // Boxed since it holds a catch count per signal.
+    Registered(Box<SignalSetOnce>),
+    /// Registration failed and the policy chose to resolve.
+    Resolved,
+    /// Registration failed and the policy chose to stay pending.
+    Stalled,
+}
+
+impl From<SignalSet> for SignalSetFuture {
+    #[inline]
+    fn from(signals: SignalSet) -> Self {
+        Self::new(signals)
+    }
+}
+
+impl Future for SignalSetFuture {
+    type Output = Option<Signal>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        if let State::Unregistered(signals) = this.state {
+            match SignalSetOnce::register(signals) {
+                Ok(once) => {
+                    this.state =
+                        State::Registered(Box::new(match this.policy {
+                            Some(policy) => once.with_error_policy(policy),
+                            None => once,
+                        }));
+                }
+                Err(error) => {
+                    let error = io::Error::from(error);
+                    let policy =
+                        this.policy.unwrap_or_else(ErrorPolicy::global);
+                    let poll = policy.handle(error, &mut this.error);
+                    this.state = match poll {
+                        Poll::Ready(()) => State::Resolved,
+                        Poll::Pending => State::Stalled,
+                    };
+                    return poll.map(|()| None);
+                }
+            }
+        }
+
+        match &mut this.state {
+            State::Registered(once) => Pin::new(&mut **once).poll(cx),
+            State::Resolved => Poll::Ready(None),
+            State::Unregistered(_) | State::Stalled => Poll::Pending,
+        }
+    }
+}
+
+// SAFETY: This only wraps `SignalSetOnce`.
+unsafe impl CancelSafe for SignalSetFuture {}
+
+impl SignalSetFuture {
+    /// Creates a future that registers a handler for `signals` upon first
+    /// being polled.
+    #[inline]
+    pub const fn new(signals: SignalSet) -> Self {
+        Self {
+            state: State::Unregistered(signals),
+            policy: None,
+            error: None,
+        }
+    }
+
+    /// Returns `self` with `policy` used for handling registration and
+    /// internal errors instead of the
+    /// [global policy](enum.ErrorPolicy.html#method.global).
+    #[inline]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = Some(policy);
+        if let State::Registered(once) = self.state {
+            self.state =
+                State::Registered(Box::new(once.with_error_policy(policy)));
+        }
+        self
+    }
+
+    /// Takes the registration or internal error that fulfilled `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        match &mut self.state {
+            State::Registered(once) => once.take_error(),
+            _ => self.error.take(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "once"))]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::future::IntoFuture;
+
+    #[test]
+    fn registers_on_first_poll() {
+        let signals = Signal::WindowChange | Signal::Urgent;
+
+        test_util::run_exclusive(|| {
+            let mut future = signals.into_future();
+            assert!(matches!(future.state, State::Unregistered(_)));
+
+            assert!(test_util::poll(&mut future).is_pending());
+            assert!(matches!(future.state, State::Registered(_)));
+
+            Signal::Urgent.raise().unwrap();
+            assert_eq!(
+                test_util::poll(&mut future),
+                Poll::Ready(Some(Signal::Urgent))
+            );
+        });
+    }
+}
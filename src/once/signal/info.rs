@@ -0,0 +1,266 @@
+use libc::{c_int, pid_t, uid_t};
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::atomic::Ordering,
+    task::{Context, Poll},
+    time::{Duration, SystemTime},
+};
+
+use super::{table::Table, Driver, RegisterOnceError, SignalSetOnce};
+use crate::{once::CancelSafe, Signal, SignalSet};
+
+/// Details about a received [`Signal`], as reported by `siginfo_t`.
+///
+/// These are only recorded for signals registered through an opt-in
+/// constructor, such as [`SignalInfoOnce::register`], which installs the
+/// handler with `SA_SIGINFO`. Since deliveries are coalesced, this describes
+/// the most recent delivery of the signal at the time it was observed.
+///
+/// # Wall-Clock Time
+///
+/// Registering via [`SignalInfoOnce::register_timestamped`] additionally
+/// records when each delivery was handled, as read from `CLOCK_REALTIME` by
+/// the handler itself. This suits audit logs that show absolute times for
+/// operator signals, but comes with trade-offs:
+///
+/// - The clock can jump backwards or forwards, such as when adjusted by NTP or
+///   an administrator, so it must not be used for ordering or measuring
+///   durations.
+///
+/// - Reading it adds a `clock_gettime` call to every delivery of the signal,
+///   which is async-signal-safe but not free, for as long as any listener of
+///   the signal remains.
+///
+/// - Like the other details, it describes the most recent delivery, not
+///   necessarily the one that woke the listener.
+///
+/// [`Signal`]: ../../signal/enum.Signal.html
+/// [`SignalInfoOnce::register`]: struct.SignalInfoOnce.html#method.register
+/// [`SignalInfoOnce::register_timestamped`]: struct.SignalInfoOnce.html#method.register_timestamped
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SignalInfo {
+    signal: Signal,
+    pid: pid_t,
+    uid: uid_t,
+    code: c_int,
+    /// Nanoseconds since the Unix epoch, or 0 if not recorded.
+    time: u64,
+}
+
+impl SignalInfo {
+    /// Returns the details recorded for the last delivery of `signal`.
+    pub(crate) fn last(signal: Signal) -> Self {
+        let entry = Table::global().entry(signal);
+        Self {
+            signal,
+            pid: entry.pid.load(Ordering::SeqCst),
+            uid: entry.uid.load(Ordering::SeqCst),
+            code: entry.code.load(Ordering::SeqCst),
+            time: entry.time.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Returns the signal that was received.
+    #[inline]
+    pub fn signal(&self) -> Signal {
+        self.signal
+    }
+
+    /// Returns the `si_pid` field, which is the ID of the sending process.
+    ///
+    /// This is only meaningful if the signal was sent by a process, such as
+    /// via `kill` or `sigqueue`, or for [`Child`], in which case it is the ID
+    /// of the child. It is 0 if the signal was sent by the kernel.
+    ///
+    /// [`Child`]: ../../signal/enum.Signal.html#variant.Child
+    #[inline]
+    pub fn pid(&self) -> pid_t {
+        self.pid
+    }
+
+    /// Returns the `si_uid` field, which is the real user ID of the sending
+    /// process.
+    ///
+    /// This is meaningful under the same conditions as [`pid`](#method.pid).
+    #[inline]
+    pub fn uid(&self) -> uid_t {
+        self.uid
+    }
+
+    /// Returns the `si_code` field, which describes why the signal was sent,
+    /// such as `SI_USER` for `kill`.
+    #[inline]
+    pub fn code(&self) -> c_int {
+        self.code
+    }
+
+    /// Returns the wall-clock time at which the signal was handled, if
+    /// recorded.
+    ///
+    /// See [the type documentation](#wall-clock-time) for the trade-offs of
+    /// this time.
+    #[inline]
+    pub fn wall_time(&self) -> Option<SystemTime> {
+        match self.time {
+            0 => None,
+            nanos => Some(SystemTime::UNIX_EPOCH + Duration::from_nanos(nanos)),
+        }
+    }
+}
+
+/// A future that is fulfilled once upon receiving a [`Signal`] in a
+/// [`SignalSet`], resolving with details about who sent it.
+///
+/// This behaves like [`SignalSetOnce`], except that handlers are installed with
+/// `SA_SIGINFO` for recording each sender. The handler of a signal keeps doing
+/// so until no listeners for it remain.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{once::signal::SignalInfoOnce, SignalSet};
+///
+/// # async fn example() -> Result<(), asygnal::once::signal::RegisterOnceError> {
+/// let signals = SignalSet::new().user_def_1();
+///
+/// if let Some(info) = SignalInfoOnce::register(signals)?.await {
+///     println!("{} sent by PID {}", info.signal(), info.pid());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Signal`]:        ../../signal/enum.Signal.html
+/// [`SignalSet`]:     ../../signal/struct.SignalSet.html
+/// [`SignalSetOnce`]: struct.SignalSetOnce.html
+#[derive(Debug)]
+pub struct SignalInfoOnce {
+    inner: SignalSetOnce,
+    /// The details that fulfilled `self`, if any.
+    info: Option<SignalInfo>,
+}
+
+impl Future for SignalInfoOnce {
+    type Output = Option<SignalInfo>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if let Some(info) = self.info {
+            return Poll::Ready(Some(info));
+        }
+
+        let signal = match Pin::new(&mut self.inner).poll(cx) {
+            Poll::Ready(signal) => signal,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        self.info = signal.map(SignalInfo::last);
+        Poll::Ready(self.info)
+    }
+}
+
+// SAFETY: This only wraps `SignalSetOnce`.
+unsafe impl CancelSafe for SignalInfoOnce {}
+
+impl SignalInfoOnce {
+    /// Registers a handler for `signals` that records each sender and will
+    /// only be fulfilled once.
+    pub fn register(signals: SignalSet) -> Result<Self, RegisterOnceError> {
+        let counts = Table::global().counts(signals);
+        let driver = Driver::register_with_info(signals)?;
+        Ok(Self {
+            inner: SignalSetOnce::from_driver(signals, counts, driver),
+            info: None,
+        })
+    }
+
+    /// Registers a handler for `signals` like [`register`], also recording the
+    /// wall-clock time of each delivery.
+    ///
+    /// See [`SignalInfo`](struct.SignalInfo.html#wall-clock-time) for the
+    /// trade-offs of this time.
+    ///
+    /// [`register`]: #method.register
+    pub fn register_timestamped(
+        signals: SignalSet,
+    ) -> Result<Self, RegisterOnceError> {
+        let once = Self::register(signals)?;
+        super::record_wall_clock(signals);
+        Ok(once)
+    }
+
+    /// Returns the signals that `self` is registered for.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.inner.signals()
+    }
+
+    /// Returns the details that fulfilled `self`, if it has been.
+    #[inline]
+    pub fn info(&self) -> Option<SignalInfo> {
+        self.info
+    }
+
+    /// Takes the internal error that fulfilled `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.inner.take_error()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn records_sender() {
+        let signal = Signal::Urgent;
+
+        test_util::run_exclusive(|| {
+            let before = SystemTime::now();
+            let mut once =
+                SignalInfoOnce::register_timestamped(signal.into()).unwrap();
+            signal.raise().unwrap();
+
+            let info = match test_util::poll(&mut once) {
+                Poll::Ready(Some(info)) => info,
+                other => panic!("unexpected poll result: {:?}", other),
+            };
+            assert_eq!(info.signal(), signal);
+            assert_eq!(info.pid(), unsafe { libc::getpid() });
+            assert_eq!(info.uid(), unsafe { libc::getuid() });
+            assert!(info.wall_time().unwrap() >= before);
+        });
+    }
+
+    #[test]
+    fn wall_time_until_released() {
+        // The profiler tests also handle this signal.
+        let signal = Signal::Profile;
+
+        test_util::run_exclusive(|| {
+            let mut once =
+                SignalInfoOnce::register_timestamped(signal.into()).unwrap();
+            signal.raise().unwrap();
+            match test_util::poll(&mut once) {
+                Poll::Ready(Some(info)) => assert!(info.wall_time().is_some()),
+                other => panic!("unexpected poll result: {:?}", other),
+            }
+            drop(once);
+
+            // Releasing the handler stops recording the time.
+            let mut once = SignalInfoOnce::register(signal.into()).unwrap();
+            signal.raise().unwrap();
+            match test_util::poll(&mut once) {
+                Poll::Ready(Some(info)) => assert_eq!(info.wall_time(), None),
+                other => panic!("unexpected poll result: {:?}", other),
+            }
+        });
+    }
+}
@@ -0,0 +1,479 @@
+use std::{
+    future::Future,
+    io, mem,
+    pin::Pin,
+    ptr,
+    sync::{
+        atomic::{AtomicI32, AtomicPtr, AtomicUsize, Ordering},
+        Mutex,
+    },
+    task::{Context, Poll},
+    thread,
+};
+
+use super::{Driver, ErrorPolicy, RegisterOnceError, SignalOnce};
+use crate::{once::CancelSafe, unix::pipe::Writer, unix::SignalKind};
+
+/// Raw signal numbers are below this on every supported target.
+const MAX_RAW: usize = 128;
+
+/// The head of the list of listeners for signals not modeled by `Signal`,
+/// which are separate from those in the table since it's indexed by `Signal`.
+///
+/// Slots are never freed so that the signal handler can safely traverse the
+/// list at any time. Instead, unused slots are reused.
+static SLOTS: AtomicPtr<Slot> = AtomicPtr::new(ptr::null_mut());
+
+/// The number of handlers currently waking up listeners.
+static WAKING: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of listeners for each signal. Our handler is released once this
+/// reaches 0.
+#[allow(clippy::declare_interior_mutable_const)]
+static LISTENERS: [AtomicUsize; MAX_RAW] = {
+    const ZERO: AtomicUsize = AtomicUsize::new(0);
+    [ZERO; MAX_RAW]
+};
+
+/// The number of times each signal has been caught, wrapping on overflow.
+#[allow(clippy::declare_interior_mutable_const)]
+static COUNTS: [AtomicUsize; MAX_RAW] = {
+    const ZERO: AtomicUsize = AtomicUsize::new(0);
+    [ZERO; MAX_RAW]
+};
+
+/// The actions that were in place before installing our handler, used for
+/// releasing signals. This must never be accessed from a signal handler.
+static PREVIOUS: Mutex<[Option<libc::sigaction>; MAX_RAW]> =
+    Mutex::new([None; MAX_RAW]);
+
+/// A listener, identified by the writing end of its pipe.
+struct Slot {
+    /// The file descriptor for the writing end of the pipe, or -1 if unused.
+    writer_fd: AtomicI32,
+    /// The raw signal that wakes up the listener.
+    raw_signal: AtomicI32,
+    /// The next slot in the list, which never changes once set.
+    next: *const Slot,
+}
+
+fn slots() -> impl Iterator<Item = &'static Slot> {
+    let head = SLOTS.load(Ordering::SeqCst) as *const Slot;
+
+    // SAFETY: Slots are leaked and thus live for the rest of the program.
+    std::iter::successors(unsafe { head.as_ref() }, |slot| unsafe {
+        slot.next.as_ref()
+    })
+}
+
+/// Reuses an unused slot for `writer` or allocates a new one.
+fn claim_slot(writer: Writer) -> &'static Slot {
+    for slot in slots() {
+        let claimed = slot.writer_fd.compare_exchange(
+            -1,
+            writer.0,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+        if claimed.is_ok() {
+            return slot;
+        }
+    }
+
+    let slot: &'static mut Slot = Box::leak(Box::new(Slot {
+        writer_fd: AtomicI32::new(writer.0),
+        raw_signal: AtomicI32::new(0),
+        next: ptr::null(),
+    }));
+
+    let mut head = SLOTS.load(Ordering::SeqCst);
+    loop {
+        slot.next = head;
+        match SLOTS.compare_exchange_weak(
+            head,
+            slot,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        ) {
+            Ok(_) => return slot,
+            Err(current) => head = current,
+        }
+    }
+}
+
+/// Returns the index of `kind` into the tables above, if within bounds.
+fn index(kind: SignalKind) -> Option<usize> {
+    let raw_signal = kind.into_raw();
+    usize::try_from(raw_signal)
+        .ok()
+        .filter(|&index| index != 0 && index < MAX_RAW)
+}
+
+extern "C" fn signal_handler(raw_signal: libc::c_int) {
+    let index = match index(SignalKind::from_raw(raw_signal)) {
+        Some(index) => index,
+        None => return,
+    };
+
+    COUNTS[index].fetch_add(1, Ordering::SeqCst);
+
+    WAKING.fetch_add(1, Ordering::SeqCst);
+    for slot in slots() {
+        let fd = slot.writer_fd.load(Ordering::SeqCst);
+        if fd >= 0 && slot.raw_signal.load(Ordering::SeqCst) == raw_signal {
+            Writer(fd).wake();
+        }
+    }
+    WAKING.fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Installs the handler for `kind` if it has no listeners yet and makes
+/// `writer` be woken upon receiving it.
+fn register(kind: SignalKind, writer: Writer) -> io::Result<()> {
+    let index = match index(kind) {
+        Some(index) => index,
+        None => return Err(io::ErrorKind::InvalidInput.into()),
+    };
+
+    let _lock = super::lock_registry();
+
+    if LISTENERS[index].load(Ordering::SeqCst) == 0 {
+        let mut action: libc::sigaction = unsafe { mem::zeroed() };
+        action.sa_sigaction = signal_handler as *const () as usize;
+        action.sa_flags = libc::SA_RESTART;
+        unsafe { libc::sigemptyset(&mut action.sa_mask) };
+
+        let mut old: libc::sigaction = unsafe { mem::zeroed() };
+        if unsafe { libc::sigaction(kind.into_raw(), &action, &mut old) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Signals without listeners have our handler released, so `old` is
+        // never our own.
+        PREVIOUS.lock().unwrap_or_else(|error| error.into_inner())[index] =
+            Some(old);
+    }
+
+    LISTENERS[index].fetch_add(1, Ordering::SeqCst);
+    claim_slot(writer)
+        .raw_signal
+        .store(kind.into_raw(), Ordering::SeqCst);
+    Ok(())
+}
+
+/// Stops waking up `writer`, releasing its signal if it no longer has any
+/// listeners.
+fn unregister(writer: Writer) -> io::Result<()> {
+    let _lock = super::lock_registry();
+
+    let slot = match slots()
+        .find(|slot| slot.writer_fd.load(Ordering::SeqCst) == writer.0)
+    {
+        Some(slot) => slot,
+        None => return Ok(()),
+    };
+
+    let kind = SignalKind::from_raw(slot.raw_signal.swap(0, Ordering::SeqCst));
+    slot.writer_fd.store(-1, Ordering::SeqCst);
+
+    // Wait for handlers that may have loaded the file descriptor, so that the
+    // caller can safely close it.
+    while WAKING.load(Ordering::SeqCst) != 0 {
+        thread::yield_now();
+    }
+
+    let index = match index(kind) {
+        Some(index) => index,
+        None => return Ok(()),
+    };
+    if LISTENERS[index].fetch_sub(1, Ordering::SeqCst) != 1 {
+        return Ok(());
+    }
+
+    let previous = PREVIOUS.lock().unwrap_or_else(|error| error.into_inner())
+        [index]
+        .take();
+    match previous {
+        Some(action)
+            if unsafe {
+                libc::sigaction(kind.into_raw(), &action, ptr::null_mut())
+            } != 0 =>
+        {
+            Err(io::Error::last_os_error())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A future that is fulfilled once upon receiving a raw [`SignalKind`].
+///
+/// Kinds modeled by [`Signal`] are registered like [`SignalOnce`]. Others are
+/// handled separately, and so are not reflected by the rest of this crate,
+/// such as [`pause`].
+///
+/// After an instance is fulfilled, all subsequent polls will return [`Ready`].
+///
+/// Dropping an instance unregisters it, restoring the previous action of its
+/// signal if it has no other listeners.
+///
+/// [`SignalKind`]: ../../unix/struct.SignalKind.html
+/// [`Signal`]:     ../../signal/enum.Signal.html
+/// [`SignalOnce`]: struct.SignalOnce.html
+/// [`pause`]:      fn.pause.html
+/// [`Ready`]: https://doc.rust-lang.org/std/task/enum.Poll.html#variant.Ready
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct SignalKindOnce {
+    kind: SignalKind,
+    inner: Inner,
+}
+
+#[derive(Debug)]
+enum Inner {
+    Signal(SignalOnce),
+    Raw(RawKind),
+}
+
+/// A registration in the separate table above.
+#[derive(Debug)]
+struct RawKind {
+    driver: Driver,
+    /// The catch count of the signal when `self` was registered.
+    count: usize,
+    /// Whether `self` has been fulfilled.
+    fulfilled: bool,
+}
+
+impl Drop for RawKind {
+    fn drop(&mut self) {
+        // There's no reasonable way to report failing to restore an action.
+        // This happens before `driver` closes the pipe.
+        let _ = unregister(self.driver.writer);
+    }
+}
+
+impl Future for SignalKindOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let index = index(self.kind);
+        let raw = match &mut self.inner {
+            Inner::Signal(once) => return Pin::new(once).poll(cx),
+            Inner::Raw(raw) => raw,
+        };
+
+        if raw.fulfilled {
+            return Poll::Ready(());
+        }
+
+        // Registering succeeded, so the index is within bounds.
+        let counts = &COUNTS[index.unwrap_or_default()];
+        let count = raw.count;
+        let caught = || counts.load(Ordering::SeqCst) != count;
+
+        let poll = if caught() {
+            Poll::Ready(())
+        } else {
+            match raw.driver.poll_readable(cx) {
+                Poll::Ready(true) => {
+                    // Clear readiness before checking again, so that a signal
+                    // caught in between wakes up the task.
+                    if raw.driver.park(cx).is_ready() || caught() {
+                        Poll::Ready(())
+                    } else {
+                        Poll::Pending
+                    }
+                }
+                Poll::Ready(false) => Poll::Ready(()),
+                Poll::Pending => Poll::Pending,
+            }
+        };
+
+        raw.fulfilled = poll.is_ready();
+        poll
+    }
+}
+
+// SAFETY: Caught signals are only read from the process-wide catch counts.
+unsafe impl CancelSafe for SignalKindOnce {}
+
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::future::FusedFuture for SignalKindOnce {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.is_fulfilled()
+    }
+}
+
+impl SignalKindOnce {
+    /// Registers a handler for `kind` that will only be fulfilled once.
+    ///
+    /// This fails with [`io::ErrorKind::InvalidInput`] if `kind` is out of the
+    /// range of signal numbers, and with the error of `sigaction` if it can't
+    /// be handled.
+    ///
+    /// [`io::ErrorKind::InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn register(kind: SignalKind) -> Result<Self, RegisterOnceError> {
+        if let Some(signal) = kind.signal() {
+            return Ok(Self {
+                kind,
+                inner: Inner::Signal(SignalOnce::register(signal)?),
+            });
+        }
+
+        // Only deliveries after registering fulfill `self`.
+        let count = match index(kind) {
+            Some(index) => COUNTS[index].load(Ordering::SeqCst),
+            None => {
+                return Err(io::Error::from(io::ErrorKind::InvalidInput).into())
+            }
+        };
+
+        // Dropping the driver upon failure closes the pipe.
+        let driver = Driver::new()?;
+        register(kind, driver.writer)?;
+
+        Ok(Self {
+            kind,
+            inner: Inner::Raw(RawKind {
+                driver,
+                count,
+                fulfilled: false,
+            }),
+        })
+    }
+
+    /// Returns the kind of signal that `self` is registered for.
+    #[inline]
+    pub fn kind(&self) -> SignalKind {
+        self.kind
+    }
+
+    /// Returns `self` with `policy` used for handling internal errors instead
+    /// of the [global policy](enum.ErrorPolicy.html#method.global).
+    #[inline]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        match &mut self.inner {
+            Inner::Signal(once) => once.policy = Some(policy),
+            Inner::Raw(raw) => raw.driver.policy = Some(policy),
+        }
+        self
+    }
+
+    /// Returns whether `self` has been fulfilled, after which every poll
+    /// returns [`Ready`].
+    ///
+    /// [`Ready`]: https://doc.rust-lang.org/std/task/enum.Poll.html#variant.Ready
+    #[inline]
+    pub fn is_fulfilled(&self) -> bool {
+        match &self.inner {
+            Inner::Signal(once) => once.is_fulfilled(),
+            Inner::Raw(raw) => raw.fulfilled,
+        }
+    }
+
+    /// Takes the internal error that fulfilled `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        match &mut self.inner {
+            Inner::Signal(once) => once.take_error(),
+            Inner::Raw(raw) => raw.driver.error.take(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Signal;
+    use std::{
+        sync::Arc,
+        task::{Wake, Waker},
+    };
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    /// Polling requires a reactor when using tokio.
+    fn in_reactor(f: impl FnOnce()) {
+        #[cfg(feature = "once-tokio")]
+        {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_io()
+                .build()
+                .unwrap();
+            let _guard = runtime.enter();
+            f();
+        }
+
+        #[cfg(not(feature = "once-tokio"))]
+        f();
+    }
+
+    fn poll(once: &mut SignalKindOnce) -> Poll<()> {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        Pin::new(once).poll(&mut Context::from_waker(&waker))
+    }
+
+    #[test]
+    fn rejects_out_of_range() {
+        for raw_signal in [0, -1, MAX_RAW as libc::c_int] {
+            let error =
+                SignalKindOnce::register(SignalKind::from_raw(raw_signal))
+                    .unwrap_err();
+            match error {
+                RegisterOnceError::Io(error) => {
+                    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+                }
+                error => panic!("unexpected error: {error}"),
+            }
+        }
+    }
+
+    #[test]
+    fn delegates_modeled_signals() {
+        in_reactor(|| {
+            let once =
+                SignalKindOnce::register(Signal::WindowChange.into()).unwrap();
+            assert_eq!(once.kind().signal(), Some(Signal::WindowChange));
+            assert!(matches!(once.inner, Inner::Signal(_)));
+        });
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn raw_signal_delivery() {
+        let kind = SignalKind::from_raw(libc::SIGPWR);
+        assert_eq!(kind.signal(), None);
+
+        in_reactor(|| {
+            let mut once = kind.register_once().unwrap();
+            assert!(poll(&mut once).is_pending());
+
+            unsafe { libc::raise(kind.into_raw()) };
+            assert!(poll(&mut once).is_ready());
+            assert!(once.is_fulfilled());
+
+            // Only deliveries after registering fulfill the next instance.
+            let mut again = kind.register_once().unwrap();
+            assert!(poll(&mut again).is_pending());
+            drop(once);
+            drop(again);
+
+            // The default action is restored once there are no listeners.
+            let mut action: libc::sigaction = unsafe { mem::zeroed() };
+            unsafe {
+                libc::sigaction(kind.into_raw(), ptr::null(), &mut action)
+            };
+            assert_eq!(action.sa_sigaction, libc::SIG_DFL);
+        });
+    }
+}
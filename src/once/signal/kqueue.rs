@@ -0,0 +1,328 @@
+use std::{
+    future::Future,
+    io, mem,
+    os::unix::io::{AsRawFd, RawFd},
+    pin::Pin,
+    ptr,
+    task::{Context, Poll},
+};
+
+use super::{readiness::Readiness, ErrorPolicy};
+use crate::{once::CancelSafe, unix::pipe, Signal, SignalSet};
+
+/// A future that is fulfilled once upon receiving a [`Signal`] in a
+/// [`SignalSet`], observed through a kqueue `EVFILT_SIGNAL` filter rather than
+/// a signal handler.
+///
+/// Because no handler is installed, this coexists with other code that
+/// installs its own handlers for the same signals, all of which keep running.
+/// However, kqueue only observes signals and does not prevent their action. So
+/// that receiving a signal does not terminate or stop the process, signals
+/// whose action is the default one are set to be ignored until `self` is
+/// dropped.
+///
+/// Like [`SignalSetOnce`], the future resolves with the lowest signal received,
+/// or with `None` if an internal error fulfilled it under
+/// [`ErrorPolicy::ResolveWithError`].
+///
+/// Signals received through kqueue are not reflected by the rest of this
+/// crate, such as [`poll_caught`] or [`pause`].
+///
+/// [`Signal`]:        ../../signal/enum.Signal.html
+/// [`SignalSet`]:     ../../signal/struct.SignalSet.html
+/// [`SignalSetOnce`]: struct.SignalSetOnce.html
+/// [`poll_caught`]:   fn.poll_caught.html
+/// [`pause`]:         fn.pause.html
+/// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
+#[derive(Debug)]
+pub struct KqueueSignalSetOnce {
+    signals: SignalSet,
+    /// The signals whose default action was replaced with ignoring them.
+    ignored: SignalSet,
+    readiness: mem::ManuallyDrop<Readiness>,
+    caught: Option<Signal>,
+    policy: Option<ErrorPolicy>,
+    error: Option<io::Error>,
+}
+
+impl Future for KqueueSignalSetOnce {
+    type Output = Option<Signal>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        if let Some(signal) = this.caught {
+            return Poll::Ready(Some(signal));
+        }
+
+        loop {
+            match this.take_events() {
+                Ok(Some(signal)) => {
+                    this.caught = Some(signal);
+                    return Poll::Ready(Some(signal));
+                }
+                Ok(None) => {}
+                Err(error) => return this.handle_error(error),
+            }
+
+            match this.readiness.poll_ready(cx) {
+                Poll::Ready(Ok(())) => {
+                    // Clear readiness before taking events again, so that an
+                    // event queued in between wakes up the task.
+                    if let Err(error) = this.readiness.clear_ready(cx) {
+                        return this.handle_error(error);
+                    }
+                }
+                Poll::Ready(Err(error)) => return this.handle_error(error),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+// SAFETY: Each kqueue only reports events to its own instance, and the
+// received signal is kept within it once taken.
+unsafe impl CancelSafe for KqueueSignalSetOnce {}
+
+impl Drop for KqueueSignalSetOnce {
+    fn drop(&mut self) {
+        for signal in self.ignored {
+            restore_default(signal);
+        }
+
+        let kq = self.as_raw_fd();
+
+        // Deregister from any reactor before closing the kqueue, which also
+        // removes its filters.
+        unsafe {
+            mem::ManuallyDrop::drop(&mut self.readiness);
+            libc::close(kq);
+        }
+    }
+}
+
+impl AsRawFd for KqueueSignalSetOnce {
+    /// Returns the kqueue file descriptor, which becomes readable upon
+    /// receiving a signal in the set.
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.readiness.reader().0
+    }
+}
+
+impl KqueueSignalSetOnce {
+    /// Adds an `EVFILT_SIGNAL` filter for each of `signals` to a new kqueue.
+    pub fn register(signals: SignalSet) -> io::Result<Self> {
+        let kq = unsafe { libc::kqueue() };
+        if kq < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let close = || unsafe {
+            libc::close(kq);
+        };
+
+        if let Err(error) = add_filters(kq, signals) {
+            close();
+            return Err(error);
+        }
+
+        let readiness = match Readiness::new(pipe::Reader(kq)) {
+            Ok(readiness) => readiness,
+            Err(error) => {
+                close();
+                return Err(error);
+            }
+        };
+
+        let mut once = Self {
+            signals,
+            ignored: SignalSet::new(),
+            readiness: mem::ManuallyDrop::new(readiness),
+            caught: None,
+            policy: None,
+            error: None,
+        };
+
+        // Dropping `once` upon failure restores the default actions.
+        for signal in signals {
+            if ignore_if_default(signal)? {
+                once.ignored.insert(signal);
+            }
+        }
+
+        Ok(once)
+    }
+
+    /// Returns the signals that `self` is registered for.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.signals
+    }
+
+    /// Returns `self` with `policy` used for handling internal errors instead
+    /// of the [global policy](enum.ErrorPolicy.html#method.global).
+    #[inline]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Takes the internal error that fulfilled `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Takes all pending events without blocking, returning the lowest signal
+    /// received.
+    fn take_events(&self) -> io::Result<Option<Signal>> {
+        let mut received = SignalSet::new();
+        let mut events: [libc::kevent; 8] = unsafe { mem::zeroed() };
+        let timeout = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+
+        loop {
+            let n = unsafe {
+                libc::kevent(
+                    self.as_raw_fd(),
+                    ptr::null(),
+                    0,
+                    events.as_mut_ptr(),
+                    events.len() as _,
+                    &timeout,
+                )
+            };
+            if n < 0 {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(error);
+            }
+
+            for event in &events[..n as usize] {
+                if let Some(signal) = Signal::from_raw(event.ident as _) {
+                    received.insert(signal);
+                }
+            }
+
+            if (n as usize) < events.len() {
+                return Ok(received.first());
+            }
+        }
+    }
+
+    fn handle_error(&mut self, error: io::Error) -> Poll<Option<Signal>> {
+        self.policy
+            .unwrap_or_else(ErrorPolicy::global)
+            .handle(error, &mut self.error)
+            .map(|()| None)
+    }
+}
+
+fn add_filters(kq: RawFd, signals: SignalSet) -> io::Result<()> {
+    let changes: Vec<libc::kevent> = signals
+        .into_iter()
+        .map(|signal| {
+            let mut event: libc::kevent = unsafe { mem::zeroed() };
+            event.ident = signal.into_raw() as _;
+            event.filter = libc::EVFILT_SIGNAL as _;
+            event.flags = (libc::EV_ADD | libc::EV_CLEAR) as _;
+            event
+        })
+        .collect();
+
+    let result = unsafe {
+        libc::kevent(
+            kq,
+            changes.as_ptr(),
+            changes.len() as _,
+            ptr::null_mut(),
+            0,
+            ptr::null(),
+        )
+    };
+
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Sets `signal` to be ignored if its action is the default and would affect
+/// the process, returning whether it was.
+fn ignore_if_default(signal: Signal) -> io::Result<bool> {
+    // Signals that are harmless by default are left alone, since ignoring some
+    // of them has side effects, such as children of an ignored `SIGCHLD` being
+    // reaped automatically.
+    let harmless = SignalSet::new()
+        .child()
+        .cont()
+        .info()
+        .urgent()
+        .window_change();
+    if harmless.contains(signal) {
+        return Ok(false);
+    }
+
+    let raw_signal = signal.into_raw();
+
+    let mut action: libc::sigaction = unsafe { mem::zeroed() };
+    if unsafe { libc::sigaction(raw_signal, ptr::null(), &mut action) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    if action.sa_sigaction != libc::SIG_DFL {
+        return Ok(false);
+    }
+
+    if unsafe { libc::signal(raw_signal, libc::SIG_IGN) } == libc::SIG_ERR {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(true)
+}
+
+/// Restores the default action of `signal` if it's still being ignored.
+fn restore_default(signal: Signal) {
+    let raw_signal = signal.into_raw();
+
+    let mut action: libc::sigaction = unsafe { mem::zeroed() };
+    if unsafe { libc::sigaction(raw_signal, ptr::null(), &mut action) } != 0 {
+        return;
+    }
+
+    // A handler may have been installed in the meantime.
+    if action.sa_sigaction == libc::SIG_IGN {
+        unsafe { libc::signal(raw_signal, libc::SIG_DFL) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn kqueue_delivery() {
+        let signal = Signal::Info;
+
+        test_util::run(|| {
+            let mut once =
+                KqueueSignalSetOnce::register(signal.into()).unwrap();
+            assert!(test_util::poll(&mut once).is_pending());
+
+            // Filters only observe signals sent to the process, which `raise`
+            // doesn't do on every target.
+            unsafe { libc::kill(libc::getpid(), signal.into_raw()) };
+            assert_eq!(test_util::poll(&mut once), Poll::Ready(Some(signal)));
+        });
+    }
+}
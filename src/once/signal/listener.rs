@@ -0,0 +1,162 @@
+use std::{
+    io,
+    task::{Context, Poll},
+};
+
+use super::{table::Table, Driver};
+use crate::{
+    signal::{Signal, SignalArray},
+    SignalSet,
+};
+
+/// A repeatedly fulfilled listener for the signals in a set.
+///
+/// Signals received in between polls are coalesced, with each pending signal
+/// returned once in order of its integer value.
+#[derive(Debug)]
+pub(crate) struct Listener {
+    signals: SignalSet,
+    driver: Driver,
+    /// Signals observed but not yet returned.
+    pending: SignalSet,
+    /// The catch counts last observed for each signal.
+    counts: SignalArray<usize>,
+}
+
+impl Listener {
+    /// Registers a handler for `signals`.
+    pub fn register(signals: SignalSet) -> io::Result<Self> {
+        Self::register_impl(signals, false)
+    }
+
+    /// Registers a handler for `signals` that records the sender of each
+    /// delivery.
+    #[cfg(feature = "stream")]
+    pub fn register_with_info(signals: SignalSet) -> io::Result<Self> {
+        Self::register_impl(signals, true)
+    }
+
+    fn register_impl(signals: SignalSet, with_info: bool) -> io::Result<Self> {
+        let mut listener = Self {
+            signals,
+            driver: Driver::new()?,
+            pending: SignalSet::new(),
+            counts: [0; Signal::NUM],
+        };
+
+        // Signals caught before registering are not returned.
+        Table::global().take_caught(signals, &mut listener.counts);
+
+        // Dropping the listener upon failure closes the pipe.
+        let writer = listener.driver.writer;
+        if with_info {
+            super::register_set_with_info(signals, writer)?;
+        } else {
+            super::register_set(signals, writer)?;
+        }
+        Ok(listener)
+    }
+
+    /// Returns the signals handled by `self`.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.signals
+    }
+
+    #[cfg(feature = "stream")]
+    /// Uses `policy` for handling errors instead of the global policy.
+    #[inline]
+    pub fn set_error_policy(&mut self, policy: super::ErrorPolicy) {
+        self.driver.policy = Some(policy);
+    }
+
+    #[cfg(feature = "stream")]
+    /// Handles `error` according to the error policy, returning whether the
+    /// caller should resolve.
+    #[inline]
+    pub fn handle_error(&mut self, error: io::Error) -> Poll<()> {
+        self.driver.handle_error(error)
+    }
+
+    #[cfg(feature = "stream")]
+    /// Takes the error stored under `ErrorPolicy::ResolveWithError`, if any.
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.driver.error.take()
+    }
+
+    /// Stops listening for new signals, keeping those caught so far so that
+    /// they are returned by `pop_pending`.
+    #[cfg(feature = "stream")]
+    pub fn close(&mut self) -> io::Result<()> {
+        let caught =
+            Table::global().take_caught(self.signals, &mut self.counts);
+        self.pending.insert(caught);
+        super::unregister_set(self.signals, self.driver.writer)
+    }
+
+    /// Takes the next signal caught but not yet returned, if any.
+    #[cfg(feature = "stream")]
+    #[inline]
+    pub fn pop_pending(&mut self) -> Option<Signal> {
+        self.pending.pop_first()
+    }
+
+    /// Polls for the next signal received.
+    pub fn poll_next(&mut self, cx: &mut Context) -> Poll<io::Result<Signal>> {
+        let table = Table::global();
+
+        loop {
+            if let Some(signal) = self.pending.pop_first() {
+                return Poll::Ready(Ok(signal));
+            }
+
+            if table.is_paused() {
+                // `resume` writes to the pipe if a relevant signal was caught.
+                return match self.driver.clear(cx) {
+                    Ok(()) => Poll::Pending,
+                    Err(error) => Poll::Ready(Err(error)),
+                };
+            }
+
+            self.pending = table.take_caught(self.signals, &mut self.counts);
+            if !self.pending.is_empty() {
+                continue;
+            }
+
+            match self.driver.readiness.poll_ready(cx) {
+                Poll::Ready(Ok(())) => {
+                    // Check for signals again after clearing readiness, so
+                    // that none caught in between are missed.
+                    if let Err(error) = self.driver.clear(cx) {
+                        return Poll::Ready(Err(error));
+                    }
+                }
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "stream"))]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn close_keeps_caught() {
+        let signals = Signal::Urgent | Signal::WindowChange;
+
+        test_util::run_exclusive(|| {
+            let mut listener = Listener::register(signals).unwrap();
+            Signal::WindowChange.raise().unwrap();
+            Signal::Urgent.raise().unwrap();
+
+            listener.close().unwrap();
+            assert_eq!(listener.pop_pending(), signals.first());
+            assert_eq!(listener.pop_pending(), signals.last());
+            assert_eq!(listener.pop_pending(), None);
+        });
+    }
+}
@@ -0,0 +1,498 @@
+use std::{
+    collections::VecDeque,
+    io, mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex, MutexGuard,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use super::{table::Table, RegisterOnceError};
+use crate::{
+    once::CancelSafe,
+    signal::{Signal, SignalArray},
+    unix::pipe,
+    SignalSet,
+};
+
+/// Options for registering a listener that prioritizes wakeup latency over
+/// resource usage.
+///
+/// This suits processes such as audio engines and robot controllers that must
+/// react to control signals like [`UserDef1`] within tight bounds. Unlike other
+/// listeners, a [`LowLatencyListener`]:
+///
+/// - Has a dedicated dispatcher thread blocked on its pipe alone, optionally
+///   with a realtime scheduling priority, rather than sharing the watcher
+///   thread or the tokio reactor.
+///
+/// - Keeps the waker of its task registered with the dispatcher in between
+///   polls, so that waking it never waits for interest to be registered again.
+///
+/// - Reports every delivery, rather than coalescing those received in between
+///   polls. Deliveries of a standard signal that arrive while it is already
+///   pending are still merged by the OS.
+///
+/// - Reports the latency of each delivery to a [hook](#method.with_hook).
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{
+///     once::signal::{LatencySample, LowLatency},
+///     SignalSet,
+/// };
+/// use std::future::poll_fn;
+///
+/// fn record(sample: LatencySample) {
+///     eprintln!("{:?} took {:?}", sample.signal(), sample.total());
+/// }
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut listener = LowLatency::new()
+///     .with_priority(80)
+///     .with_hook(record)
+///     .register(SignalSet::new().user_def_1())?;
+///
+/// loop {
+///     let signal = poll_fn(|cx| listener.poll_next(cx)).await?;
+///     println!("switching presets due to {:?}", signal);
+/// }
+/// # }
+/// ```
+///
+/// [`UserDef1`]: ../../signal/enum.Signal.html#variant.UserDef1
+/// [`LowLatencyListener`]: struct.LowLatencyListener.html
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LowLatency {
+    priority: Option<libc::c_int>,
+    hook: Option<fn(LatencySample)>,
+}
+
+impl LowLatency {
+    /// Creates options for a dispatcher thread with the default scheduling
+    /// policy and no latency hook.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            priority: None,
+            hook: None,
+        }
+    }
+
+    /// Returns `self` with the dispatcher thread scheduled under `SCHED_FIFO`
+    /// at `priority`.
+    ///
+    /// This usually requires privileges, such as `CAP_SYS_NICE` on Linux.
+    #[inline]
+    pub const fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Returns `self` with `hook` called with the latency of each delivery
+    /// when it is returned by [`LowLatencyListener::poll_next`].
+    ///
+    /// The hook is called by the polling task, so it should return quickly.
+    ///
+    /// [`LowLatencyListener::poll_next`]: struct.LowLatencyListener.html#method.poll_next
+    #[inline]
+    pub const fn with_hook(mut self, hook: fn(LatencySample)) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    /// Registers a handler for `signals`, spawning the dispatcher thread.
+    ///
+    /// The handlers record the wall-clock time of each delivery for measuring
+    /// latency, as with [`SignalInfoOnce::register_timestamped`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegisterOnceError::Io`] if the scheduling priority cannot be
+    /// set, in which case nothing is registered.
+    ///
+    /// [`SignalInfoOnce::register_timestamped`]: struct.SignalInfoOnce.html#method.register_timestamped
+    /// [`RegisterOnceError::Io`]: enum.RegisterOnceError.html#variant.Io
+    pub fn register(
+        self,
+        signals: SignalSet,
+    ) -> Result<LowLatencyListener, RegisterOnceError> {
+        let (reader, writer) = pipe::pipe().map_err(super::error::pipe)?;
+
+        // Dropping the listener upon failure closes the pipe and stops the
+        // dispatcher thread.
+        let mut listener = LowLatencyListener {
+            signals,
+            reader,
+            writer,
+            dispatcher: Arc::default(),
+            thread: None,
+            hook: self.hook,
+            counts: [0; Signal::NUM],
+            pending: VecDeque::new(),
+            wakeup: None,
+        };
+
+        let (started, start) = mpsc::channel();
+        let dispatcher = Arc::clone(&listener.dispatcher);
+        let priority = self.priority;
+
+        listener.thread = Some(
+            thread::Builder::new()
+                .name("asygnal-low-latency".into())
+                .spawn(move || {
+                    let result = priority.map_or(Ok(()), set_priority);
+                    let ok = result.is_ok();
+                    let _ = started.send(result);
+                    if ok {
+                        dispatcher.run(reader, writer);
+                    }
+                })?,
+        );
+
+        match start.recv() {
+            Ok(result) => result?,
+            Err(_) => {
+                return Err(RegisterOnceError::Io(io::Error::new(
+                    io::ErrorKind::Other,
+                    "dispatcher thread panicked",
+                )))
+            }
+        }
+
+        // Signals caught before registering are not returned.
+        let table = Table::global();
+        for signal in signals {
+            listener.counts[signal as usize] =
+                table.entry(signal).count.load(Ordering::SeqCst);
+        }
+
+        super::register_set_with_info(signals, writer)?;
+        super::record_wall_clock(signals);
+        Ok(listener)
+    }
+}
+
+/// The latency of a delivery to a [`LowLatencyListener`].
+///
+/// Latencies are measured from the wall-clock time recorded by the handler for
+/// the latest delivery of the signal, so they are unavailable if the clock was
+/// adjusted in between.
+///
+/// [`LowLatencyListener`]: struct.LowLatencyListener.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LatencySample {
+    signal: Signal,
+    dispatch: Option<Duration>,
+    wake: Option<Duration>,
+    total: Option<Duration>,
+}
+
+impl LatencySample {
+    /// Returns the signal that was delivered.
+    #[inline]
+    pub fn signal(&self) -> Signal {
+        self.signal
+    }
+
+    /// Returns the time from the handler running until the dispatcher thread
+    /// woke the task.
+    ///
+    /// This is `None` if the task observed the delivery before being woken.
+    #[inline]
+    pub fn dispatch(&self) -> Option<Duration> {
+        self.dispatch
+    }
+
+    /// Returns the time from the dispatcher thread waking the task until the
+    /// delivery was returned.
+    ///
+    /// Unlike the others, this is measured with a monotonic clock. It is
+    /// `None` if the task observed the delivery before being woken.
+    #[inline]
+    pub fn wake(&self) -> Option<Duration> {
+        self.wake
+    }
+
+    /// Returns the time from the handler running until the delivery was
+    /// returned.
+    #[inline]
+    pub fn total(&self) -> Option<Duration> {
+        self.total
+    }
+}
+
+/// A repeatedly fulfilled listener for the signals in a set, registered via
+/// [`LowLatency`].
+///
+/// Each delivery is returned once, with those observed at the same time
+/// returned in order of integer value.
+///
+/// Dropping an instance unregisters it and stops its dispatcher thread.
+///
+/// With the `stream` feature, this implements `Stream` and `FusedStream`.
+///
+/// [`LowLatency`]: struct.LowLatency.html
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct LowLatencyListener {
+    signals: SignalSet,
+    reader: pipe::Reader,
+    writer: pipe::Writer,
+    dispatcher: Arc<Dispatcher>,
+    thread: Option<thread::JoinHandle<()>>,
+    hook: Option<fn(LatencySample)>,
+    /// The catch counts last observed for each signal.
+    counts: SignalArray<usize>,
+    /// Deliveries observed but not yet returned.
+    pending: VecDeque<Signal>,
+    /// When the dispatcher woke the task for the deliveries in `pending`.
+    wakeup: Option<Wakeup>,
+}
+
+// SAFETY: Deliveries are only removed from `pending` when returned.
+unsafe impl CancelSafe for LowLatencyListener {}
+
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::Stream for LowLatencyListener {
+    type Item = io::Result<Signal>;
+
+    #[inline]
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Self::Item>> {
+        LowLatencyListener::poll_next(&mut self, cx).map(Some)
+    }
+}
+
+/// Never terminated, since `self` never ends.
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::stream::FusedStream for LowLatencyListener {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for LowLatencyListener {
+    fn drop(&mut self) {
+        // There's no reasonable way to report failing to restore an action.
+        let _ = super::unregister_set(SignalSet::all(), self.writer);
+
+        self.dispatcher.stopped.store(true, Ordering::SeqCst);
+        self.writer.wake();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        super::close_pipe(self.reader, self.writer);
+    }
+}
+
+impl LowLatencyListener {
+    /// Returns the signals handled by `self`.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.signals
+    }
+
+    /// Registers `waker` to be woken by the dispatcher thread upon every
+    /// delivery, ahead of the first poll.
+    ///
+    /// The waker passed to [`poll_next`](#method.poll_next) replaces it.
+    pub fn register_waker(&self, waker: &Waker) {
+        let mut current = lock(&self.dispatcher.waker);
+        match &*current {
+            Some(current) if current.will_wake(waker) => {}
+            _ => *current = Some(waker.clone()),
+        }
+    }
+
+    /// Polls for the next delivery.
+    ///
+    /// Errors are returned if the dispatcher thread fails to wait on the pipe,
+    /// after which `self` is no longer woken.
+    pub fn poll_next(&mut self, cx: &mut Context) -> Poll<io::Result<Signal>> {
+        self.register_waker(cx.waker());
+
+        if let Some(error) = lock(&self.dispatcher.error).take() {
+            return Poll::Ready(Err(error));
+        }
+
+        // `resume` writes to the pipe if a relevant signal was caught.
+        if self.pending.is_empty() && !Table::global().is_paused() {
+            self.take_deliveries();
+        }
+
+        let signal = match self.pending.pop_front() {
+            Some(signal) => signal,
+            None => return Poll::Pending,
+        };
+
+        if let Some(hook) = self.hook {
+            hook(self.sample(signal));
+        }
+        Poll::Ready(Ok(signal))
+    }
+
+    fn take_deliveries(&mut self) {
+        // Taken before reading counts so that it never belongs to a later
+        // delivery.
+        self.wakeup = lock(&self.dispatcher.wakeup).take();
+
+        let table = Table::global();
+        for signal in self.signals {
+            let count = table.entry(signal).count.load(Ordering::SeqCst);
+            let last = mem::replace(&mut self.counts[signal as usize], count);
+            for _ in 0..count.wrapping_sub(last) {
+                self.pending.push_back(signal);
+            }
+        }
+    }
+
+    fn sample(&self, signal: Signal) -> LatencySample {
+        let handled = Table::global().entry(signal).time.load(Ordering::SeqCst);
+        let since_handled = |time: SystemTime| {
+            let time = time.duration_since(UNIX_EPOCH).ok()?;
+            match handled {
+                0 => None,
+                handled => time.checked_sub(Duration::from_nanos(handled)),
+            }
+        };
+
+        LatencySample {
+            signal,
+            dispatch: self.wakeup.and_then(|wakeup| since_handled(wakeup.time)),
+            wake: self.wakeup.map(|wakeup| wakeup.instant.elapsed()),
+            total: since_handled(SystemTime::now()),
+        }
+    }
+}
+
+/// The state shared with the dispatcher thread of a `LowLatencyListener`.
+#[derive(Debug, Default)]
+struct Dispatcher {
+    /// Set when the listener is dropped, stopping the thread.
+    stopped: AtomicBool,
+    /// The task to wake upon every delivery, kept in between wakeups.
+    waker: Mutex<Option<Waker>>,
+    /// When the task was first woken since it last took deliveries.
+    wakeup: Mutex<Option<Wakeup>>,
+    /// The error that stopped the thread, if any.
+    error: Mutex<Option<io::Error>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Wakeup {
+    instant: Instant,
+    time: SystemTime,
+}
+
+impl Dispatcher {
+    fn run(&self, reader: pipe::Reader, writer: pipe::Writer) {
+        let table = Table::global();
+
+        loop {
+            match reader.wait_readable(None) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(error) => {
+                    *lock(&self.error) = Some(error);
+                    self.wake();
+                    return;
+                }
+            }
+
+            if self.stopped.load(Ordering::SeqCst) {
+                return;
+            }
+
+            table.drain(reader, writer);
+            lock(&self.wakeup).get_or_insert_with(|| Wakeup {
+                instant: Instant::now(),
+                time: SystemTime::now(),
+            });
+            self.wake();
+        }
+    }
+
+    fn wake(&self) {
+        if let Some(waker) = &*lock(&self.waker) {
+            waker.wake_by_ref();
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+/// Schedules the calling thread under `SCHED_FIFO` at `priority`.
+fn set_priority(priority: libc::c_int) -> io::Result<()> {
+    // Zeroed since some targets have private fields.
+    let mut param: libc::sched_param = unsafe { mem::zeroed() };
+    param.sched_priority = priority;
+
+    let thread = unsafe { libc::pthread_self() };
+    match unsafe {
+        libc::pthread_setschedparam(thread, libc::SCHED_FIFO, &param)
+    } {
+        0 => Ok(()),
+        code => Err(io::Error::from_raw_os_error(code)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::{sync::atomic::AtomicUsize, task::Wake};
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    static SAMPLES: AtomicUsize = AtomicUsize::new(0);
+
+    fn record(sample: LatencySample) {
+        assert_eq!(sample.signal(), Signal::TtOut);
+        SAMPLES.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn reports_every_delivery() {
+        let _delivery = test_util::delivery();
+
+        let signal = Signal::TtOut;
+        let mut listener = LowLatency::new()
+            .with_hook(record)
+            .register(signal.into())
+            .unwrap();
+
+        let waker = Arc::new(NoopWaker).into();
+        let mut cx = Context::from_waker(&waker);
+        assert!(listener.poll_next(&mut cx).is_pending());
+
+        // Handlers run before `raise` returns, so neither is merged.
+        signal.raise().unwrap();
+        signal.raise().unwrap();
+
+        for _ in 0..2 {
+            match listener.poll_next(&mut cx) {
+                Poll::Ready(Ok(received)) => assert_eq!(received, signal),
+                poll => panic!("unexpected poll: {:?}", poll),
+            }
+        }
+        assert!(listener.poll_next(&mut cx).is_pending());
+        assert_eq!(SAMPLES.load(Ordering::SeqCst), 2);
+    }
+}
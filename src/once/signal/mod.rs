@@ -1,65 +1,548 @@
 //! Unix-specific functionality.
 
 use std::{
-    io, mem, ptr,
-    sync::atomic::Ordering,
-    task::{Context, Poll},
+    io,
+    mem::{self, ManuallyDrop},
+    ptr,
+    sync::{atomic::Ordering, Mutex, MutexGuard},
 };
-use tokio::io::PollEvented;
 
-use crate::{unix::pipe, Signal, SignalSet};
+use std::task::{Context, Poll};
 
+use crate::{signal::SignalArray, unix::pipe, Signal, SignalSet};
+
+mod coalesce;
+pub(crate) mod error;
+mod fallible;
+pub(crate) mod force_exit;
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd",
+))]
+mod kqueue;
+mod pause;
+mod raw;
+mod readiness;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) mod realtime;
+pub(crate) mod table;
+
+mod future;
+mod info;
+mod kind;
+pub(crate) mod listener;
+mod low_latency;
+mod policy;
+mod router;
+mod shared;
+#[allow(clippy::module_inception)]
 mod signal;
 mod signal_set;
-mod table;
+mod source;
+pub(crate) mod validate;
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd",
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "openbsd",
+        target_os = "netbsd",
+    )))
+)]
+pub use kqueue::KqueueSignalSetOnce;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(target_os = "linux", target_os = "android")))
+)]
+pub use realtime::{RealtimeEvent, RealtimeSignalOnce};
 
-pub use {signal::SignalOnce, signal_set::SignalSetOnce};
+pub use {
+    coalesce::Coalesce,
+    error::RegisterOnceError,
+    fallible::Fallible,
+    force_exit::ForceExit,
+    pause::{is_paused, pause, resume, PauseGuard},
+    raw::RawSignalSetOnce,
+};
+
+pub use {
+    future::SignalSetFuture,
+    info::{SignalInfo, SignalInfoOnce},
+    kind::SignalKindOnce,
+    low_latency::{LatencySample, LowLatency, LowLatencyListener},
+    policy::ErrorPolicy,
+    router::{Next, SignalRouter},
+    signal::SignalOnce,
+    signal_set::{register_all, SignalSetOnce},
+    source::SignalSource,
+    validate::ValidationError,
+};
 
 /// The event driver for when the pipe can be read.
+///
+/// Dropping the driver unregisters it from all of its signals and closes the
+/// pipe.
 #[derive(Debug)]
-struct Driver(PollEvented<pipe::Reader>);
+struct Driver {
+    /// Dropped manually so that the reading end is deregistered from any
+    /// reactor before being closed.
+    readiness: ManuallyDrop<readiness::Readiness>,
+    /// The writing end of the pipe, used for identifying our table entries.
+    writer: pipe::Writer,
+    /// The policy for handling errors, or `None` to use the global policy.
+    policy: Option<ErrorPolicy>,
+    /// The error that fulfilled the future under
+    /// `ErrorPolicy::ResolveWithError`.
+    error: Option<io::Error>,
+}
 
 impl Driver {
-    pub fn new(reader: pipe::Reader) -> io::Result<Self> {
-        Ok(Self(PollEvented::new(reader)?))
+    /// Creates a driver for a new pipe, without registering any signals.
+    pub fn new() -> io::Result<Self> {
+        let (reader, writer) = pipe::pipe().map_err(error::pipe)?;
+
+        let readiness = match readiness::Readiness::new(reader) {
+            Ok(readiness) => readiness,
+            Err(error) => {
+                close_pipe(reader, writer);
+                return Err(error);
+            }
+        };
+
+        Ok(Self {
+            readiness: ManuallyDrop::new(readiness),
+            writer,
+            policy: None,
+            error: None,
+        })
+    }
+
+    /// Creates a driver woken up upon receiving any of `signals`.
+    pub fn register(signals: SignalSet) -> io::Result<Self> {
+        let driver = Self::new()?;
+
+        // Dropping the driver upon failure closes the pipe.
+        register_set(signals, driver.writer)?;
+        Ok(driver)
+    }
+
+    /// Creates a driver like [`register`](#method.register), with the
+    /// handlers of `signals` recording the sender of each delivery.
+    pub fn register_with_info(signals: SignalSet) -> io::Result<Self> {
+        let driver = Self::new()?;
+
+        // Dropping the driver upon failure closes the pipe.
+        register_set_with_info(signals, driver.writer)?;
+        Ok(driver)
     }
 
-    pub fn poll(&self, cx: &mut Context) -> Poll<()> {
-        match self.0.poll_read_ready(cx, mio::Ready::readable()) {
-            Poll::Ready(Ok(_)) => Poll::Ready(()),
-            Poll::Ready(Err(error)) => panic!("Error on self-pipe: {}", error),
+    /// Polls whether the pipe became readable, with `false` meaning that an
+    /// error resolved the future.
+    pub fn poll_readable(&mut self, cx: &mut Context) -> Poll<bool> {
+        match self.readiness.poll_ready(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(true),
+            Poll::Ready(Err(error)) => self.handle_error(error).map(|()| false),
             Poll::Pending => Poll::Pending,
         }
     }
+
+    /// Schedules `cx` to be woken upon the next write to the pipe, regardless
+    /// of whether it is currently readable.
+    pub fn park(&mut self, cx: &mut Context) -> Poll<()> {
+        match self.clear(cx) {
+            Ok(()) => Poll::Pending,
+            Err(error) => self.handle_error(error),
+        }
+    }
+
+    /// Drains the pipe and schedules `cx` to be woken upon the next write.
+    ///
+    /// Signals caught in between must be checked for afterwards.
+    pub fn clear(&mut self, cx: &mut Context) -> io::Result<()> {
+        table::Table::global().drain(self.readiness.reader(), self.writer);
+        self.readiness.clear_ready(cx)
+    }
+
+    fn handle_error(&mut self, error: io::Error) -> Poll<()> {
+        self.policy
+            .unwrap_or_else(ErrorPolicy::global)
+            .handle(error, &mut self.error)
+    }
+}
+
+impl Drop for Driver {
+    fn drop(&mut self) {
+        // There's no reasonable way to report failing to restore an action.
+        let _ = unregister_set(SignalSet::all(), self.writer);
+
+        let reader = self.readiness.reader();
+
+        // SAFETY: `readiness` is not used after this.
+        unsafe { ManuallyDrop::drop(&mut self.readiness) };
+
+        close_pipe(reader, self.writer);
+    }
+}
+
+fn close_pipe(reader: pipe::Reader, writer: pipe::Writer) {
+    unsafe {
+        libc::close(reader.0);
+        libc::close(writer.0);
+    }
+}
+
+/// Serializes changes to the listeners in the table, so that a handler is
+/// never released while a listener is being added for it.
+static REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+
+pub(crate) fn lock_registry() -> MutexGuard<'static, ()> {
+    REGISTRY_LOCK
+        .lock()
+        .unwrap_or_else(|error| error.into_inner())
+}
+
+/// Runs `f` with the action that releasing `signal` would restore, which is
+/// `None` while our handler is not installed for it.
+///
+/// The registry is locked meanwhile, so that code installing handlers of its
+/// own can coordinate with ours.
+pub(crate) fn with_previous<R>(
+    signal: Signal,
+    f: impl FnOnce(&mut Option<libc::sigaction>) -> R,
+) -> R {
+    let _lock = lock_registry();
+    let mut previous = table::Table::global()
+        .previous
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    f(&mut previous[signal as usize])
+}
+
+/// Installs the handler for each signal in `signals`, waking up `writer` upon
+/// receiving any of them.
+///
+/// Any number of writers may be woken up by the same signal.
+///
+/// If installing fails for any signal, the previous handlers are restored.
+pub(crate) fn register_set(
+    signals: SignalSet,
+    writer: pipe::Writer,
+) -> io::Result<()> {
+    register_set_impl(signals, writer, false)
+}
+
+/// Installs the handler for each signal in `signals` like [`register_set`],
+/// with `SA_SIGINFO` so that the sender of each delivery is recorded.
+///
+/// Once installed this way, a signal keeps recording senders until released,
+/// even if registered again without it.
+///
+/// [`register_set`]: fn.register_set.html
+pub(crate) fn register_set_with_info(
+    signals: SignalSet,
+    writer: pipe::Writer,
+) -> io::Result<()> {
+    register_set_impl(signals, writer, true)
+}
+
+/// Installs the handler for each signal in `signals` like [`register_set`],
+/// skipping those that fail rather than restoring the others.
+///
+/// Returns the signals that were registered along with the error for each
+/// that was not.
+///
+/// [`register_set`]: fn.register_set.html
+pub(crate) fn register_set_partial(
+    signals: SignalSet,
+    writer: pipe::Writer,
+) -> (SignalSet, Vec<RegisterOnceError>) {
+    let _lock = lock_registry();
+    let mut handles = Vec::with_capacity(signals.len());
+    let mut registered = SignalSet::new();
+    let mut errors = Vec::new();
+
+    for signal in signals {
+        match install_set(signal.into(), false, &mut handles) {
+            Ok(()) => registered.insert(signal),
+            Err(error) => errors.push(error.into()),
+        }
+    }
+
+    table::Table::global().attach(registered, writer);
+    (registered, errors)
 }
 
-/// An error returned when registering a [`Signal`] or [`SignalSet`] fails.
+/// Makes the handlers of `signals`, which must have been registered via
+/// [`register_set_with_info`], also record the wall-clock time of each delivery
+/// until released.
 ///
-/// [`Signal`]:    ../../unix/enum.Signal.html
-/// [`SignalSet`]: ../../unix/struct.SignalSet.html
-#[derive(Debug)]
-pub enum RegisterOnceError {
-    /// Signals were already registered.
-    Registered(SignalSet),
-    /// An I/O error.
-    Io(io::Error),
+/// [`register_set_with_info`]: fn.register_set_with_info.html
+pub(crate) fn record_wall_clock(signals: SignalSet) {
+    table::Table::global()
+        .wall_clock
+        .insert(signals, Ordering::SeqCst);
 }
 
-impl From<io::Error> for RegisterOnceError {
+fn register_set_impl(
+    signals: SignalSet,
+    writer: pipe::Writer,
+    with_info: bool,
+) -> io::Result<()> {
+    let _lock = lock_registry();
+    let mut handles = Vec::with_capacity(signals.len());
+
+    if let Err(error) = install_set(signals, with_info, &mut handles) {
+        reset_all(handles);
+        return Err(error);
+    }
+
+    let table = table::Table::global();
+    if with_info {
+        table.with_info.insert(signals, Ordering::SeqCst);
+    }
+    table.attach(signals, writer);
+    Ok(())
+}
+
+/// Stops waking up `writer` upon receiving any of `signals`, releasing those
+/// that have no other listeners.
+pub(crate) fn unregister_set(
+    signals: SignalSet,
+    writer: pipe::Writer,
+) -> io::Result<()> {
+    let _lock = lock_registry();
+
+    for signal in table::Table::global().detach(signals, writer) {
+        release(signal)?;
+    }
+    Ok(())
+}
+
+/// Returns the label of the registration that currently handles `signal`, if
+/// it was registered with one.
+///
+/// This allows for attributing signal handling to the component of a large
+/// application that installed it.
+pub fn registration_label(signal: Signal) -> Option<&'static str> {
+    let labels = table::Table::global()
+        .labels
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    labels[signal as usize]
+}
+
+/// The catch counts last observed by a caller of [`poll_caught`].
+///
+/// Each caller keeps its own counts, so that it observes every delivery
+/// regardless of what other callers have taken.
+///
+/// [`poll_caught`]: fn.poll_caught.html
+#[derive(Clone, Debug)]
+pub struct CaughtCounts(SignalArray<usize>);
+
+impl Default for CaughtCounts {
     #[inline]
-    fn from(error: io::Error) -> Self {
-        Self::Io(error)
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CaughtCounts {
+    /// Records the current catch counts, so that only signals caught afterward
+    /// are taken.
+    #[inline]
+    pub fn new() -> Self {
+        Self(table::Table::global().counts(SignalSet::all()))
+    }
+}
+
+/// Takes the signals in `signals` that have been caught since they were last
+/// taken with `counts`.
+///
+/// This is a low-level primitive for building custom futures and streams on
+/// top of the handler installed by this crate, with consumption semantics of
+/// their own. Each caught signal is returned once per `counts`, and taking it
+/// does not affect other callers or the futures of this crate.
+///
+/// Multiple deliveries of the same signal between calls are merged.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{
+///     once::signal::{poll_caught, CaughtCounts},
+///     SignalSet,
+/// };
+///
+/// let signals = SignalSet::new().hangup().user_def_1();
+/// let mut counts = CaughtCounts::new();
+/// let _once = signals.register_once()?;
+///
+/// for signal in poll_caught(signals, &mut counts) {
+///     println!("caught {:?}", signal);
+/// }
+/// # Ok::<(), asygnal::once::signal::RegisterOnceError>(())
+/// ```
+pub fn poll_caught(signals: SignalSet, counts: &mut CaughtCounts) -> SignalSet {
+    table::Table::global().take_caught(signals, &mut counts.0)
+}
+
+/// Sets the label of the registration handling each signal in `signals`.
+fn set_label(signals: SignalSet, label: Option<&'static str>) {
+    let mut labels = table::Table::global()
+        .labels
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    for signal in signals {
+        labels[signal as usize] = label;
+    }
+}
+
+/// Installs the handler for each signal in `signals`, pushing the previous
+/// handlers onto `handles` so that the caller can restore them on failure.
+///
+/// Signals already installed with `SA_SIGINFO` keep it regardless of
+/// `with_info`.
+fn install_set(
+    signals: SignalSet,
+    with_info: bool,
+    handles: &mut Vec<RegisteredSignal>,
+) -> io::Result<()> {
+    // Registrations replace any previous label.
+    set_label(signals, None);
+
+    let with_info = match with_info {
+        true => signals,
+        false => {
+            let installed = &table::Table::global().with_info;
+            installed
+                .load(Ordering::SeqCst)
+                .filter(|s| signals.contains(s))
+        }
+    };
+
+    for signal in signals {
+        let handle = register_signal(signal, with_info.contains(signal))?;
+        handle.save_previous(signal);
+        handles.push(handle);
     }
+
+    Ok(())
 }
 
-fn register_signal(signal: Signal) -> io::Result<RegisteredSignal> {
+/// Restores the action that was in place before our handler was installed for
+/// `signal`, if any.
+fn release(signal: Signal) -> io::Result<()> {
+    let table = table::Table::global();
+
+    let mut previous = table
+        .previous
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+
+    if let Some(action) = previous[signal as usize] {
+        let raw_signal = signal.into_raw();
+        if unsafe { libc::sigaction(raw_signal, &action, ptr::null_mut()) } != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        previous[signal as usize] = None;
+    }
+
+    table.registered.remove(signal, Ordering::SeqCst);
+    table.with_info.remove(signal, Ordering::SeqCst);
+    table.wall_clock.remove(signal, Ordering::SeqCst);
+    set_label(signal.into(), None);
+    Ok(())
+}
+
+/// Restores the previous handlers in reverse order of installation.
+fn reset_all(handles: Vec<RegisteredSignal>) {
+    handles.iter().rev().for_each(RegisteredSignal::reset);
+}
+
+/// Records `signal` as caught and wakes up its listeners.
+///
+/// It is imperative that this function is signal-safe.
+fn on_signal(signal: Signal) {
+    let table = table::Table::global();
+
+    let entry = table.entry(signal);
+
+    // Set the flag before waking up the reading end. If delivery is paused,
+    // `resume` will perform the wake up instead.
+    entry.count.fetch_add(1, Ordering::SeqCst);
+    table.caught.insert(signal, Ordering::SeqCst);
+    if !table.is_paused() {
+        table.wake(signal);
+    }
+
+    force_exit::on_signal(signal);
+}
+
+fn register_signal(
+    signal: Signal,
+    with_info: bool,
+) -> io::Result<RegisteredSignal> {
     extern "C" fn signal_handler(signal: libc::c_int) {
         if let Some(signal) = Signal::from_raw(signal) {
-            let table = table::Table::global();
+            on_signal(signal);
+        }
+    }
+
+    extern "C" fn info_handler(
+        signal: libc::c_int,
+        info: *mut libc::siginfo_t,
+        _: *mut libc::c_void,
+    ) {
+        if let Some(signal) = Signal::from_raw(signal) {
+            if let Some(info) = unsafe { info.as_ref() } {
+                // Recorded before the count is incremented, so that readers
+                // observing the new count also observe these.
+                let table = table::Table::global();
+                let entry = table.entry(signal);
 
-            // Set the flag before waking up the reading end.
-            table.caught.insert(signal, Ordering::SeqCst);
-            table.entry(signal).load_writer(Ordering::SeqCst).wake();
+                // `clock_gettime` is async-signal-safe. Without recording,
+                // the time of an earlier delivery must not be reported.
+                let nanos =
+                    if table.wall_clock.load(Ordering::SeqCst).contains(signal)
+                    {
+                        let mut time: libc::timespec = unsafe { mem::zeroed() };
+                        unsafe {
+                            libc::clock_gettime(libc::CLOCK_REALTIME, &mut time)
+                        };
+                        (time.tv_sec as u64)
+                            .wrapping_mul(1_000_000_000)
+                            .wrapping_add(time.tv_nsec as u64)
+                    } else {
+                        0
+                    };
+                entry.time.store(nanos, Ordering::SeqCst);
+
+                unsafe {
+                    entry.pid.store(info.si_pid(), Ordering::SeqCst);
+                    entry.uid.store(info.si_uid(), Ordering::SeqCst);
+                }
+                entry.code.store(info.si_code, Ordering::SeqCst);
+            }
+            on_signal(signal);
         }
     }
 
@@ -82,8 +565,13 @@ fn register_signal(signal: Signal) -> io::Result<RegisteredSignal> {
 
         unsafe {
             let mut action: sigaction = mem::zeroed();
-            action.sa_handler = Some(signal_handler);
-            action.libc.sa_flags = libc::SA_RESTART | libc::SA_NOCLDSTOP;
+            if with_info {
+                action.libc.sa_sigaction = info_handler as *const () as usize;
+                action.libc.sa_flags = libc::SA_SIGINFO;
+            } else {
+                action.sa_handler = Some(signal_handler);
+            }
+            action.libc.sa_flags |= libc::SA_RESTART | libc::SA_NOCLDSTOP;
             action.libc
         }
     };
@@ -95,7 +583,7 @@ fn register_signal(signal: Signal) -> io::Result<RegisteredSignal> {
             raw_signal,
             old_action,
         }),
-        _ => Err(io::Error::last_os_error()),
+        _ => Err(error::sigaction(signal, io::Error::last_os_error())),
     }
 }
 
@@ -105,9 +593,44 @@ struct RegisteredSignal {
 }
 
 impl RegisteredSignal {
+    /// Saves the action replaced by our handler, unless one was saved already
+    /// since that one may be our own handler.
+    pub fn save_previous(&self, signal: Signal) {
+        let mut previous = table::Table::global()
+            .previous
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+
+        previous[signal as usize].get_or_insert(self.old_action);
+    }
+
     pub fn reset(&self) {
         unsafe {
             libc::sigaction(self.raw_signal, &self.old_action, ptr::null_mut());
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn poll_caught_per_caller() {
+        let signal = Signal::Pipe;
+
+        test_util::run_exclusive(|| {
+            let (mut a, mut b) = (CaughtCounts::new(), CaughtCounts::new());
+            let _once = SignalSetOnce::register(signal.into()).unwrap();
+
+            signal.raise().unwrap();
+            assert_eq!(poll_caught(signal.into(), &mut a), signal.into());
+            assert!(poll_caught(signal.into(), &mut a).is_empty());
+
+            // Taking the signal with `a` leaves it for `b`.
+            assert_eq!(poll_caught(signal.into(), &mut b), signal.into());
+            assert!(poll_caught(signal.into(), &mut b).is_empty());
+        });
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::{atomic::Ordering, MutexGuard};
+
+use super::table::Table;
+use crate::signal::{SignalArray, SignalSet};
+
+/// Pauses delivery of caught signals to listeners.
+///
+/// Signals caught while paused are still recorded, but futures will not be
+/// fulfilled until a matching call to [`resume`]. This allows for deferring the
+/// reaction to e.g. a shutdown request during a short non-interruptible phase.
+///
+/// Calls nest: delivery only resumes once every call to `pause` has been
+/// matched by a call to [`resume`].
+///
+/// [`resume`]: fn.resume.html
+#[inline]
+pub fn pause() {
+    let table = Table::global();
+    let mut counts = lock_counts(table);
+
+    // Counts are recorded before pausing, so that signals caught in between
+    // are also delivered upon resuming.
+    if !table.is_paused() {
+        *counts = table.counts(SignalSet::all());
+    }
+
+    table.pause_depth.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Resumes delivery of caught signals to listeners after a call to [`pause`].
+///
+/// If this matches the outermost call to [`pause`], all listeners for signals
+/// that were caught while paused are woken up.
+///
+/// [`pause`]: fn.pause.html
+pub fn resume() {
+    let table = Table::global();
+    let counts = lock_counts(table);
+
+    let previous = table.pause_depth.fetch_update(
+        Ordering::SeqCst,
+        Ordering::SeqCst,
+        |depth| depth.checked_sub(1),
+    );
+
+    if previous != Ok(1) {
+        return;
+    }
+
+    // Signals handled after the depth reached 0 wake up listeners themselves.
+    let registered = table.registered.load(Ordering::SeqCst);
+
+    for signal in table.caught_since(registered, &counts) {
+        table.wake(signal);
+    }
+}
+
+fn lock_counts(table: &Table) -> MutexGuard<'_, SignalArray<usize>> {
+    // Counts are only ever replaced whole, so they remain consistent.
+    table
+        .pause_counts
+        .lock()
+        .unwrap_or_else(|error| error.into_inner())
+}
+
+/// Returns `true` if delivery of caught signals is currently paused.
+#[inline]
+pub fn is_paused() -> bool {
+    Table::global().is_paused()
+}
+
+/// A guard that pauses delivery of caught signals until dropped.
+///
+/// See [`pause`] for more info.
+///
+/// [`pause`]: fn.pause.html
+#[derive(Debug)]
+#[must_use = "delivery is resumed immediately if the guard is dropped"]
+pub struct PauseGuard(());
+
+impl Drop for PauseGuard {
+    #[inline]
+    fn drop(&mut self) {
+        resume();
+    }
+}
+
+impl PauseGuard {
+    /// Pauses delivery of caught signals until the returned guard is dropped.
+    #[inline]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        pause();
+        Self(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        once::signal::{SignalOnce, SignalSetOnce},
+        test_util,
+        unix::pipe,
+        Signal,
+    };
+
+    #[test]
+    fn wakes_signals_caught_while_paused() {
+        let (before, during) = (Signal::Trap, Signal::System);
+        let table = Table::global();
+
+        test_util::run_exclusive(|| {
+            let _once = SignalSetOnce::register(before | during).unwrap();
+
+            let (before_reader, before_writer) = pipe::pipe().unwrap();
+            let (during_reader, during_writer) = pipe::pipe().unwrap();
+            table.attach(before.into(), before_writer);
+            table.attach(during.into(), during_writer);
+
+            let pending_bytes = |reader: pipe::Reader| {
+                let mut count = 0;
+                unsafe { libc::ioctl(reader.0, libc::FIONREAD, &mut count) };
+                count
+            };
+
+            before.raise().unwrap();
+            table.drain(before_reader, before_writer);
+
+            let guard = PauseGuard::new();
+            during.raise().unwrap();
+            assert_eq!(pending_bytes(during_reader), 0);
+            drop(guard);
+
+            // Only the signal caught while paused is delivered upon resuming.
+            assert_eq!(pending_bytes(before_reader), 0);
+            assert_eq!(pending_bytes(during_reader), 1);
+
+            for (signal, reader, writer) in [
+                (before, before_reader, before_writer),
+                (during, during_reader, during_writer),
+            ] {
+                table.detach(signal.into(), writer);
+                unsafe {
+                    libc::close(reader.0);
+                    libc::close(writer.0);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn waits_again_after_resuming() {
+        let signal = Signal::Trap;
+
+        test_util::run_exclusive(|| {
+            let mut first = SignalOnce::register(signal).unwrap();
+
+            let guard = PauseGuard::new();
+            signal.raise().unwrap();
+            assert!(test_util::poll(&mut first).is_pending());
+            drop(guard);
+            assert!(test_util::poll(&mut first).is_ready());
+
+            // Registering again waits for the next delivery.
+            let mut second = SignalOnce::register(signal).unwrap();
+            assert!(test_util::poll(&mut second).is_pending());
+
+            signal.raise().unwrap();
+            assert!(test_util::poll(&mut second).is_ready());
+        });
+    }
+}
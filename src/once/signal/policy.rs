@@ -0,0 +1,106 @@
+use std::{
+    io,
+    sync::atomic::{AtomicU8, Ordering},
+    task::Poll,
+};
+
+static GLOBAL: AtomicU8 = AtomicU8::new(ErrorPolicy::LogAndPend as u8);
+
+/// How signal futures handle internal errors, such as failing to poll the
+/// self-pipe.
+///
+/// The policy can be set for all futures via [`set_global`] or for an
+/// individual future via e.g. [`SignalOnce::with_error_policy`].
+///
+/// [`set_global`]: #method.set_global
+/// [`SignalOnce::with_error_policy`]: struct.SignalOnce.html#method.with_error_policy
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ErrorPolicy {
+    /// Panic with the error, taking down whichever task polls the future.
+    Panic,
+    /// Write the error to standard error and remain pending forever. This is
+    /// the default.
+    LogAndPend,
+    /// Fulfill the future and keep the error to be retrieved later via e.g.
+    /// [`SignalOnce::take_error`].
+    ///
+    /// [`SignalOnce::take_error`]: struct.SignalOnce.html#method.take_error
+    ResolveWithError,
+}
+
+impl Default for ErrorPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::LogAndPend
+    }
+}
+
+impl ErrorPolicy {
+    /// Returns the policy used by futures without their own policy.
+    #[inline]
+    pub fn global() -> Self {
+        match GLOBAL.load(Ordering::Relaxed) {
+            0 => Self::Panic,
+            2 => Self::ResolveWithError,
+            _ => Self::LogAndPend,
+        }
+    }
+
+    /// Sets `self` as the policy used by futures without their own policy.
+    #[inline]
+    pub fn set_global(self) {
+        GLOBAL.store(self as u8, Ordering::Relaxed);
+    }
+
+    /// Handles `error` according to `self`, storing it in `slot` if the future
+    /// should resolve.
+    pub(super) fn handle(
+        self,
+        error: io::Error,
+        slot: &mut Option<io::Error>,
+    ) -> Poll<()> {
+        match self {
+            Self::Panic => panic!("Error on self-pipe: {}", error),
+            Self::LogAndPend => {
+                eprintln!("asygnal: error on self-pipe: {}", error);
+                Poll::Pending
+            }
+            Self::ResolveWithError => {
+                *slot = Some(error);
+                Poll::Ready(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error() -> io::Error {
+        io::Error::from(io::ErrorKind::BrokenPipe)
+    }
+
+    #[test]
+    fn resolve_with_error() {
+        let mut slot = None;
+        let poll = ErrorPolicy::ResolveWithError.handle(error(), &mut slot);
+        assert!(poll.is_ready());
+        assert_eq!(slot.unwrap().kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn log_and_pend() {
+        let mut slot = None;
+        let poll = ErrorPolicy::LogAndPend.handle(error(), &mut slot);
+        assert!(poll.is_pending());
+        assert!(slot.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Error on self-pipe")]
+    fn panic() {
+        let _ = ErrorPolicy::Panic.handle(error(), &mut None);
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    future::Future,
+    os::unix::io::{AsRawFd, RawFd},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::{table::Table, RegisterOnceError};
+use crate::{once::CancelSafe, signal::SignalArray, unix::pipe, SignalSet};
+
+/// A future that is fulfilled once upon receiving a [`Signal`] in a
+/// [`SignalSet`], without depending on any runtime.
+///
+/// Instead of registering the waker from [`Context`], this future expects the
+/// caller to wait until the file descriptor returned by [`as_raw_fd`] is
+/// readable and then poll it again. This suits minimal executors and FFI hosts
+/// with their own event loop. Use [`SignalSetOnce`] when running on an
+/// executor.
+///
+/// After an instance is fulfilled, all subsequent polls will return [`Ready`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{once::signal::RawSignalSetOnce, SignalSet};
+/// use std::{future::Future, os::unix::io::AsRawFd, pin::Pin, task::*};
+///
+/// # fn wait_readable(fd: std::os::unix::io::RawFd) {}
+/// # fn noop_waker() -> Waker { unimplemented!() }
+/// let mut signal = RawSignalSetOnce::register(SignalSet::termination())?;
+///
+/// let waker = noop_waker();
+/// let mut cx = Context::from_waker(&waker);
+///
+/// while Pin::new(&mut signal).poll(&mut cx).is_pending() {
+///     wait_readable(signal.as_raw_fd());
+/// }
+/// # Ok::<(), asygnal::once::signal::RegisterOnceError>(())
+/// ```
+///
+/// [`Signal`]:        ../../signal/enum.Signal.html
+/// [`SignalSet`]:     ../../signal/struct.SignalSet.html
+/// [`SignalSetOnce`]: struct.SignalSetOnce.html
+/// [`as_raw_fd`]:     #method.as_raw_fd
+/// [`Context`]: https://doc.rust-lang.org/std/task/struct.Context.html
+/// [`Ready`]:   https://doc.rust-lang.org/std/task/enum.Poll.html#variant.Ready
+#[derive(Debug)]
+pub struct RawSignalSetOnce {
+    signals: SignalSet,
+    reader: pipe::Reader,
+    /// The catch counts of `signals` when `self` was registered.
+    counts: SignalArray<usize>,
+}
+
+impl Future for RawSignalSetOnce {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Self::Output> {
+        // Drain before checking so that a signal caught in between leaves the
+        // pipe readable for the next wait.
+        self.reader.drain();
+
+        let table = Table::global();
+
+        if table.is_paused() {
+            // `resume` writes to the pipe if a relevant signal was caught.
+            return Poll::Pending;
+        }
+
+        if !table.caught_since(self.signals, &self.counts).is_empty() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+// SAFETY: Caught signals are only read from the process-wide catch counts.
+unsafe impl CancelSafe for RawSignalSetOnce {}
+
+impl AsRawFd for RawSignalSetOnce {
+    /// Returns the file descriptor that becomes readable when `self` should be
+    /// polled again.
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.reader.0
+    }
+}
+
+impl RawSignalSetOnce {
+    /// Registers a handler for `signals` that will only be fulfilled once.
+    pub fn register(signals: SignalSet) -> Result<Self, RegisterOnceError> {
+        let counts = Table::global().counts(signals);
+        let (reader, writer) = pipe::pipe().map_err(super::error::pipe)?;
+
+        if let Err(error) = super::register_set(signals, writer) {
+            unsafe {
+                libc::close(reader.0);
+                libc::close(writer.0);
+            }
+            return Err(error.into());
+        }
+
+        Ok(Self {
+            signals,
+            reader,
+            counts,
+        })
+    }
+
+    /// Returns the signals that `self` is registered for.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.signals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util, Signal};
+
+    fn is_readable(fd: RawFd) -> bool {
+        let mut poll_fd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        unsafe { libc::poll(&mut poll_fd, 1, 0) == 1 }
+    }
+
+    #[test]
+    fn wakes_through_fd() {
+        let signal = Signal::Urgent;
+
+        test_util::run_exclusive(|| {
+            let mut once = RawSignalSetOnce::register(signal.into()).unwrap();
+            assert!(test_util::poll(&mut once).is_pending());
+            assert!(!is_readable(once.as_raw_fd()));
+
+            signal.raise().unwrap();
+            assert!(is_readable(once.as_raw_fd()));
+            assert!(test_util::poll(&mut once).is_ready());
+        });
+    }
+}
@@ -0,0 +1,305 @@
+//! Readiness of the reading end of a pipe, as reported to the executor.
+//!
+//! With the `once-tokio` feature, readiness comes from the tokio reactor.
+//! Otherwise, a single watcher thread polls the pipes of all live futures and
+//! wakes their tasks, which works on any executor.
+
+use std::{
+    io,
+    task::{Context, Poll},
+};
+
+use crate::unix::pipe;
+
+#[cfg(feature = "once-tokio")]
+pub(crate) use self::tokio_impl::Readiness;
+
+#[cfg(not(feature = "once-tokio"))]
+pub(crate) use self::watcher::Readiness;
+
+#[cfg(feature = "once-tokio")]
+mod tokio_impl {
+    use super::*;
+    use tokio::{io::unix::AsyncFd, runtime::Handle};
+
+    #[derive(Debug)]
+    pub(crate) struct Readiness(AsyncFd<pipe::Reader>);
+
+    impl Readiness {
+        pub fn new(reader: pipe::Reader) -> io::Result<Self> {
+            // `AsyncFd::new` panics outside of a runtime.
+            if Handle::try_current().is_err() {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "no tokio runtime is running",
+                ));
+            }
+            AsyncFd::new(reader).map(Self)
+        }
+
+        #[inline]
+        pub fn reader(&self) -> pipe::Reader {
+            *self.0.get_ref()
+        }
+
+        #[inline]
+        pub fn poll_ready(&self, cx: &mut Context) -> Poll<io::Result<()>> {
+            self.0.poll_read_ready(cx).map_ok(drop)
+        }
+
+        pub fn clear_ready(&self, cx: &mut Context) -> io::Result<()> {
+            match self.0.poll_read_ready(cx) {
+                Poll::Ready(Ok(mut guard)) => guard.clear_ready(),
+                Poll::Ready(Err(error)) => return Err(error),
+                Poll::Pending => return Ok(()),
+            }
+
+            // Register interest again so that the task is woken on the next
+            // write. Readiness reported here is seen by the next poll.
+            match self.0.poll_read_ready(cx) {
+                Poll::Ready(Err(error)) => Err(error),
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "once-tokio"))]
+mod watcher {
+    use super::*;
+    use std::{
+        sync::{
+            atomic::{AtomicBool, AtomicI32, Ordering},
+            Arc, Mutex,
+        },
+        task::Waker,
+        thread,
+    };
+
+    /// The pipes being watched.
+    static SLOTS: Mutex<Vec<Arc<Slot>>> = Mutex::new(Vec::new());
+
+    /// The writing end of the pipe that interrupts the watcher thread when
+    /// `SLOTS` changes, or -1 if it has not been spawned.
+    static CONTROL_FD: AtomicI32 = AtomicI32::new(-1);
+
+    /// Serializes spawning the watcher thread.
+    static SPAWN_LOCK: Mutex<()> = Mutex::new(());
+
+    /// The OS error that stopped the watcher thread, or 0 if it hasn't.
+    static FAILURE: AtomicI32 = AtomicI32::new(0);
+
+    #[derive(Debug)]
+    struct Slot {
+        reader: pipe::Reader,
+        /// Whether the pipe was found readable since readiness was cleared.
+        ///
+        /// The watcher thread skips ready pipes so that it doesn't spin.
+        ready: AtomicBool,
+        /// The task to wake once `ready` is set.
+        waker: Mutex<Option<Waker>>,
+    }
+
+    impl Slot {
+        fn set_ready(&self) {
+            // Set the flag before taking the waker, so that a concurrent
+            // `poll_ready` either sees it or leaves its waker to be taken.
+            self.ready.store(true, Ordering::SeqCst);
+            let waker = self
+                .waker
+                .lock()
+                .unwrap_or_else(|error| error.into_inner())
+                .take();
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+
+        fn set_waker(&self, cx: &Context) {
+            let mut waker =
+                self.waker.lock().unwrap_or_else(|error| error.into_inner());
+            match &*waker {
+                Some(waker) if waker.will_wake(cx.waker()) => {}
+                _ => *waker = Some(cx.waker().clone()),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct Readiness(Arc<Slot>);
+
+    impl Readiness {
+        pub fn new(reader: pipe::Reader) -> io::Result<Self> {
+            spawn_watcher()?;
+
+            let slot = Arc::new(Slot {
+                reader,
+                ready: AtomicBool::new(false),
+                waker: Mutex::new(None),
+            });
+
+            lock_slots().push(slot.clone());
+            notify();
+
+            Ok(Self(slot))
+        }
+
+        #[inline]
+        pub fn reader(&self) -> pipe::Reader {
+            self.0.reader
+        }
+
+        pub fn poll_ready(&self, cx: &mut Context) -> Poll<io::Result<()>> {
+            if self.0.ready.load(Ordering::SeqCst) {
+                return Poll::Ready(failure());
+            }
+
+            self.0.set_waker(cx);
+
+            // The watcher may have found the pipe readable, or stopped, before
+            // the waker was stored.
+            if self.0.ready.load(Ordering::SeqCst) {
+                Poll::Ready(failure())
+            } else if let Err(error) = failure() {
+                Poll::Ready(Err(error))
+            } else {
+                Poll::Pending
+            }
+        }
+
+        pub fn clear_ready(&self, cx: &mut Context) -> io::Result<()> {
+            self.0.set_waker(cx);
+            if self.0.ready.swap(false, Ordering::SeqCst) {
+                // Have the watcher resume polling the pipe.
+                notify();
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for Readiness {
+        fn drop(&mut self) {
+            lock_slots().retain(|slot| !Arc::ptr_eq(slot, &self.0));
+            notify();
+        }
+    }
+
+    fn lock_slots() -> std::sync::MutexGuard<'static, Vec<Arc<Slot>>> {
+        SLOTS.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Returns the error that stopped the watcher thread, if any.
+    fn failure() -> io::Result<()> {
+        match FAILURE.load(Ordering::SeqCst) {
+            0 => Ok(()),
+            code => Err(io::Error::from_raw_os_error(code)),
+        }
+    }
+
+    /// Interrupts the watcher thread so that it reloads `SLOTS`.
+    fn notify() {
+        let fd = CONTROL_FD.load(Ordering::SeqCst);
+        if fd >= 0 {
+            pipe::Writer(fd).wake();
+        }
+    }
+
+    fn spawn_watcher() -> io::Result<()> {
+        let _lock =
+            SPAWN_LOCK.lock().unwrap_or_else(|error| error.into_inner());
+
+        if CONTROL_FD.load(Ordering::SeqCst) >= 0 {
+            return failure();
+        }
+
+        let (control, pipe::Writer(writer)) = pipe::pipe()?;
+
+        thread::Builder::new()
+            .name("asygnal-watcher".into())
+            .spawn(move || watch(control))?;
+
+        CONTROL_FD.store(writer, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn watch(control: pipe::Reader) {
+        let mut slots = Vec::new();
+        let mut fds = Vec::new();
+
+        loop {
+            slots.clear();
+            slots.extend(
+                lock_slots()
+                    .iter()
+                    .filter(|slot| !slot.ready.load(Ordering::SeqCst))
+                    .cloned(),
+            );
+
+            fds.clear();
+            fds.extend(
+                Some(control)
+                    .into_iter()
+                    .chain(slots.iter().map(|slot| slot.reader))
+                    .map(|reader| libc::pollfd {
+                        fd: reader.0,
+                        events: libc::POLLIN,
+                        revents: 0,
+                    }),
+            );
+
+            let len = fds.len() as libc::nfds_t;
+            if unsafe { libc::poll(fds.as_mut_ptr(), len, -1) } < 0 {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+
+                // Other errors would recur upon polling again, so every
+                // future is woken up to observe the error instead.
+                let code = error.raw_os_error().unwrap_or(libc::EIO);
+                FAILURE.store(code, Ordering::SeqCst);
+                for slot in lock_slots().iter() {
+                    slot.set_ready();
+                }
+                return;
+            }
+
+            if fds[0].revents != 0 {
+                control.drain();
+            }
+
+            for (slot, fd) in slots.iter().zip(&fds[1..]) {
+                if fd.revents != 0 {
+                    slot.set_ready();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::future::poll_fn;
+
+    #[test]
+    fn ready_upon_write() {
+        let (reader, writer) = pipe::pipe().unwrap();
+
+        // Registering with tokio panics outside of a runtime.
+        #[cfg(feature = "once-tokio")]
+        assert!(Readiness::new(reader).is_err());
+
+        test_util::block_on(async {
+            let readiness = Readiness::new(reader).unwrap();
+            writer.wake();
+            poll_fn(|cx| readiness.poll_ready(cx)).await.unwrap();
+        });
+
+        unsafe {
+            libc::close(reader.0);
+            libc::close(writer.0);
+        }
+    }
+}
@@ -0,0 +1,572 @@
+use std::{
+    future::Future,
+    io, mem,
+    pin::Pin,
+    ptr,
+    sync::{
+        atomic::{AtomicI32, AtomicPtr, AtomicU64, AtomicUsize, Ordering},
+        Mutex,
+    },
+    task::{Context, Poll},
+    thread,
+};
+
+use super::{Driver, ErrorPolicy, RegisterOnceError};
+use crate::{
+    once::CancelSafe,
+    signal::{RealtimeSignal, RealtimeSignalSet},
+    unix::pipe::{Reader, Writer},
+};
+
+const MAX_COUNT: usize = RealtimeSignal::MAX_COUNT;
+
+/// The head of the list of realtime listeners, which are separate from those
+/// in the table since each delivery is written out as a whole `Record`.
+///
+/// Slots are never freed so that the signal handler can safely traverse the
+/// list at any time. Instead, unused slots are reused.
+static SLOTS: AtomicPtr<Slot> = AtomicPtr::new(ptr::null_mut());
+
+/// The number of handlers currently writing to listeners.
+static WAKING: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of listeners for each signal. Our handler is released once this
+/// reaches 0.
+#[allow(clippy::declare_interior_mutable_const)]
+static LISTENERS: [AtomicUsize; MAX_COUNT] = {
+    const ZERO: AtomicUsize = AtomicUsize::new(0);
+    [ZERO; MAX_COUNT]
+};
+
+/// The actions that were in place before installing our handler, used for
+/// releasing signals. This must never be accessed from a signal handler.
+static PREVIOUS: Mutex<[Option<libc::sigaction>; MAX_COUNT]> =
+    Mutex::new([None; MAX_COUNT]);
+
+/// A listener, identified by the writing end of its pipe.
+struct Slot {
+    /// The file descriptor for the writing end of the pipe, or -1 if unused.
+    writer_fd: AtomicI32,
+    /// The bits of the signals that are written to the listener.
+    signals: AtomicU64,
+    /// The next slot in the list, which never changes once set.
+    next: *const Slot,
+}
+
+fn slots() -> impl Iterator<Item = &'static Slot> {
+    let head = SLOTS.load(Ordering::SeqCst) as *const Slot;
+
+    // SAFETY: Slots are leaked and thus live for the rest of the program.
+    std::iter::successors(unsafe { head.as_ref() }, |slot| unsafe {
+        slot.next.as_ref()
+    })
+}
+
+/// Reuses an unused slot for `writer` or allocates a new one.
+fn claim_slot(writer: Writer) -> &'static Slot {
+    for slot in slots() {
+        let claimed = slot.writer_fd.compare_exchange(
+            -1,
+            writer.0,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+        if claimed.is_ok() {
+            return slot;
+        }
+    }
+
+    let slot: &'static mut Slot = Box::leak(Box::new(Slot {
+        writer_fd: AtomicI32::new(writer.0),
+        signals: AtomicU64::new(0),
+        next: ptr::null(),
+    }));
+
+    let mut head = SLOTS.load(Ordering::SeqCst);
+    loop {
+        slot.next = head;
+        match SLOTS.compare_exchange_weak(
+            head,
+            slot,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        ) {
+            Ok(_) => return slot,
+            Err(current) => head = current,
+        }
+    }
+}
+
+/// A single delivery, written to each listener's pipe by the handler.
+///
+/// Records are far smaller than `PIPE_BUF`, so each write is atomic and reads
+/// never observe a partial record.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Record {
+    offset: u32,
+    code: libc::c_int,
+    pid: libc::pid_t,
+    uid: libc::uid_t,
+    value: usize,
+}
+
+extern "C" fn signal_handler(
+    raw_signal: libc::c_int,
+    info: *mut libc::siginfo_t,
+    _: *mut libc::c_void,
+) {
+    let offset = (raw_signal - libc::SIGRTMIN()) as u32;
+    if offset as usize >= MAX_COUNT {
+        return;
+    }
+
+    let info = match unsafe { info.as_ref() } {
+        Some(info) => info,
+        None => return,
+    };
+
+    let record = unsafe {
+        Record {
+            offset,
+            code: info.si_code,
+            pid: info.si_pid(),
+            uid: info.si_uid(),
+            value: info.si_value().sival_ptr as usize,
+        }
+    };
+
+    WAKING.fetch_add(1, Ordering::SeqCst);
+    for slot in slots() {
+        let fd = slot.writer_fd.load(Ordering::SeqCst);
+        if fd >= 0 && slot.signals.load(Ordering::SeqCst) & (1 << offset) != 0 {
+            // Errors are ignored since there's no reasonable way to handle
+            // them from within the signal handler. A full pipe drops the
+            // record, much like the kernel's own queue limit.
+            unsafe {
+                libc::write(
+                    fd,
+                    &record as *const Record as *const libc::c_void,
+                    mem::size_of::<Record>(),
+                );
+            }
+        }
+    }
+    WAKING.fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Installs the handler for each signal in `signals` that has no listeners yet
+/// and makes `writer` receive their records.
+///
+/// If installing fails for any signal, the handlers installed by this call are
+/// restored.
+fn register(signals: RealtimeSignalSet, writer: Writer) -> io::Result<()> {
+    let _lock = super::lock_registry();
+    let mut previous =
+        PREVIOUS.lock().unwrap_or_else(|error| error.into_inner());
+
+    let mut installed = Vec::new();
+    for signal in signals.iter() {
+        let offset = signal.offset() as usize;
+        if LISTENERS[offset].load(Ordering::SeqCst) != 0 {
+            continue;
+        }
+
+        let mut action: libc::sigaction = unsafe { mem::zeroed() };
+        action.sa_sigaction = signal_handler as *const () as usize;
+        action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+        unsafe { libc::sigemptyset(&mut action.sa_mask) };
+
+        let mut old: libc::sigaction = unsafe { mem::zeroed() };
+        let raw_signal = signal.into_raw();
+        if unsafe { libc::sigaction(raw_signal, &action, &mut old) } != 0 {
+            let error = io::Error::last_os_error();
+            for signal in installed.into_iter().rev() {
+                let offset = RealtimeSignal::offset(signal) as usize;
+                if let Some(old) = previous[offset].take() {
+                    let raw_signal = RealtimeSignal::into_raw(signal);
+                    unsafe {
+                        libc::sigaction(raw_signal, &old, ptr::null_mut())
+                    };
+                }
+            }
+            return Err(error);
+        }
+
+        // Signals without listeners have our handler released, so `old` is
+        // never our own.
+        installed.push(signal);
+        previous[offset] = Some(old);
+    }
+
+    for signal in signals.iter() {
+        LISTENERS[signal.offset() as usize].fetch_add(1, Ordering::SeqCst);
+    }
+
+    claim_slot(writer)
+        .signals
+        .fetch_or(signals.0, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Stops writing to `writer`, releasing the signals that no longer have any
+/// listeners.
+fn unregister(writer: Writer) -> io::Result<()> {
+    let _lock = super::lock_registry();
+
+    let slot = match slots()
+        .find(|slot| slot.writer_fd.load(Ordering::SeqCst) == writer.0)
+    {
+        Some(slot) => slot,
+        None => return Ok(()),
+    };
+
+    let signals = RealtimeSignalSet(slot.signals.swap(0, Ordering::SeqCst));
+    slot.writer_fd.store(-1, Ordering::SeqCst);
+
+    // Wait for handlers that may have loaded the file descriptor, so that the
+    // caller can safely close it.
+    while WAKING.load(Ordering::SeqCst) != 0 {
+        thread::yield_now();
+    }
+
+    let mut previous =
+        PREVIOUS.lock().unwrap_or_else(|error| error.into_inner());
+    let mut result = Ok(());
+
+    for signal in signals.iter() {
+        let offset = signal.offset() as usize;
+        if LISTENERS[offset].fetch_sub(1, Ordering::SeqCst) != 1 {
+            continue;
+        }
+        if let Some(action) = previous[offset].take() {
+            let raw_signal = signal.into_raw();
+            if unsafe { libc::sigaction(raw_signal, &action, ptr::null_mut()) }
+                != 0
+                && result.is_ok()
+            {
+                result = Err(io::Error::last_os_error());
+            }
+        }
+    }
+
+    result
+}
+
+/// A received [`RealtimeSignal`], along with details about who sent it.
+///
+/// [`RealtimeSignal`]: ../../signal/struct.RealtimeSignal.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RealtimeEvent {
+    signal: RealtimeSignal,
+    code: libc::c_int,
+    pid: libc::pid_t,
+    uid: libc::uid_t,
+    value: usize,
+}
+
+impl RealtimeEvent {
+    /// Returns the signal that was received.
+    #[inline]
+    pub fn signal(&self) -> RealtimeSignal {
+        self.signal
+    }
+
+    /// Returns the `sigval` payload, if the signal was sent via `sigqueue`
+    /// or a mechanism like it, such as a POSIX timer.
+    #[inline]
+    pub fn value(&self) -> Option<usize> {
+        match self.code {
+            libc::SI_QUEUE
+            | libc::SI_TIMER
+            | libc::SI_MESGQ
+            | libc::SI_ASYNCIO => Some(self.value),
+            _ => None,
+        }
+    }
+
+    /// Returns the `si_pid` field, which is the ID of the sending process.
+    ///
+    /// This is only meaningful if the signal was sent by a process, such as
+    /// via `kill` or `sigqueue`.
+    #[inline]
+    pub fn pid(&self) -> libc::pid_t {
+        self.pid
+    }
+
+    /// Returns the `si_uid` field, which is the real user ID of the sending
+    /// process.
+    ///
+    /// This is meaningful under the same conditions as [`pid`](#method.pid).
+    #[inline]
+    pub fn uid(&self) -> libc::uid_t {
+        self.uid
+    }
+
+    /// Returns the `si_code` field, which describes why the signal was sent,
+    /// such as `SI_QUEUE` for `sigqueue`.
+    #[inline]
+    pub fn code(&self) -> libc::c_int {
+        self.code
+    }
+}
+
+/// A repeatedly fulfilled listener for the realtime signals in a set.
+///
+/// Unlike `Listener`, every delivery is returned, in the order received.
+#[derive(Debug)]
+pub(crate) struct RealtimeListener {
+    signals: RealtimeSignalSet,
+    driver: Driver,
+}
+
+impl Drop for RealtimeListener {
+    fn drop(&mut self) {
+        // There's no reasonable way to report failing to restore an action.
+        // This happens before `driver` closes the pipe.
+        let _ = unregister(self.driver.writer);
+    }
+}
+
+impl RealtimeListener {
+    /// Registers a handler for `signals`.
+    pub fn register(signals: RealtimeSignalSet) -> io::Result<Self> {
+        // Dropping the driver upon failure closes the pipe.
+        let driver = Driver::new()?;
+        register(signals, driver.writer)?;
+        Ok(Self { signals, driver })
+    }
+
+    /// Returns the signals handled by `self`.
+    #[inline]
+    pub fn signals(&self) -> RealtimeSignalSet {
+        self.signals
+    }
+
+    /// Uses `policy` for handling errors instead of the global policy.
+    #[inline]
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) {
+        self.driver.policy = Some(policy);
+    }
+
+    /// Handles `error` according to the error policy, returning whether the
+    /// caller should resolve.
+    #[inline]
+    pub fn handle_error(&mut self, error: io::Error) -> Poll<()> {
+        self.driver.handle_error(error)
+    }
+
+    /// Takes the error stored under `ErrorPolicy::ResolveWithError`, if any.
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.driver.error.take()
+    }
+
+    /// Stops listening for new signals, keeping those received so far so that
+    /// they are returned by `read_pending`.
+    #[cfg(feature = "stream")]
+    #[inline]
+    pub fn close(&mut self) -> io::Result<()> {
+        unregister(self.driver.writer)
+    }
+
+    /// Reads the next delivery not yet returned without waiting, if any.
+    #[cfg(feature = "stream")]
+    #[inline]
+    pub fn read_pending(&mut self) -> io::Result<Option<RealtimeEvent>> {
+        read_event(self.driver.readiness.reader())
+    }
+
+    /// Polls for the next signal received.
+    pub fn poll_next(
+        &mut self,
+        cx: &mut Context,
+    ) -> Poll<io::Result<RealtimeEvent>> {
+        loop {
+            if let Some(event) = read_event(self.driver.readiness.reader())? {
+                return Poll::Ready(Ok(event));
+            }
+
+            match self.driver.readiness.poll_ready(cx) {
+                Poll::Ready(Ok(())) => {
+                    // Read again after clearing readiness, so that no record
+                    // written in between is missed.
+                    self.driver.readiness.clear_ready(cx)?;
+                }
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Reads the next record from `reader` without blocking.
+fn read_event(reader: Reader) -> io::Result<Option<RealtimeEvent>> {
+    let mut record = mem::MaybeUninit::<Record>::uninit();
+    let size = mem::size_of::<Record>();
+
+    loop {
+        let n =
+            unsafe { libc::read(reader.0, record.as_mut_ptr().cast(), size) };
+        if n == size as isize {
+            break;
+        }
+        let error = match n {
+            -1 => io::Error::last_os_error(),
+            _ => return Err(io::ErrorKind::UnexpectedEof.into()),
+        };
+        match error.kind() {
+            io::ErrorKind::Interrupted => continue,
+            io::ErrorKind::WouldBlock => return Ok(None),
+            _ => return Err(error),
+        }
+    }
+
+    let record = unsafe { record.assume_init() };
+    Ok(Some(RealtimeEvent {
+        // The handler only writes offsets within bounds.
+        signal: RealtimeSignal::new(record.offset as u8)
+            .unwrap_or_else(RealtimeSignal::min),
+        code: record.code,
+        pid: record.pid,
+        uid: record.uid,
+        value: record.value,
+    }))
+}
+
+/// A future that is fulfilled once upon receiving a [`RealtimeSignal`] in a
+/// [`RealtimeSignalSet`].
+///
+/// The future resolves with the first signal received, or with `None` only if
+/// an internal error fulfilled it under [`ErrorPolicy::ResolveWithError`].
+///
+/// Dropping an instance unregisters it, restoring the previous action of each
+/// signal that has no other listeners.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{
+///     once::signal::RealtimeSignalOnce,
+///     signal::{RealtimeSignal, RealtimeSignalSet},
+/// };
+///
+/// # async fn example() -> Result<(), asygnal::once::signal::RegisterOnceError> {
+/// let signals = RealtimeSignalSet::from(RealtimeSignal::min());
+///
+/// if let Some(event) = RealtimeSignalOnce::register(signals)?.await {
+///     println!("{} with payload {:?}", event.signal(), event.value());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`RealtimeSignal`]:    ../../signal/struct.RealtimeSignal.html
+/// [`RealtimeSignalSet`]: ../../signal/struct.RealtimeSignalSet.html
+/// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
+#[derive(Debug)]
+pub struct RealtimeSignalOnce {
+    listener: RealtimeListener,
+    /// The event that fulfilled `self`, if any.
+    event: Option<RealtimeEvent>,
+    done: bool,
+}
+
+impl Future for RealtimeSignalOnce {
+    type Output = Option<RealtimeEvent>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        if this.done {
+            return Poll::Ready(this.event);
+        }
+
+        match this.listener.poll_next(cx) {
+            Poll::Ready(Ok(event)) => this.event = Some(event),
+            Poll::Ready(Err(error)) => {
+                match this.listener.handle_error(error) {
+                    Poll::Ready(()) => {}
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            Poll::Pending => return Poll::Pending,
+        }
+
+        this.done = true;
+        Poll::Ready(this.event)
+    }
+}
+
+// SAFETY: Each listener receives its own copy of every record, and only reads
+// one in the same poll that returns it.
+unsafe impl CancelSafe for RealtimeSignalOnce {}
+
+impl RealtimeSignalOnce {
+    /// Registers a handler for `signals` that will only be fulfilled once.
+    pub fn register(
+        signals: RealtimeSignalSet,
+    ) -> Result<Self, RegisterOnceError> {
+        Ok(Self {
+            listener: RealtimeListener::register(signals)?,
+            event: None,
+            done: false,
+        })
+    }
+
+    /// Returns the signals that `self` is registered for.
+    #[inline]
+    pub fn signals(&self) -> RealtimeSignalSet {
+        self.listener.signals()
+    }
+
+    /// Returns `self` with `policy` used for handling internal errors instead
+    /// of the [global policy](enum.ErrorPolicy.html#method.global).
+    #[inline]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.listener.set_error_policy(policy);
+        self
+    }
+
+    /// Takes the internal error that fulfilled `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.listener.take_error()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::future::poll_fn;
+
+    #[test]
+    fn queued_values() {
+        let signal = RealtimeSignal::min();
+        let pid = unsafe { libc::getpid() };
+
+        let events = test_util::block_on(async {
+            let mut listener =
+                RealtimeListener::register(signal.into()).unwrap();
+
+            signal.queue(pid, 1).unwrap();
+            signal.queue(pid, 2).unwrap();
+
+            let first = poll_fn(|cx| listener.poll_next(cx)).await;
+            let second = poll_fn(|cx| listener.poll_next(cx)).await;
+            [first.unwrap(), second.unwrap()]
+        });
+
+        // Unlike standard signals, both deliveries are received.
+        for (event, value) in events.iter().zip([1, 2]) {
+            assert_eq!(event.signal(), signal);
+            assert_eq!(event.value(), Some(value));
+            assert_eq!(event.pid(), pid);
+        }
+    }
+}
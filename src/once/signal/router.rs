@@ -0,0 +1,189 @@
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::{listener::Listener, RegisterOnceError};
+use crate::{Signal, SignalSet};
+
+/// Dispatches signals to user-chosen keys from a single listener.
+///
+/// Each route maps a [`SignalSet`] to a key. Awaiting [`next`] returns the key
+/// for the next signal received, which allows for handling e.g. reload,
+/// shutdown, and status requests in one loop without a `select!` arm for each.
+///
+/// Unlike the futures in this module, a router is fulfilled repeatedly.
+/// Signals received in between calls to [`next`] are coalesced, with each
+/// pending signal dispatched once in order of its integer value.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{once::signal::SignalRouter, SignalSet};
+///
+/// #[derive(Clone, Copy)]
+/// enum Action {
+///     Reload,
+///     Shutdown,
+/// }
+///
+/// # async fn example() -> std::io::Result<()> {
+/// let mut router = SignalRouter::register(vec![
+///     (SignalSet::new().hangup(), Action::Reload),
+///     (SignalSet::new().interrupt().terminate(), Action::Shutdown),
+/// ])
+/// .expect("failed to register");
+///
+/// loop {
+///     match router.next().await? {
+///         Action::Reload => println!("reloading"),
+///         Action::Shutdown => break,
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`SignalSet`]: ../../signal/struct.SignalSet.html
+/// [`next`]:      #method.next
+#[derive(Debug)]
+pub struct SignalRouter<K> {
+    routes: Vec<(SignalSet, K)>,
+    listener: Listener,
+}
+
+impl<K: Clone> SignalRouter<K> {
+    /// Registers a handler for the signals of each route.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegisterOnceError::Registered`] with the conflicting signals if
+    /// any signal appears in more than one route.
+    ///
+    /// [`RegisterOnceError::Registered`]: enum.RegisterOnceError.html#variant.Registered
+    pub fn register<I>(routes: I) -> Result<Self, RegisterOnceError>
+    where
+        I: IntoIterator<Item = (SignalSet, K)>,
+    {
+        let routes: Vec<(SignalSet, K)> = routes.into_iter().collect();
+
+        let mut signals = SignalSet::new();
+        let mut conflicts = SignalSet::new();
+
+        for &(route, _) in &routes {
+            for signal in route {
+                if signals.contains(signal) {
+                    conflicts.insert(signal);
+                }
+            }
+            signals.insert(route);
+        }
+
+        if !conflicts.is_empty() {
+            return Err(RegisterOnceError::Registered(conflicts));
+        }
+
+        let listener = Listener::register(signals)?;
+        Ok(Self { routes, listener })
+    }
+
+    /// Returns a future that resolves to the key of the next signal received.
+    // Named after `StreamExt::next`, which this mirrors.
+    #[allow(clippy::should_implement_trait)]
+    #[inline]
+    pub fn next(&mut self) -> Next<'_, K> {
+        Next { router: self }
+    }
+
+    /// Polls for the key of the next signal received.
+    pub fn poll_next(&mut self, cx: &mut Context) -> Poll<io::Result<K>> {
+        self.listener
+            .poll_next(cx)
+            .map_ok(|signal| self.key(signal))
+    }
+
+    /// Returns the signals handled by `self`.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.listener.signals()
+    }
+
+    fn key(&self, signal: Signal) -> K {
+        let (_, key) = self
+            .routes
+            .iter()
+            .find(|(route, _)| route.contains(signal))
+            .expect("caught signal without a route");
+        key.clone()
+    }
+}
+
+/// A future that resolves to the key of the next signal received by a
+/// [`SignalRouter`].
+///
+/// This is created by [`SignalRouter::next`].
+///
+/// [`SignalRouter`]:       struct.SignalRouter.html
+/// [`SignalRouter::next`]: struct.SignalRouter.html#method.next
+#[derive(Debug)]
+#[must_use = "futures do nothing unless awaited"]
+pub struct Next<'a, K> {
+    router: &'a mut SignalRouter<K>,
+}
+
+impl<K: Clone> Future for Next<'_, K> {
+    type Output = io::Result<K>;
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.router.poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn rejects_overlapping_routes() {
+        let error = SignalRouter::register(vec![
+            (Signal::Urgent | Signal::WindowChange, 0),
+            (Signal::WindowChange.into(), 1),
+        ])
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            RegisterOnceError::Registered(signals)
+                if signals == Signal::WindowChange.into()
+        ));
+    }
+
+    #[test]
+    fn dispatches_to_keys() {
+        test_util::run_exclusive(|| {
+            let mut router = SignalRouter::register(vec![
+                (SignalSet::from(Signal::Urgent), "urgent"),
+                (SignalSet::from(Signal::WindowChange), "resize"),
+            ])
+            .unwrap();
+            assert!(test_util::poll(&mut router.next()).is_pending());
+
+            Signal::WindowChange.raise().unwrap();
+            Signal::Urgent.raise().unwrap();
+
+            // Pending signals are dispatched in order of their integer value,
+            // and `SIGURG` is lower than `SIGWINCH` on every target.
+            for key in ["urgent", "resize"] {
+                match test_util::poll(&mut router.next()) {
+                    Poll::Ready(Ok(next)) => assert_eq!(next, key),
+                    poll => panic!("unexpected poll: {:?}", poll),
+                }
+            }
+            assert!(test_util::poll(&mut router.next()).is_pending());
+        });
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    io, mem,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Wake, Waker},
+};
+
+use super::{Driver, ErrorPolicy};
+use crate::unix::pipe;
+
+/// A [`Driver`] shared by clones of a future.
+///
+/// Every task polling through it is woken upon the next write to the pipe,
+/// rather than only the one that polled last.
+#[derive(Debug)]
+pub(super) struct SharedDriver {
+    driver: Mutex<Driver>,
+    /// Copied out of `driver` so that it can be read without locking.
+    writer: pipe::Writer,
+    wakers: Arc<Wakers>,
+}
+
+impl SharedDriver {
+    #[inline]
+    pub fn new(driver: Driver) -> Arc<Self> {
+        Arc::new(Self {
+            writer: driver.writer,
+            driver: Mutex::new(driver),
+            wakers: Arc::default(),
+        })
+    }
+
+    /// The writing end of the pipe, used for identifying our table entries.
+    #[inline]
+    pub fn writer(&self) -> pipe::Writer {
+        self.writer
+    }
+
+    /// Calls `f` with the driver on behalf of the task of `cx`.
+    ///
+    /// Errors are handled according to `policy`, with any error that fulfills
+    /// the future stored in `error` rather than in the shared driver.
+    pub fn poll<T>(
+        &self,
+        cx: &mut Context,
+        policy: Option<ErrorPolicy>,
+        error: &mut Option<io::Error>,
+        f: impl FnOnce(&mut Driver, &mut Context) -> Poll<T>,
+    ) -> Poll<T> {
+        self.wakers.insert(cx.waker());
+
+        let waker = Waker::from(Arc::clone(&self.wakers));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut driver = self.lock();
+        driver.policy = policy;
+        let poll = f(&mut driver, &mut cx);
+        if let Some(driver_error) = driver.error.take() {
+            *error = Some(driver_error);
+        }
+        poll
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Driver> {
+        // The driver is left consistent if a poll panics.
+        self.driver
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// The wakers of every task waiting on a `SharedDriver`, woken all at once.
+#[derive(Debug, Default)]
+struct Wakers(Mutex<Vec<Waker>>);
+
+impl Wakers {
+    fn lock(&self) -> MutexGuard<'_, Vec<Waker>> {
+        self.0.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn insert(&self, waker: &Waker) {
+        let mut wakers = self.lock();
+        if !wakers.iter().any(|other| other.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+}
+
+impl Wake for Wakers {
+    #[inline]
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        // Taken before waking so that tasks polling in response can insert
+        // their wakers again.
+        for waker in mem::take(&mut *self.lock()) {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn wakes_every_task() {
+        let a = Arc::<CountingWaker>::default();
+        let b = Arc::<CountingWaker>::default();
+        let wakers = Arc::<Wakers>::default();
+
+        wakers.insert(&Waker::from(Arc::clone(&a)));
+        wakers.insert(&Waker::from(Arc::clone(&b)));
+        wakers.insert(&Waker::from(Arc::clone(&b)));
+
+        let waker = Waker::from(Arc::clone(&wakers));
+        waker.wake_by_ref();
+        waker.wake();
+
+        assert_eq!(a.0.load(Ordering::SeqCst), 1);
+        assert_eq!(b.0.load(Ordering::SeqCst), 1);
+        assert!(wakers.lock().is_empty());
+    }
+}
@@ -1,71 +1,269 @@
 use std::{
     future::Future,
+    io,
     pin::Pin,
-    sync::atomic::Ordering,
+    sync::{atomic::Ordering, Arc},
     task::{Context, Poll},
 };
 
-use super::{table::Table, Driver, RegisterOnceError};
-use crate::{unix::pipe, Signal};
+use super::{
+    shared::SharedDriver, table::Table, Driver, ErrorPolicy, RegisterOnceError,
+};
+use crate::{once::CancelSafe, Signal};
 
 /// A future that is fulfilled once upon receiving a [`Signal`].
 ///
+/// Dropping an instance unregisters it, restoring the previous action of each
+/// signal that has no other listeners.
+///
 /// After an instance is fulfilled, all subsequent polls will return [`Ready`].
 ///
-/// [`Signal`]: ../../unix/enum.Signal.html
+/// Cloning an instance is cheap and shares its registration, so that many
+/// tasks can wait for the same signal. Every clone is woken upon the signal,
+/// and it is unregistered once all clones are dropped.
+///
+/// With the `stream` feature, this implements `FusedFuture` for use in
+/// `futures::select!`.
+///
+/// [`Signal`]: ../../signal/enum.Signal.html
 ///
 /// [`Ready`]: https://doc.rust-lang.org/std/task/enum.Poll.html#variant.Ready
 #[derive(Debug)]
 pub struct SignalOnce {
     pub(super) signal: Signal,
-    pub(super) driver: Driver,
+    pub(super) driver: Arc<SharedDriver>,
+    /// The policy for handling errors, or `None` to use the global policy.
+    pub(super) policy: Option<ErrorPolicy>,
+    /// The error that fulfilled `self` under `ErrorPolicy::ResolveWithError`.
+    pub(super) error: Option<io::Error>,
+    /// The catch count of `signal` when `self` was registered.
+    pub(super) count: usize,
+    /// Whether `self` has been fulfilled.
+    fulfilled: bool,
 }
 
 impl Future for SignalOnce {
     type Output = ();
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
-        let table = Table::global();
-
-        if table.caught.load(Ordering::SeqCst).contains(self.signal) {
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if self.fulfilled {
             return Poll::Ready(());
         }
 
-        self.driver.poll(cx)
+        let this = &mut *self;
+        let entry = Table::global().entry(this.signal);
+        let count = this.count;
+        let caught = || entry.count.load(Ordering::SeqCst) != count;
+
+        let poll =
+            this.driver
+                .poll(cx, this.policy, &mut this.error, |driver, cx| {
+                    if Table::global().is_paused() {
+                        return driver.park(cx);
+                    }
+
+                    if caught() {
+                        return Poll::Ready(());
+                    }
+
+                    match driver.poll_readable(cx) {
+                        Poll::Ready(true) => {
+                            // Clear readiness before checking again, so that a
+                            // signal caught in between wakes up the task.
+                            if driver.park(cx).is_ready() || caught() {
+                                Poll::Ready(())
+                            } else {
+                                Poll::Pending
+                            }
+                        }
+                        Poll::Ready(false) => Poll::Ready(()),
+                        Poll::Pending => Poll::Pending,
+                    }
+                });
+
+        if poll.is_ready() {
+            self.fulfilled = true;
+        }
+        poll
+    }
+}
+
+/// Shares the registration of `self`, without the internal error that
+/// fulfilled it, if any.
+impl Clone for SignalOnce {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            signal: self.signal,
+            driver: Arc::clone(&self.driver),
+            policy: self.policy,
+            error: None,
+            count: self.count,
+            fulfilled: self.fulfilled,
+        }
+    }
+}
+
+// SAFETY: This only wraps `SignalSetOnce`.
+unsafe impl CancelSafe for SignalOnce {}
+
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::future::FusedFuture for SignalOnce {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.fulfilled
     }
 }
 
 impl SignalOnce {
     /// Registers a handler for `signal` that will only be fulfilled once.
     pub fn register(signal: Signal) -> Result<Self, RegisterOnceError> {
-        // TODO: Handle `signal` already being registered.
+        // Only deliveries after registering fulfill `self`.
+        let count = Table::global().entry(signal).count.load(Ordering::SeqCst);
+        let driver = Driver::register(signal.into())?;
+        Ok(Self {
+            signal,
+            driver: SharedDriver::new(driver),
+            policy: None,
+            error: None,
+            count,
+            fulfilled: false,
+        })
+    }
 
-        let (reader, writer) = pipe::pipe()?;
+    /// Registers a handler for `signal` like [`register`], with `label`
+    /// attributing it to a component of the application.
+    ///
+    /// The label can be retrieved via [`registration_label`].
+    ///
+    /// [`register`]: #method.register
+    /// [`registration_label`]: fn.registration_label.html
+    pub fn register_labeled(
+        signal: Signal,
+        label: &'static str,
+    ) -> Result<Self, RegisterOnceError> {
+        let once = Self::register(signal)?;
+        super::set_label(signal.into(), Some(label));
+        Ok(once)
+    }
 
-        let close_pipe = || unsafe {
-            libc::close(reader.0);
-            libc::close(writer.0);
-        };
+    /// Returns `self` with `policy` used for handling internal errors instead
+    /// of the [global policy](enum.ErrorPolicy.html#method.global).
+    #[inline]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
 
-        let driver = match Driver::new(reader) {
-            Ok(d) => d,
-            Err(error) => {
-                close_pipe();
-                return Err(error.into());
-            }
-        };
+    /// Returns whether `self` has been fulfilled, after which every poll
+    /// returns [`Ready`].
+    ///
+    /// [`Ready`]: https://doc.rust-lang.org/std/task/enum.Poll.html#variant.Ready
+    #[inline]
+    pub fn is_fulfilled(&self) -> bool {
+        self.fulfilled
+    }
+
+    /// Takes the internal error that fulfilled `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn ignores_earlier_deliveries() {
+        let signal = Signal::Io;
+
+        test_util::run_exclusive(|| {
+            let mut first = SignalOnce::register(signal).unwrap();
+            assert!(test_util::poll(&mut first).is_pending());
+
+            signal.raise().unwrap();
+            assert!(test_util::poll(&mut first).is_ready());
+
+            // Registering again waits for the next delivery.
+            let mut second = SignalOnce::register(signal).unwrap();
+            assert!(test_util::poll(&mut second).is_pending());
 
-        Table::global()
-            .entry(signal)
-            .writer_fd
-            .store(writer.0, Ordering::SeqCst);
-
-        match super::register_signal(signal) {
-            Ok(_) => Ok(Self { signal, driver }),
-            Err(error) => {
-                close_pipe();
-                Err(error.into())
-            }
+            signal.raise().unwrap();
+            assert!(test_util::poll(&mut second).is_ready());
+        });
+    }
+
+    #[test]
+    fn restores_previous_handler() {
+        use std::sync::atomic::AtomicBool;
+
+        static HANDLED: AtomicBool = AtomicBool::new(false);
+
+        extern "C" fn handler(_: libc::c_int) {
+            HANDLED.store(true, Ordering::SeqCst);
         }
+
+        let signal = Signal::FloatExc;
+
+        let set_handler = |handler: libc::sighandler_t| unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handler;
+            assert_eq!(
+                libc::sigaction(
+                    signal.into_raw(),
+                    &action,
+                    std::ptr::null_mut()
+                ),
+                0
+            );
+        };
+
+        set_handler(handler as *const () as libc::sighandler_t);
+
+        test_util::run(|| {
+            let once = SignalOnce::register(signal).unwrap();
+            drop(once.clone());
+
+            // Clones share the registration.
+            signal.raise().unwrap();
+            assert!(!HANDLED.load(Ordering::SeqCst));
+            drop(once);
+        });
+
+        signal.raise().unwrap();
+        assert!(HANDLED.load(Ordering::SeqCst));
+
+        set_handler(libc::SIG_DFL);
+    }
+
+    #[test]
+    fn fulfilled_after_ready() {
+        let signal = Signal::Io;
+
+        test_util::run_exclusive(|| {
+            let mut once = SignalOnce::register(signal).unwrap();
+            assert!(test_util::poll(&mut once).is_pending());
+            assert!(!once.is_fulfilled());
+
+            // Catching the signal alone doesn't fulfill `once` until polled.
+            signal.raise().unwrap();
+            assert!(!once.is_fulfilled());
+
+            assert!(test_util::poll(&mut once).is_ready());
+            assert!(once.is_fulfilled());
+            #[cfg(feature = "stream")]
+            assert!(futures_core::future::FusedFuture::is_terminated(&once));
+
+            // Clones share whether the original was fulfilled.
+            assert!(once.clone().is_fulfilled());
+        });
     }
 }
@@ -1,98 +1,558 @@
 use std::{
     future::Future,
+    io,
     pin::Pin,
-    sync::atomic::Ordering,
+    sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 
-use super::{table::Table, Driver, RegisterOnceError, SignalOnce};
-use crate::{unix::pipe, SignalSet};
+use super::{
+    shared::SharedDriver, table::Table, Coalesce, Driver, ErrorPolicy,
+    Fallible, RegisterOnceError, SignalOnce,
+};
+use crate::{
+    once::CancelSafe,
+    signal::{Signal, SignalArray},
+    SignalSet,
+};
 
 /// A future that is fulfilled once upon receiving a [`Signal`] in a
 /// [`SignalSet`].
 ///
-/// After an instance is fulfilled, all subsequent polls will return [`Ready`].
+/// The future resolves with the signal that was received, or with the lowest
+/// one if several were received at once. It resolves with `None` only if an
+/// internal error fulfilled it under [`ErrorPolicy::ResolveWithError`].
+///
+/// Dropping an instance unregisters it, restoring the previous action of each
+/// signal that has no other listeners.
+///
+/// After an instance is fulfilled, all subsequent polls will return [`Ready`]
+/// with the same output.
+///
+/// Cloning an instance is cheap and shares its registration, so that many
+/// tasks can wait for the same signals. Every clone is woken upon a signal and
+/// resolves with the same output, and the signals are unregistered once all
+/// clones are dropped.
+///
+/// With the `stream` feature, this implements `FusedFuture` for use in
+/// `futures::select!`.
+///
+/// # Examples
 ///
-/// [`Signal`]:    ../../unix/enum.Signal.html
-/// [`SignalSet`]: ../../unix/struct.SignalSet.html
+/// ```no_run
+/// use asygnal::{Signal, SignalSet};
+///
+/// # async fn example() -> Result<(), asygnal::once::signal::RegisterOnceError> {
+/// let signal = SignalSet::termination().register_once()?.await;
+///
+/// let code = match signal {
+///     Some(Signal::Interrupt) => 130,
+///     _ => 143,
+/// };
+/// # let _ = code;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Signal`]:    ../../signal/enum.Signal.html
+/// [`SignalSet`]: ../../signal/struct.SignalSet.html
+/// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
 ///
 /// [`Ready`]: https://doc.rust-lang.org/std/task/enum.Poll.html#variant.Ready
 #[derive(Debug)]
 pub struct SignalSetOnce {
     signals: SignalSet,
-    driver: Driver,
+    driver: Arc<SharedDriver>,
+    /// The policy for handling errors, or `None` to use the global policy.
+    policy: Option<ErrorPolicy>,
+    /// The error that fulfilled `self` under `ErrorPolicy::ResolveWithError`.
+    error: Option<io::Error>,
+    /// The catch counts of `signals` when `self` was registered.
+    counts: SignalArray<usize>,
+    /// The signal that fulfilled `self`, if any.
+    caught: Option<Signal>,
+    /// Whether `self` has been fulfilled.
+    fulfilled: bool,
 }
 
 impl From<SignalOnce> for SignalSetOnce {
     #[inline]
     fn from(signal: SignalOnce) -> Self {
-        let signals = SignalSet::from(signal.signal);
-        let driver = signal.driver;
-        Self { signals, driver }
+        let mut counts = [0; Signal::NUM];
+        counts[signal.signal as usize] = signal.count;
+
+        Self {
+            signals: SignalSet::from(signal.signal),
+            driver: signal.driver,
+            policy: signal.policy,
+            error: signal.error,
+            counts,
+            caught: None,
+            fulfilled: false,
+        }
     }
 }
 
-impl Future for SignalSetOnce {
-    type Output = ();
+/// Shares the registration of `self`, without the internal error that
+/// fulfilled it, if any.
+impl Clone for SignalSetOnce {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            signals: self.signals,
+            driver: Arc::clone(&self.driver),
+            policy: self.policy,
+            error: None,
+            counts: self.counts,
+            caught: self.caught,
+            fulfilled: self.fulfilled,
+        }
+    }
+}
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
-        let table = Table::global();
+impl Future for SignalSetOnce {
+    type Output = Option<Signal>;
 
-        if table
-            .caught
-            .load(Ordering::SeqCst)
-            .contains_any(self.signals)
-        {
-            return Poll::Ready(());
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let poll = self.poll_caught(cx);
+        if poll.is_ready() {
+            self.fulfilled = true;
         }
+        poll
+    }
+}
 
-        self.driver.poll(cx)
+// SAFETY: Caught signals are only read from the process-wide catch counts.
+unsafe impl CancelSafe for SignalSetOnce {}
+
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::future::FusedFuture for SignalSetOnce {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.fulfilled
     }
 }
 
 impl SignalSetOnce {
+    fn poll_caught(&mut self, cx: &mut Context) -> Poll<Option<Signal>> {
+        if let Some(signal) = self.caught {
+            return Poll::Ready(Some(signal));
+        }
+
+        let signals = self.signals;
+        let counts = &self.counts;
+        let first_caught =
+            || Table::global().caught_since(signals, counts).first();
+        let poll =
+            self.driver
+                .poll(cx, self.policy, &mut self.error, |driver, cx| {
+                    if Table::global().is_paused() {
+                        return driver.park(cx).map(|()| None);
+                    }
+
+                    if let Some(signal) = first_caught() {
+                        return Poll::Ready(Some(signal));
+                    }
+
+                    match driver.poll_readable(cx) {
+                        Poll::Ready(true) => {
+                            // Clear readiness before checking again, so that a signal
+                            // caught in between wakes up the task.
+                            if driver.park(cx).is_ready() {
+                                return Poll::Ready(None);
+                            }
+                            match first_caught() {
+                                Some(signal) => Poll::Ready(Some(signal)),
+                                None => Poll::Pending,
+                            }
+                        }
+                        Poll::Ready(false) => Poll::Ready(None),
+                        Poll::Pending => Poll::Pending,
+                    }
+                });
+
+        if let Poll::Ready(caught) = poll {
+            self.caught = caught;
+        }
+        poll
+    }
+
     /// Registers a handler for `signals` that will only be fulfilled once.
     pub fn register(signals: SignalSet) -> Result<Self, RegisterOnceError> {
-        // TODO: Handle a signal in `signals` already being registered.
+        let counts = Table::global().counts(signals);
+        let driver = Driver::register(signals)?;
+        Ok(Self::from_driver(signals, counts, driver))
+    }
 
-        let (reader, writer) = pipe::pipe()?;
+    /// Registers a handler for each signal in `signals` that can be handled,
+    /// returning an instance for those along with the error for each signal
+    /// that cannot.
+    ///
+    /// Unlike [`register`], failing to install one handler does not fail the
+    /// whole set. This suits sets shared across platforms, where some signal
+    /// may not be available on every one. The returned future is only
+    /// fulfilled by the signals in its [`signals`], and never if that is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegisterOnceError::Pipe`] if the pipe for waking up the
+    /// future cannot be created, in which case no handlers are installed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asygnal::SignalSet;
+    ///
+    /// # fn example() -> Result<(), asygnal::once::signal::RegisterOnceError> {
+    /// let (once, errors) = SignalSet::termination().register_once_partial()?;
+    ///
+    /// for error in errors {
+    ///     eprintln!("cannot handle signal: {}", error);
+    /// }
+    /// println!("waiting for {:?}", once.signals());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`register`]: #method.register
+    /// [`signals`]: #method.signals
+    /// [`RegisterOnceError::Pipe`]: enum.RegisterOnceError.html#variant.Pipe
+    pub fn register_partial(
+        signals: SignalSet,
+    ) -> Result<(Self, Vec<RegisterOnceError>), RegisterOnceError> {
+        let counts = Table::global().counts(signals);
+        let driver = Driver::new()?;
+        let (registered, errors) =
+            super::register_set_partial(signals, driver.writer);
+        Ok((Self::from_driver(registered, counts, driver), errors))
+    }
 
-        let close_pipe = || unsafe {
-            libc::close(reader.0);
-            libc::close(writer.0);
-        };
+    /// Creates an instance for `signals` woken up by `driver`, fulfilled once
+    /// their catch counts move past `counts`.
+    pub(super) fn from_driver(
+        signals: SignalSet,
+        counts: SignalArray<usize>,
+        driver: Driver,
+    ) -> Self {
+        Self {
+            signals,
+            driver: SharedDriver::new(driver),
+            policy: None,
+            error: None,
+            counts,
+            caught: None,
+            fulfilled: false,
+        }
+    }
 
-        let driver = match Driver::new(reader) {
-            Ok(d) => d,
-            Err(error) => {
-                close_pipe();
-                return Err(error.into());
-            }
-        };
-
-        let mut old_handles =
-            Vec::<super::RegisteredSignal>::with_capacity(signals.len());
-
-        for signal in signals {
-            Table::global()
-                .entry(signal)
-                .writer_fd
-                .store(writer.0, Ordering::SeqCst);
-
-            match super::register_signal(signal) {
-                Ok(handle) => {
-                    old_handles.push(handle);
-                }
-                Err(error) => {
-                    old_handles.into_iter().for_each(|handle| {
-                        handle.reset();
-                    });
-                    close_pipe();
-                    return Err(error.into());
-                }
-            }
+    /// Registers a handler for `signals` like [`register`], with `label`
+    /// attributing it to a component of the application.
+    ///
+    /// The label can be retrieved via [`registration_label`].
+    ///
+    /// [`register`]: #method.register
+    /// [`registration_label`]: fn.registration_label.html
+    pub fn register_labeled(
+        signals: SignalSet,
+        label: &'static str,
+    ) -> Result<Self, RegisterOnceError> {
+        let once = Self::register(signals)?;
+        super::set_label(signals, Some(label));
+        Ok(once)
+    }
+
+    /// Returns the signals that `self` is registered for.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.signals
+    }
+
+    /// Returns whether `self` has been fulfilled, after which every poll
+    /// returns [`Ready`] with the same output.
+    ///
+    /// [`Ready`]: https://doc.rust-lang.org/std/task/enum.Poll.html#variant.Ready
+    #[inline]
+    pub fn is_fulfilled(&self) -> bool {
+        self.fulfilled
+    }
+
+    /// Returns the signal that fulfilled `self`, if it has been.
+    #[inline]
+    pub fn caught(&self) -> Option<Signal> {
+        self.caught
+    }
+
+    /// Returns the signals in `self` caught since it was registered.
+    pub(super) fn caught_since_registered(&self) -> SignalSet {
+        Table::global().caught_since(self.signals, &self.counts)
+    }
+
+    /// Replaces the signals that `self` is registered for with `signals`.
+    ///
+    /// Handlers for newly added signals are installed before any removed ones
+    /// are released, so there is no gap during which neither set is handled.
+    /// Removed signals have their previous action restored, unless they are
+    /// still registered elsewhere.
+    ///
+    /// If `self` has clones, it is detached from them by registering `signals`
+    /// anew, leaving their registration unchanged.
+    ///
+    /// If installing a handler fails, `self` is left unchanged.
+    pub fn replace_set(&mut self, signals: SignalSet) -> io::Result<()> {
+        let table = Table::global();
+
+        if Arc::strong_count(&self.driver) > 1 {
+            let counts = table.counts(signals);
+            self.driver = SharedDriver::new(Driver::register(signals)?);
+            self.signals = signals;
+            self.counts = counts;
+            return Ok(());
         }
 
-        Ok(Self { signals, driver })
+        let added = signals.without_all(self.signals);
+        let removed = self.signals.without_all(signals);
+        let writer = self.driver.writer();
+
+        let counts = table.counts(added);
+        super::register_set(added, writer)?;
+        for signal in added {
+            self.counts[signal as usize] = counts[signal as usize];
+        }
+        self.signals = signals;
+
+        super::unregister_set(removed, writer)
+    }
+
+    /// Returns a future that, once a signal in `self` is received, waits for
+    /// `window` and then resolves with every signal in `self` received so far.
+    ///
+    /// Signals often arrive in quick succession, such as a terminal delivering
+    /// [`Interrupt`] and [`Hangup`] when closing. Coalescing them allows for
+    /// reporting what was actually received rather than whichever was observed
+    /// first.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asygnal::SignalSet;
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> Result<(), asygnal::once::signal::RegisterOnceError> {
+    /// let once = SignalSet::termination().register_once()?;
+    /// let received = once.coalesce(Duration::from_millis(50)).await;
+    ///
+    /// println!("shutting down due to {:?}", received);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Interrupt`]: ../../signal/enum.Signal.html#variant.Interrupt
+    /// [`Hangup`]:    ../../signal/enum.Signal.html#variant.Hangup
+    #[inline]
+    pub fn coalesce(self, window: Duration) -> Coalesce {
+        Coalesce::new(self, window)
+    }
+
+    /// Returns a future that resolves with the received signal or with the
+    /// internal error that fulfilled `self`.
+    ///
+    /// This overrides any [`ErrorPolicy`] with
+    /// [`ResolveWithError`](enum.ErrorPolicy.html#variant.ResolveWithError).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asygnal::SignalSet;
+    ///
+    /// # async fn example() -> Result<(), asygnal::once::signal::RegisterOnceError> {
+    /// let once = SignalSet::termination().register_once()?;
+    ///
+    /// match once.fallible().await {
+    ///     Ok(signal) => println!("received {:?}", signal),
+    ///     Err(error) => eprintln!("cannot wait for signals: {}", error),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ErrorPolicy`]: enum.ErrorPolicy.html
+    #[inline]
+    pub fn fallible(self) -> Fallible {
+        Fallible::new(self)
+    }
+
+    /// Returns `self` with `policy` used for handling internal errors instead
+    /// of the [global policy](enum.ErrorPolicy.html#method.global).
+    #[inline]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Takes the internal error that fulfilled `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+/// Registers handlers for each of `sets` all at once, returning a future for
+/// each in the same order.
+///
+/// Either every handler is installed or none are: if installing any handler
+/// fails, all previously installed ones are restored. This avoids ending up
+/// with only some signals handled during startup.
+///
+/// Sets may overlap, in which case every future for a signal is fulfilled upon
+/// receiving it.
+pub fn register_all<I>(sets: I) -> Result<Vec<SignalSetOnce>, RegisterOnceError>
+where
+    I: IntoIterator<Item = SignalSet>,
+{
+    let sets: Vec<SignalSet> = sets.into_iter().collect();
+
+    // Dropping the futures upon failure closes their pipes.
+    let table = Table::global();
+    let futures = sets
+        .iter()
+        .map(|&signals| {
+            let counts = table.counts(signals);
+            Ok(SignalSetOnce::from_driver(signals, counts, Driver::new()?))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    // Declared after `futures` so that it's released before they're dropped.
+    let _lock = super::lock_registry();
+    let mut handles = Vec::new();
+
+    for &signals in &sets {
+        if let Err(error) = super::install_set(signals, false, &mut handles) {
+            super::reset_all(handles);
+            return Err(error.into());
+        }
+    }
+
+    for future in &futures {
+        table.attach(future.signals, future.driver.writer());
+    }
+
+    Ok(futures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn ignores_earlier_deliveries() {
+        let signals = Signal::Quit | Signal::XFileSize;
+
+        test_util::run_exclusive(|| {
+            let mut first = SignalSetOnce::register(signals).unwrap();
+            assert!(test_util::poll(&mut first).is_pending());
+
+            Signal::Quit.raise().unwrap();
+            assert_eq!(
+                test_util::poll(&mut first),
+                Poll::Ready(Some(Signal::Quit))
+            );
+
+            // Registering again waits for the next delivery.
+            let mut second = SignalSetOnce::register(signals).unwrap();
+            assert!(test_util::poll(&mut second).is_pending());
+
+            Signal::XFileSize.raise().unwrap();
+            assert_eq!(
+                test_util::poll(&mut second),
+                Poll::Ready(Some(Signal::XFileSize)),
+            );
+        });
+    }
+
+    #[test]
+    fn register_all_overlapping() {
+        let signal = Signal::XCpu;
+
+        test_util::run(|| {
+            let mut futures =
+                register_all([signal.into(), signal.into()]).unwrap();
+            signal.raise().unwrap();
+
+            for future in &mut futures {
+                assert_eq!(test_util::poll(future), Poll::Ready(Some(signal)));
+            }
+        });
+    }
+
+    #[test]
+    fn replace_set() {
+        test_util::run_exclusive(|| {
+            let mut once =
+                SignalSetOnce::register(Signal::WindowChange.into()).unwrap();
+            once.replace_set(Signal::Urgent.into()).unwrap();
+            assert_eq!(once.signals(), Signal::Urgent.into());
+
+            // Removed signals no longer fulfill `once`.
+            Signal::WindowChange.raise().unwrap();
+            assert!(test_util::poll(&mut once).is_pending());
+
+            Signal::Urgent.raise().unwrap();
+            assert_eq!(
+                test_util::poll(&mut once),
+                Poll::Ready(Some(Signal::Urgent))
+            );
+        });
+    }
+
+    #[test]
+    fn resolves_with_caught() {
+        let signals = Signal::Quit | Signal::XFileSize;
+
+        test_util::run_exclusive(|| {
+            let mut once = SignalSetOnce::register(signals).unwrap();
+            assert!(test_util::poll(&mut once).is_pending());
+            assert_eq!(once.caught(), None);
+
+            Signal::XFileSize.raise().unwrap();
+            assert_eq!(
+                test_util::poll(&mut once),
+                Poll::Ready(Some(Signal::XFileSize))
+            );
+            assert_eq!(once.caught(), Some(Signal::XFileSize));
+
+            // Later polls resolve with the same signal.
+            Signal::Quit.raise().unwrap();
+            assert_eq!(
+                test_util::poll(&mut once),
+                Poll::Ready(Some(Signal::XFileSize))
+            );
+        });
+    }
+
+    #[test]
+    fn register_partial() {
+        let signals = Signal::Urgent | Signal::WindowChange;
+
+        test_util::run_exclusive(|| {
+            // Every signal here can be handled, so none are left out.
+            let (mut once, errors) =
+                SignalSetOnce::register_partial(signals).unwrap();
+            assert!(errors.is_empty());
+            assert_eq!(once.signals(), signals);
+            assert!(test_util::poll(&mut once).is_pending());
+
+            Signal::WindowChange.raise().unwrap();
+            assert_eq!(
+                test_util::poll(&mut once),
+                Poll::Ready(Some(Signal::WindowChange))
+            );
+        });
     }
 }
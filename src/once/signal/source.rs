@@ -0,0 +1,95 @@
+use std::io;
+
+use crate::{Signal, SignalSet};
+
+/// A handle for delivering signals from outside of the OS, such as signals
+/// proxied from a hypervisor or from a supervisor over RPC.
+///
+/// Delivered signals go through the same path as signals received by the
+/// handler installed by this crate, and so fulfill the same futures and
+/// streams, including respecting [`pause`] and any [`ForceExit`] policy. The
+/// exception is [`KqueueSignalSetOnce`], which only observes signals received
+/// by the process.
+///
+/// A handle is a capability for a fixed set of signals, so that the code
+/// handing it out controls what the embedder may deliver.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{once::signal::SignalSource, Signal, SignalSet};
+///
+/// let source = SignalSource::new(SignalSet::new().hangup().terminate());
+///
+/// // Upon receiving a request from the supervisor:
+/// source.deliver(Signal::Hangup)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`pause`]:               fn.pause.html
+/// [`ForceExit`]:           struct.ForceExit.html
+/// [`KqueueSignalSetOnce`]: struct.KqueueSignalSetOnce.html
+#[derive(Clone, Debug)]
+pub struct SignalSource {
+    signals: SignalSet,
+}
+
+impl SignalSource {
+    /// Creates a handle for delivering any of `signals`.
+    #[inline]
+    pub const fn new(signals: SignalSet) -> Self {
+        Self { signals }
+    }
+
+    /// Returns the signals that `self` may deliver.
+    #[inline]
+    pub const fn signals(&self) -> SignalSet {
+        self.signals
+    }
+
+    /// Delivers `signal` as if it were received by the process.
+    ///
+    /// This fails with [`PermissionDenied`] if `signal` is not one of
+    /// [`signals`](#method.signals). Details such as those of [`SignalInfo`]
+    /// keep describing the last delivery from the OS.
+    ///
+    /// [`PermissionDenied`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.PermissionDenied
+    /// [`SignalInfo`]: struct.SignalInfo.html
+    pub fn deliver(&self, signal: Signal) -> io::Result<()> {
+        if !self.signals.contains(signal) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} cannot be delivered by this source", signal),
+            ));
+        }
+        super::on_signal(signal);
+        Ok(())
+    }
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "android")))]
+mod tests {
+    use super::*;
+    use crate::once::signal::table::Table;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn deliver() {
+        let signal = Signal::VtAlarm;
+        let source = SignalSource::new(signal.into());
+
+        let error = source.deliver(Signal::Hangup).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+
+        let count = Table::global().entry(signal).count.load(Ordering::SeqCst);
+        source.deliver(signal).unwrap();
+        assert_eq!(
+            Table::global().entry(signal).count.load(Ordering::SeqCst),
+            count + 1
+        );
+        assert!(Table::global()
+            .caught
+            .load(Ordering::SeqCst)
+            .contains(signal));
+    }
+}
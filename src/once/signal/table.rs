@@ -1,14 +1,52 @@
 use crate::{
-    signal::{AtomicSignalSet, Signal, SignalArray},
-    unix::pipe::Writer,
+    signal::{AtomicSignalSet, Signal, SignalArray, SignalSet},
+    unix::pipe::{Reader, Writer},
+};
+use std::{
+    ptr,
+    sync::{
+        atomic::{
+            AtomicI32, AtomicPtr, AtomicU32, AtomicU64, AtomicU8, AtomicUsize,
+            Ordering,
+        },
+        Mutex,
+    },
+    thread,
 };
-use std::sync::atomic::{AtomicI32, Ordering};
 
 #[repr(align(32))] // Potentially improve cache performance.
 pub(crate) struct Table {
     pub registered: AtomicSignalSet,
+    /// Every signal caught so far, which is never cleared.
+    ///
+    /// Listeners must compare catch counts instead, so that they only observe
+    /// deliveries after they registered.
     pub caught: AtomicSignalSet,
+    /// The signals whose handler was installed with `SA_SIGINFO`, and so
+    /// record the sender of each delivery.
+    pub with_info: AtomicSignalSet,
+    /// The signals in `with_info` that also record the wall-clock time of each
+    /// delivery.
+    pub wall_clock: AtomicSignalSet,
+    /// The number of active calls to `pause` without a matching `resume`.
+    pub pause_depth: AtomicUsize,
+    /// The catch count of each signal when delivery was last paused, which
+    /// also serializes changes to `pause_depth`.
+    pub pause_counts: Mutex<SignalArray<usize>>,
+    /// The actions that were in place before installing our handler, used for
+    /// releasing signals. This must never be accessed from a signal handler.
+    pub previous: Mutex<SignalArray<Option<libc::sigaction>>>,
+    /// The label of the registration handling each signal, if any.
+    pub labels: Mutex<SignalArray<Option<&'static str>>>,
     entries: SignalArray<Entry>,
+    /// The head of the list of listeners, each woken upon receiving any of its
+    /// signals.
+    ///
+    /// Slots are never freed so that the signal handler can safely traverse
+    /// the list at any time. Instead, unused slots are reused.
+    slots: AtomicPtr<Slot>,
+    /// The number of handlers currently waking up listeners.
+    waking: AtomicUsize,
 }
 
 impl Table {
@@ -17,7 +55,15 @@ impl Table {
         static GLOBAL: Table = Table {
             registered: AtomicSignalSet::new(),
             caught: AtomicSignalSet::new(),
+            with_info: AtomicSignalSet::new(),
+            wall_clock: AtomicSignalSet::new(),
+            pause_depth: AtomicUsize::new(0),
+            pause_counts: Mutex::new([0; Signal::NUM]),
+            previous: Mutex::new([None; Signal::NUM]),
+            labels: Mutex::new([None; Signal::NUM]),
             entries: [Entry::EMPTY; Signal::NUM],
+            slots: AtomicPtr::new(ptr::null_mut()),
+            waking: AtomicUsize::new(0),
         };
         &GLOBAL
     }
@@ -26,25 +72,311 @@ impl Table {
     pub fn entry(&self, signal: Signal) -> &Entry {
         &self.entries[signal as usize]
     }
+
+    /// Returns `true` if delivery to listeners is currently paused.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.pause_depth.load(Ordering::SeqCst) != 0
+    }
+
+    /// Returns the current catch counts of `signals`, with all others 0.
+    ///
+    /// Listeners record this before installing their handlers, so that only
+    /// deliveries after registering are observed.
+    pub fn counts(&self, signals: SignalSet) -> SignalArray<usize> {
+        let mut counts = [0; Signal::NUM];
+        self.take_caught(signals, &mut counts);
+        counts
+    }
+
+    /// Returns the signals in `signals` caught since `counts` was recorded,
+    /// without updating it.
+    pub fn caught_since(
+        &self,
+        signals: SignalSet,
+        counts: &SignalArray<usize>,
+    ) -> SignalSet {
+        signals.filter(|signal| {
+            self.entry(signal).count.load(Ordering::SeqCst)
+                != counts[signal as usize]
+        })
+    }
+
+    /// Returns the signals in `signals` caught since `counts` was last
+    /// updated, updating it with the current counts.
+    pub fn take_caught(
+        &self,
+        signals: SignalSet,
+        counts: &mut SignalArray<usize>,
+    ) -> SignalSet {
+        let mut caught = SignalSet::new();
+
+        for signal in signals {
+            let count = self.entry(signal).count.load(Ordering::SeqCst);
+            let last = &mut counts[signal as usize];
+
+            if *last != count {
+                *last = count;
+                caught.insert(signal);
+            }
+        }
+
+        caught
+    }
 }
 
 pub(crate) struct Entry {
-    // TODO: Use `signalfd` on platforms that support it.
-    /// The file descriptor for the writing end of the pipe.
-    pub writer_fd: AtomicI32,
+    /// The number of listeners for the signal. Our handler is released once
+    /// this reaches 0.
+    pub listeners: AtomicUsize,
+    /// The number of times the signal has been caught, wrapping on overflow.
+    pub count: AtomicUsize,
+    /// The `si_pid` of the last delivery, if installed with `SA_SIGINFO`.
+    pub pid: AtomicI32,
+    /// The `si_uid` of the last delivery, if installed with `SA_SIGINFO`.
+    pub uid: AtomicU32,
+    /// The `si_code` of the last delivery, if installed with `SA_SIGINFO`.
+    pub code: AtomicI32,
+    /// The `CLOCK_REALTIME` time of the last delivery in nanoseconds since the
+    /// Unix epoch, or 0 if not recorded.
+    pub time: AtomicU64,
 }
 
 impl Entry {
     #[allow(clippy::declare_interior_mutable_const)]
     const EMPTY: Self = Self {
-        writer_fd: AtomicI32::new(0),
+        listeners: AtomicUsize::new(0),
+        count: AtomicUsize::new(0),
+        pid: AtomicI32::new(0),
+        uid: AtomicU32::new(0),
+        code: AtomicI32::new(0),
+        time: AtomicU64::new(0),
     };
+}
+
+/// The pipe of a slot is written to upon every wake up, since its reader
+/// drains it without going through the table, such as over FFI.
+const UNTRACKED: u8 = 0;
+/// The pipe of a slot is empty, or its reader is about to check the table.
+const IDLE: u8 = 1;
+/// The pipe of a slot has a byte that its reader has not drained yet, so wake
+/// ups skip writing another.
+const PENDING: u8 = 2;
 
-    /// Returns the writing end of the pipe.
+/// A listener in the table, identified by the writing end of its pipe.
+struct Slot {
+    // TODO: Use `signalfd` on platforms that support it.
+    /// The file descriptor for the writing end of the pipe, or -1 if unused.
+    writer_fd: AtomicI32,
+    /// The signals that wake up the listener.
+    signals: AtomicSignalSet,
+    /// Whether the pipe has an undrained byte, which keeps it from filling up.
+    state: AtomicU8,
+    /// The next slot in the list, which never changes once set.
+    next: *const Slot,
+}
+
+impl Slot {
+    /// Returns `true` if the pipe should be written to, marking it pending.
+    ///
+    /// It is imperative that this function is signal-safe.
     #[inline]
-    pub fn load_writer(&self, ordering: Ordering) -> Writer {
-        // SAFETY: In the case that the file descriptor is 0, the `pipe::Writer`
-        // still has a valid representation.
-        Writer(self.writer_fd.load(ordering))
+    fn should_write(&self) -> bool {
+        match self.state.load(Ordering::SeqCst) {
+            UNTRACKED => true,
+            _ => self.state.swap(PENDING, Ordering::SeqCst) != PENDING,
+        }
+    }
+}
+
+impl Table {
+    /// Wakes up every listener for `signal`.
+    ///
+    /// It is imperative that this function is signal-safe.
+    pub fn wake(&self, signal: Signal) {
+        self.waking.fetch_add(1, Ordering::SeqCst);
+        for slot in self.slots() {
+            let fd = slot.writer_fd.load(Ordering::SeqCst);
+            if fd >= 0
+                && slot.signals.load(Ordering::SeqCst).contains(signal)
+                && slot.should_write()
+            {
+                Writer(fd).wake();
+            }
+        }
+        self.waking.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Reads all pending bytes from `reader` and has the next wake up of
+    /// `writer` write to the pipe again.
+    ///
+    /// Afterwards, the table must be checked for signals caught in between,
+    /// since their wake ups may have been skipped.
+    pub fn drain(&self, reader: Reader, writer: Writer) {
+        // Drain before marking idle so that a byte written in between is not
+        // left behind with wake ups skipped.
+        reader.drain();
+        if let Some(slot) = self.find_slot(writer) {
+            slot.state.store(IDLE, Ordering::SeqCst);
+        }
+    }
+
+    /// Makes `writer` be woken upon receiving any of `signals`, in addition to
+    /// the signals it's already woken by.
+    pub fn attach(&self, signals: SignalSet, writer: Writer) {
+        let slot = match self.find_slot(writer) {
+            Some(slot) => slot,
+            None => self.claim_slot(writer),
+        };
+
+        let added = signals.without_all(slot.signals.load(Ordering::SeqCst));
+        for signal in added {
+            self.entry(signal).listeners.fetch_add(1, Ordering::SeqCst);
+        }
+
+        slot.signals.insert(added, Ordering::SeqCst);
+        self.registered.insert(signals, Ordering::SeqCst);
+    }
+
+    /// Stops waking `writer` upon receiving any of `signals`, returning those
+    /// that no longer have any listeners.
+    pub fn detach(&self, signals: SignalSet, writer: Writer) -> SignalSet {
+        let slot = match self.find_slot(writer) {
+            Some(slot) => slot,
+            None => return SignalSet::new(),
+        };
+
+        let listened = slot.signals.load(Ordering::SeqCst);
+        let removed = listened.filter(|signal| signals.contains(signal));
+
+        slot.signals.remove(removed, Ordering::SeqCst);
+        if listened.without_all(removed).is_empty() {
+            slot.writer_fd.store(-1, Ordering::SeqCst);
+
+            // Wait for handlers that may have loaded the file descriptor, so
+            // that the caller can safely close it.
+            while self.waking.load(Ordering::SeqCst) != 0 {
+                thread::yield_now();
+            }
+        }
+
+        let mut unused = SignalSet::new();
+        for signal in removed {
+            let entry = self.entry(signal);
+            if entry.listeners.fetch_sub(1, Ordering::SeqCst) == 1 {
+                unused.insert(signal);
+            }
+        }
+        unused
+    }
+
+    fn slots(&self) -> impl Iterator<Item = &'static Slot> {
+        let head = self.slots.load(Ordering::SeqCst) as *const Slot;
+
+        // SAFETY: Slots are leaked and thus live for the rest of the program.
+        std::iter::successors(unsafe { head.as_ref() }, |slot| unsafe {
+            slot.next.as_ref()
+        })
+    }
+
+    fn find_slot(&self, writer: Writer) -> Option<&'static Slot> {
+        self.slots()
+            .find(|slot| slot.writer_fd.load(Ordering::SeqCst) == writer.0)
+    }
+
+    /// Reuses an unused slot for `writer` or allocates a new one.
+    fn claim_slot(&self, writer: Writer) -> &'static Slot {
+        for slot in self.slots() {
+            let claimed = slot.writer_fd.compare_exchange(
+                -1,
+                writer.0,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            );
+            if claimed.is_ok() {
+                slot.state.store(UNTRACKED, Ordering::SeqCst);
+                return slot;
+            }
+        }
+
+        let slot: &'static mut Slot = Box::leak(Box::new(Slot {
+            writer_fd: AtomicI32::new(writer.0),
+            signals: AtomicSignalSet::new(),
+            state: AtomicU8::new(UNTRACKED),
+            next: ptr::null(),
+        }));
+
+        let mut head = self.slots.load(Ordering::SeqCst);
+        loop {
+            slot.next = head;
+            match self.slots.compare_exchange_weak(
+                head,
+                slot,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return slot,
+                Err(current) => head = current,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listeners() {
+        let table = Table::global();
+        let signal = Signal::UserDef1;
+        let (a, b) = (Writer(i32::MAX - 1), Writer(i32::MAX - 2));
+
+        table.attach(signal.into(), a);
+        table.attach(signal.into(), b);
+        table.attach(signal.into(), b);
+        assert_eq!(table.entry(signal).listeners.load(Ordering::SeqCst), 2);
+
+        assert!(table.detach(signal.into(), a).is_empty());
+        assert_eq!(table.detach(signal.into(), b), SignalSet::from(signal));
+        assert!(table.detach(signal.into(), b).is_empty());
+        assert_eq!(table.entry(signal).listeners.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn skips_pending_writes() {
+        let table = Table::global();
+        let signal = Signal::Alarm;
+
+        // Deliveries from other tests would also write to the pipe.
+        crate::test_util::run_exclusive(|| {
+            let (reader, writer) = crate::unix::pipe::pipe().unwrap();
+
+            let pending_bytes = || {
+                let mut count = 0;
+                unsafe { libc::ioctl(reader.0, libc::FIONREAD, &mut count) };
+                count
+            };
+
+            table.attach(signal.into(), writer);
+
+            // Readers that never drain through the table get every write.
+            table.wake(signal);
+            table.wake(signal);
+            assert_eq!(pending_bytes(), 2);
+
+            table.drain(reader, writer);
+            assert_eq!(pending_bytes(), 0);
+
+            table.wake(signal);
+            table.wake(signal);
+            assert_eq!(pending_bytes(), 1);
+
+            table.detach(signal.into(), writer);
+            unsafe {
+                libc::close(reader.0);
+                libc::close(writer.0);
+            }
+        });
     }
 }
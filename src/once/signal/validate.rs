@@ -0,0 +1,119 @@
+use std::{error::Error, fmt, io, mem, ptr, sync::atomic::Ordering};
+
+use super::table::Table;
+use crate::{Signal, SignalSet};
+
+/// An error returned by [`SignalSet::validate`] for a [`Signal`] that cannot
+/// be registered cleanly.
+///
+/// [`Signal`]: ../../signal/enum.Signal.html
+/// [`SignalSet::validate`]: ../../signal/struct.SignalSet.html#method.validate
+#[derive(Debug)]
+pub enum ValidationError {
+    /// The signal is ignored, such as by `nohup` or a parent process, and
+    /// registering would stop ignoring it.
+    Ignored(Signal),
+    /// A handler not installed by this crate is in place, and registering
+    /// would replace it until released.
+    Foreign(Signal),
+    /// The action of the signal cannot be changed, such as when it is not
+    /// supported by the running kernel or is denied by a sandbox.
+    Unsupported(Signal, io::Error),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Ignored(signal) => write!(f, "{} is ignored", signal),
+            Self::Foreign(signal) => {
+                write!(f, "{} already has a foreign handler", signal)
+            }
+            Self::Unsupported(signal, error) => {
+                write!(f, "{} cannot be handled: {}", signal, error)
+            }
+        }
+    }
+}
+
+impl Error for ValidationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Unsupported(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl ValidationError {
+    /// Returns the signal that failed validation.
+    #[inline]
+    pub fn signal(&self) -> Signal {
+        match *self {
+            Self::Ignored(signal)
+            | Self::Foreign(signal)
+            | Self::Unsupported(signal, _) => signal,
+        }
+    }
+}
+
+/// Checks that each signal in `signals` can be registered, in order of integer
+/// value, without installing any handler.
+pub(crate) fn validate(signals: SignalSet) -> Result<(), ValidationError> {
+    let registered = Table::global().registered.load(Ordering::SeqCst);
+
+    for signal in signals {
+        let raw_signal = signal.into_raw();
+        let unsupported = |signal| {
+            ValidationError::Unsupported(signal, io::Error::last_os_error())
+        };
+
+        let mut action: libc::sigaction = unsafe { mem::zeroed() };
+        if unsafe { libc::sigaction(raw_signal, ptr::null(), &mut action) } != 0
+        {
+            return Err(unsupported(signal));
+        }
+
+        // Writing back the same action checks whether it can be changed at
+        // all, without changing it.
+        if unsafe { libc::sigaction(raw_signal, &action, ptr::null_mut()) } != 0
+        {
+            return Err(unsupported(signal));
+        }
+
+        if registered.contains(signal) {
+            continue;
+        }
+
+        match action.sa_sigaction {
+            libc::SIG_DFL => {}
+            libc::SIG_IGN => return Err(ValidationError::Ignored(signal)),
+            _ => return Err(ValidationError::Foreign(signal)),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignored() {
+        let signal = Signal::TtIn;
+        let raw_signal = signal.into_raw();
+
+        assert!(SignalSet::from(signal).validate().is_ok());
+
+        unsafe { libc::signal(raw_signal, libc::SIG_IGN) };
+        let result = SignalSet::from(signal).validate();
+        unsafe { libc::signal(raw_signal, libc::SIG_DFL) };
+
+        match result {
+            Err(ValidationError::Ignored(ignored)) => {
+                assert_eq!(ignored, signal)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,363 @@
+//! Driving signal behavior from configuration.
+//!
+//! A [`SignalPolicy`] describes which signals shut the process down, which
+//! reload it, and which are ignored, so that a service can load all of it from
+//! one section of its configuration file. With the `serde` feature, it can be
+//! deserialized directly:
+//!
+//! ```toml
+//! [signals]
+//! shutdown = ["SIGINT", "SIGTERM"]
+//! reload = ["SIGHUP"]
+//! ignore = ["SIGPIPE"]
+//! escalate_after = 10.0
+//! ```
+//!
+//! Missing fields take their [default](struct.SignalPolicy.html#impl-Default),
+//! and `escalate_after` is in seconds.
+//!
+//! [`SignalPolicy`]: struct.SignalPolicy.html
+
+use std::{
+    io,
+    sync::atomic::Ordering,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use crate::{
+    once::{
+        signal::{
+            listener::Listener, table::Table, ForceExit, RegisterOnceError,
+        },
+        CancelSafe,
+    },
+    shutdown, Signal, SignalSet,
+};
+
+/// The signal behavior of a process, typically loaded from configuration.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::policy::{PolicyEvent, SignalPolicy};
+/// use std::future::poll_fn;
+///
+/// # async fn example(policy: SignalPolicy) -> Result<(), Box<dyn std::error::Error>> {
+/// let mut events = policy.apply()?;
+///
+/// loop {
+///     match poll_fn(|cx| events.poll_next(cx)).await? {
+///         PolicyEvent::Reload(_) => println!("reloading config"),
+///         PolicyEvent::Shutdown(_) => break,
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct SignalPolicy {
+    /// The signals that start a graceful shutdown.
+    ///
+    /// Receiving a second one terminates the process immediately.
+    pub shutdown: SignalSet,
+    /// The signals that request reloading, such as of configuration.
+    pub reload: SignalSet,
+    /// The signals that are ignored by the process.
+    pub ignore: SignalSet,
+    /// How long after the first shutdown signal to terminate the process if it
+    /// is still running, if at all.
+    #[cfg_attr(feature = "serde", serde(with = "seconds"))]
+    pub escalate_after: Option<Duration>,
+}
+
+/// Shuts down upon the [default shutdown signals], reloads upon [`Hangup`],
+/// and neither ignores any signals nor escalates.
+///
+/// [default shutdown signals]: ../shutdown/fn.default_shutdown.html
+/// [`Hangup`]: ../signal/enum.Signal.html#variant.Hangup
+impl Default for SignalPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            shutdown: shutdown::default_shutdown(),
+            reload: SignalSet::new().hangup(),
+            ignore: SignalSet::new(),
+            escalate_after: None,
+        }
+    }
+}
+
+impl SignalPolicy {
+    /// Applies `self` to the process.
+    ///
+    /// This:
+    ///
+    /// - Sets [`shutdown`](#structfield.shutdown) as the
+    ///   [default shutdown signals], failing if they were already configured
+    ///   differently.
+    ///
+    /// - Installs [`ForceExit::immediately`] for `shutdown` as the process-wide
+    ///   policy, replacing any previous one.
+    ///
+    /// - Ignores each signal in [`ignore`](#structfield.ignore). These stay
+    ///   ignored after the returned [`PolicyEvents`] is dropped.
+    ///
+    /// - Registers a handler for `shutdown` and [`reload`](#structfield.reload),
+    ///   which are reported by the returned [`PolicyEvents`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegisterOnceError::Registered`] with the conflicting signals,
+    /// before changing anything, if any signal appears in more than one set or
+    /// if a signal to ignore is already registered.
+    ///
+    /// [default shutdown signals]: ../shutdown/fn.default_shutdown.html
+    /// [`ForceExit::immediately`]: ../once/signal/struct.ForceExit.html#method.immediately
+    /// [`PolicyEvents`]: struct.PolicyEvents.html
+    /// [`RegisterOnceError::Registered`]: ../once/signal/enum.RegisterOnceError.html#variant.Registered
+    pub fn apply(&self) -> Result<PolicyEvents, RegisterOnceError> {
+        let registered = Table::global().registered.load(Ordering::SeqCst);
+        let conflicts = (self.shutdown & self.reload)
+            | (self.ignore & (self.shutdown | self.reload | registered));
+        if !conflicts.is_empty() {
+            return Err(RegisterOnceError::Registered(conflicts));
+        }
+
+        if let Err(current) = shutdown::set_default_shutdown(self.shutdown) {
+            if current != self.shutdown {
+                return Err(RegisterOnceError::Io(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "default shutdown signals are already configured",
+                )));
+            }
+        }
+
+        for signal in self.ignore {
+            let raw_signal = signal.into_raw();
+            if unsafe { libc::signal(raw_signal, libc::SIG_IGN) }
+                == libc::SIG_ERR
+            {
+                return Err(io::Error::last_os_error().into());
+            }
+        }
+
+        let listener = Listener::register(self.shutdown | self.reload)?;
+        if !self.shutdown.is_empty() {
+            ForceExit::immediately()
+                .with_signals(self.shutdown)
+                .install()?;
+        }
+
+        Ok(PolicyEvents {
+            listener,
+            shutdown: self.shutdown,
+            escalate_after: self.escalate_after,
+            escalated: false,
+        })
+    }
+}
+
+/// A signal received under a [`SignalPolicy`].
+///
+/// [`SignalPolicy`]: struct.SignalPolicy.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PolicyEvent {
+    /// A signal in [`SignalPolicy::shutdown`] was received.
+    ///
+    /// [`SignalPolicy::shutdown`]: struct.SignalPolicy.html#structfield.shutdown
+    Shutdown(Signal),
+    /// A signal in [`SignalPolicy::reload`] was received.
+    ///
+    /// [`SignalPolicy::reload`]: struct.SignalPolicy.html#structfield.reload
+    Reload(Signal),
+}
+
+impl PolicyEvent {
+    /// Returns the signal that was received.
+    #[inline]
+    pub fn signal(self) -> Signal {
+        match self {
+            Self::Shutdown(signal) | Self::Reload(signal) => signal,
+        }
+    }
+}
+
+/// The events of an applied [`SignalPolicy`].
+///
+/// This is created by [`SignalPolicy::apply`]. Signals received in between
+/// polls are coalesced, with each pending signal reported once in order of its
+/// integer value.
+///
+/// With the `stream` feature, this implements `Stream` and `FusedStream`.
+///
+/// [`SignalPolicy`]: struct.SignalPolicy.html
+/// [`SignalPolicy::apply`]: struct.SignalPolicy.html#method.apply
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct PolicyEvents {
+    listener: Listener,
+    shutdown: SignalSet,
+    escalate_after: Option<Duration>,
+    escalated: bool,
+}
+
+// SAFETY: Escalation is set up in the same poll that returns the signal.
+unsafe impl CancelSafe for PolicyEvents {}
+
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::Stream for PolicyEvents {
+    type Item = io::Result<PolicyEvent>;
+
+    #[inline]
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Self::Item>> {
+        PolicyEvents::poll_next(&mut self, cx).map(Some)
+    }
+}
+
+/// Never terminated, since `self` never ends.
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::stream::FusedStream for PolicyEvents {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
+
+impl PolicyEvents {
+    /// Polls for the next signal received.
+    ///
+    /// Upon the first shutdown signal, this starts the timeout of
+    /// [`SignalPolicy::escalate_after`], if any.
+    ///
+    /// [`SignalPolicy::escalate_after`]: struct.SignalPolicy.html#structfield.escalate_after
+    pub fn poll_next(
+        &mut self,
+        cx: &mut Context,
+    ) -> Poll<io::Result<PolicyEvent>> {
+        let signal = match self.listener.poll_next(cx) {
+            Poll::Ready(Ok(signal)) => signal,
+            Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+            Poll::Pending => return Poll::Pending,
+        };
+
+        if !self.shutdown.contains(signal) {
+            return Poll::Ready(Ok(PolicyEvent::Reload(signal)));
+        }
+
+        if !self.escalated {
+            self.escalated = true;
+            if let Some(timeout) = self.escalate_after {
+                shutdown::spawn_timeout(signal, timeout);
+            }
+        }
+        Poll::Ready(Ok(PolicyEvent::Shutdown(signal)))
+    }
+
+    /// Returns the signals handled by `self`.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.listener.signals()
+    }
+}
+
+/// Represents `Option<Duration>` as a number of seconds.
+#[cfg(feature = "serde")]
+mod seconds {
+    use std::{fmt, time::Duration};
+
+    use serde::{
+        de::{self, Deserializer, Unexpected, Visitor},
+        Serializer,
+    };
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_f64(duration.as_secs_f64()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        deserializer.deserialize_option(SecondsVisitor)
+    }
+
+    struct SecondsVisitor;
+
+    impl<'de> Visitor<'de> for SecondsVisitor {
+        type Value = Option<Duration>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a non-negative number of seconds")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            deserializer.deserialize_any(self)
+        }
+
+        fn visit_u64<E: de::Error>(self, secs: u64) -> Result<Self::Value, E> {
+            Ok(Some(Duration::from_secs(secs)))
+        }
+
+        fn visit_i64<E: de::Error>(self, secs: i64) -> Result<Self::Value, E> {
+            u64::try_from(secs)
+                .map(|secs| Some(Duration::from_secs(secs)))
+                .map_err(|_| E::invalid_value(Unexpected::Signed(secs), &self))
+        }
+
+        fn visit_f64<E: de::Error>(self, secs: f64) -> Result<Self::Value, E> {
+            if secs.is_finite() && secs >= 0.0 && secs < u64::MAX as f64 {
+                Ok(Some(Duration::from_secs_f64(secs)))
+            } else {
+                Err(E::invalid_value(Unexpected::Float(secs), &self))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_conflicts() {
+        let policy = SignalPolicy {
+            shutdown: SignalSet::new().interrupt().terminate(),
+            reload: SignalSet::new().hangup().terminate(),
+            ignore: SignalSet::new().pipe().interrupt(),
+            escalate_after: None,
+        };
+        match policy.apply() {
+            Err(RegisterOnceError::Registered(conflicts)) => {
+                assert_eq!(conflicts, SignalSet::new().interrupt().terminate())
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::{io, process::Child};
+
+use crate::Signal;
+
+/// Extension methods for [`Child`].
+///
+/// With the `once-tokio` feature, this is also implemented for tokio's
+/// [`Child`](https://docs.rs/tokio/1/tokio/process/struct.Child.html).
+///
+/// [`Child`]: https://doc.rust-lang.org/std/process/struct.Child.html
+pub trait ChildExt {
+    /// Sends `signal` to the child process.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`InvalidInput`] if the child has already exited, since its
+    /// process ID may have been reused.
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    fn send_signal(&mut self, signal: Signal) -> io::Result<()>;
+
+    /// Returns a future that resolves to the exit status of the child process
+    /// once it exits.
+    ///
+    /// Rather than blocking a thread in `waitpid`, this waits for [`Child`]
+    /// signals through this crate's handler and checks the child upon each.
+    ///
+    /// [`Child`]: ../signal/enum.Signal.html#variant.Child
+    #[cfg(any(docsrs, feature = "once-core"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+    fn wait_via_asygnal(&mut self) -> ChildWait<'_>;
+}
+
+impl ChildExt for Child {
+    fn send_signal(&mut self, signal: Signal) -> io::Result<()> {
+        if self.try_wait()?.is_some() {
+            return Err(exited());
+        }
+        super::kill(self.id() as libc::pid_t, signal.into_raw())
+    }
+
+    #[cfg(any(docsrs, feature = "once-core"))]
+    #[inline]
+    fn wait_via_asygnal(&mut self) -> ChildWait<'_> {
+        ChildWait {
+            child: wait::WaitTarget::Std(self),
+            listener: None,
+        }
+    }
+}
+
+#[cfg(feature = "once-tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-tokio")))]
+impl ChildExt for tokio::process::Child {
+    fn send_signal(&mut self, signal: Signal) -> io::Result<()> {
+        // The ID is only available until the child has been reaped.
+        match (self.try_wait()?, self.id()) {
+            (None, Some(pid)) => {
+                super::kill(pid as libc::pid_t, signal.into_raw())
+            }
+            _ => Err(exited()),
+        }
+    }
+
+    #[inline]
+    fn wait_via_asygnal(&mut self) -> ChildWait<'_> {
+        ChildWait {
+            child: wait::WaitTarget::Tokio(self),
+            listener: None,
+        }
+    }
+}
+
+fn exited() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "child process has already exited",
+    )
+}
+
+#[cfg(any(docsrs, feature = "once-core"))]
+pub use wait::ChildWait;
+
+#[cfg(any(docsrs, feature = "once-core"))]
+mod wait {
+    use super::*;
+    use crate::once::signal::listener::Listener;
+    use std::{
+        future::Future,
+        pin::Pin,
+        process::ExitStatus,
+        task::{Context, Poll},
+    };
+
+    /// A future that resolves to the exit status of a child process.
+    ///
+    /// This is created by [`ChildExt::wait_via_asygnal`].
+    ///
+    /// [`ChildExt::wait_via_asygnal`]: trait.ChildExt.html#tymethod.wait_via_asygnal
+    #[derive(Debug)]
+    #[must_use = "futures do nothing unless awaited"]
+    pub struct ChildWait<'a> {
+        pub(super) child: WaitTarget<'a>,
+        pub(super) listener: Option<Listener>,
+    }
+
+    /// The child process that a `ChildWait` waits for.
+    #[derive(Debug)]
+    pub(super) enum WaitTarget<'a> {
+        Std(&'a mut Child),
+        #[cfg(feature = "once-tokio")]
+        Tokio(&'a mut tokio::process::Child),
+    }
+
+    impl WaitTarget<'_> {
+        fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+            match self {
+                Self::Std(child) => child.try_wait(),
+                #[cfg(feature = "once-tokio")]
+                Self::Tokio(child) => child.try_wait(),
+            }
+        }
+    }
+
+    impl Future for ChildWait<'_> {
+        type Output = io::Result<ExitStatus>;
+
+        fn poll(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Self::Output> {
+            let this = &mut *self;
+
+            // Register before checking the child so that an exit in between
+            // is not missed.
+            let listener = match &mut this.listener {
+                Some(listener) => listener,
+                None => match Listener::register(Signal::Child.into()) {
+                    Ok(listener) => this.listener.insert(listener),
+                    Err(error) => return Poll::Ready(Err(error)),
+                },
+            };
+
+            // Reaping through the child records its status for later calls
+            // such as `Child::wait`.
+            let child = &mut this.child;
+            crate::child::poll_reap(listener, cx, || child.try_wait())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "once-core"))]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::{os::unix::process::ExitStatusExt, process::Command};
+
+    #[test]
+    fn signal_and_wait() {
+        let _children = test_util::children();
+
+        let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+
+        child.send_signal(Signal::Terminate).unwrap();
+        let status = test_util::block_on(child.wait_via_asygnal()).unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGTERM));
+
+        // The process ID may have been reused after exiting.
+        let error = child.send_signal(Signal::Terminate).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(feature = "once-tokio")]
+    #[test]
+    fn signal_and_wait_tokio() {
+        let _children = test_util::children();
+
+        let status = test_util::block_on(async {
+            let mut child =
+                tokio::process::Command::new("sleep").arg("10").spawn()?;
+
+            child.send_signal(Signal::Terminate)?;
+            let status = child.wait_via_asygnal().await?;
+
+            // The process ID may have been reused after exiting.
+            let error = child.send_signal(Signal::Terminate).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+            io::Result::Ok(status)
+        });
+        assert_eq!(status.unwrap().signal(), Some(libc::SIGTERM));
+    }
+}
@@ -0,0 +1,168 @@
+use std::{io, mem, os::unix::process::CommandExt as _, process::Command, ptr};
+
+use crate::{Signal, SignalSet};
+
+/// The signal state that a child process starts with, applied via
+/// [`CommandExt::signal_state`].
+///
+/// Children inherit ignored signals and the signal mask of the thread that
+/// spawns them across `exec`, which commonly leaves them unable to be
+/// interrupted. By default, this resets the action of every signal to the
+/// default and clears the mask.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{
+///     process::{ChildSignals, CommandExt},
+///     Signal,
+/// };
+/// use std::process::Command;
+///
+/// let child = Command::new("worker")
+///     .signal_state(ChildSignals::new().parent_death(Signal::Terminate))
+///     .spawn()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`CommandExt::signal_state`]: trait.CommandExt.html#tymethod.signal_state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChildSignals {
+    reset: SignalSet,
+    clear_mask: bool,
+    parent_death: Option<Signal>,
+}
+
+impl Default for ChildSignals {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChildSignals {
+    /// Creates a state that resets every signal to its default action and
+    /// clears the signal mask.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            reset: SignalSet::all(),
+            clear_mask: true,
+            parent_death: None,
+        }
+    }
+
+    /// Returns `self` with only `signals` reset to their default action, with
+    /// others keeping the action inherited from the parent.
+    #[inline]
+    pub fn reset(mut self, signals: SignalSet) -> Self {
+        self.reset = signals;
+        self
+    }
+
+    /// Returns `self` with the signal mask inherited from the parent rather
+    /// than cleared.
+    #[inline]
+    pub fn keep_mask(mut self) -> Self {
+        self.clear_mask = false;
+        self
+    }
+
+    /// Returns `self` with the child receiving `signal` when the parent exits.
+    ///
+    /// See [`parent_death`](fn.parent_death.html) for platform support. On
+    /// unsupported platforms, spawning fails.
+    #[inline]
+    pub fn parent_death(mut self, signal: Signal) -> Self {
+        self.parent_death = Some(signal);
+        self
+    }
+
+    /// Applies `self` to the current process.
+    ///
+    /// It is imperative that this function is signal-safe, since it runs in
+    /// the child between `fork` and `exec`.
+    fn apply(self) -> io::Result<()> {
+        for signal in self.reset {
+            let raw_signal = signal.into_raw();
+            if unsafe { libc::signal(raw_signal, libc::SIG_DFL) }
+                == libc::SIG_ERR
+            {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        if self.clear_mask {
+            let result = unsafe {
+                let mut set: libc::sigset_t = mem::zeroed();
+                libc::sigemptyset(&mut set);
+                libc::pthread_sigmask(libc::SIG_SETMASK, &set, ptr::null_mut())
+            };
+            if result != 0 {
+                return Err(io::Error::from_raw_os_error(result));
+            }
+        }
+
+        if let Some(signal) = self.parent_death {
+            // Avoid allocating the error message of `parent_death`.
+            if !super::PARENT_DEATH_SUPPORTED {
+                return Err(io::ErrorKind::Unsupported.into());
+            }
+            super::parent_death(signal)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Extension methods for [`Command`].
+///
+/// [`Command`]: https://doc.rust-lang.org/std/process/struct.Command.html
+pub trait CommandExt {
+    /// Sets the signal state that the child process starts with.
+    ///
+    /// If applying `state` fails, spawning the child fails with the error.
+    fn signal_state(&mut self, state: ChildSignals) -> &mut Command;
+}
+
+impl CommandExt for Command {
+    fn signal_state(&mut self, state: ChildSignals) -> &mut Command {
+        // SAFETY: `apply` is signal-safe and does not allocate.
+        unsafe { self.pre_exec(move || state.apply()) }
+    }
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "android")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resets_ignored() {
+        // `ChildEvents` tests reap every child.
+        #[cfg(feature = "once-core")]
+        let _children = crate::test_util::children();
+
+        let ignores_urgent = |command: &mut Command| {
+            let output = command
+                .args(["-c", "grep SigIgn /proc/self/status"])
+                .output()
+                .unwrap();
+            let stdout = String::from_utf8(output.stdout).unwrap();
+            let mask = stdout.trim().trim_start_matches("SigIgn:").trim();
+            let mask = u64::from_str_radix(mask, 16).unwrap();
+            mask & (1 << (libc::SIGURG - 1)) != 0
+        };
+
+        unsafe { libc::signal(libc::SIGURG, libc::SIG_IGN) };
+
+        let inherited = ignores_urgent(&mut Command::new("sh"));
+        let reset = ignores_urgent(
+            Command::new("sh").signal_state(ChildSignals::new()),
+        );
+
+        unsafe { libc::signal(libc::SIGURG, libc::SIG_DFL) };
+
+        assert!(inherited);
+        assert!(!reset);
+    }
+}
@@ -0,0 +1,502 @@
+//! Sending signals to other processes.
+
+use libc::pid_t;
+use std::io;
+
+use crate::Signal;
+
+mod child;
+mod command;
+mod status;
+
+pub use child::ChildExt;
+pub use command::{ChildSignals, CommandExt};
+pub use status::WaitStatus;
+
+#[cfg(any(docsrs, feature = "once-core"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub use child::ChildWait;
+
+/// The order in which [`signal_tree`] signals processes.
+///
+/// [`signal_tree`]: fn.signal_tree.html
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum TreeOrder {
+    /// Signal descendants before their parents, so that parents do not react
+    /// to their children exiting before those children are signaled.
+    ChildrenFirst,
+    /// Signal parents before their descendants, so that parents cannot spawn
+    /// new children in the meantime.
+    ParentFirst,
+}
+
+/// Sends `signal` to the process `pid` and all of its descendants.
+///
+/// # Platform Behavior
+///
+/// On Linux and Android, descendants are found by walking `/proc`, and are
+/// signaled in the given `order`. Processes that exit before being signaled are
+/// skipped.
+///
+/// Elsewhere, the process group of `pid` is signaled with a single call, in
+/// which case `order` has no effect. This fails with
+/// [`InvalidInput`](https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput)
+/// if `pid` is in the same process group as the current process, since that
+/// would also signal the current process.
+pub fn signal_tree(
+    pid: pid_t,
+    signal: Signal,
+    order: TreeOrder,
+) -> io::Result<()> {
+    signal_tree_impl(pid, signal.into_raw(), order)
+}
+
+/// Sends `signal` to every process in the process group of the current
+/// process, including itself.
+///
+/// This suits launchers that propagate e.g. `CTRL` + `C` semantics to every
+/// process they started in their own group.
+///
+/// This fails with
+/// [`InvalidInput`](https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput)
+/// if the group is led by the session leader and the current process is not
+/// that leader, since that is typically the group of an interactive shell. Use
+/// [`signal_own_group_forced`] to signal the group regardless.
+///
+/// [`signal_own_group_forced`]: fn.signal_own_group_forced.html
+pub fn signal_own_group(signal: Signal) -> io::Result<()> {
+    let (pgid, sid) = unsafe { (libc::getpgrp(), libc::getsid(0)) };
+    if sid < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    if pgid == sid && unsafe { libc::getpid() } != sid {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "process group is that of the session leader",
+        ));
+    }
+
+    signal_own_group_forced(signal)
+}
+
+/// Sends `signal` to every process in the process group of the current
+/// process, including itself, without the safeguards of
+/// [`signal_own_group`].
+///
+/// [`signal_own_group`]: fn.signal_own_group.html
+pub fn signal_own_group_forced(signal: Signal) -> io::Result<()> {
+    kill(0, signal.into_raw())
+}
+
+/// Freezes every process in the cgroup at `path`, e.g.
+/// `/sys/fs/cgroup/my-workers`.
+///
+/// Unlike sending `SIGSTOP`, frozen processes cannot observe being paused.
+/// Freezing happens asynchronously; use [`cgroup_is_frozen`] to check whether
+/// it has completed.
+///
+/// Both cgroup v2 (`cgroup.freeze`) and the v1 freezer controller
+/// (`freezer.state`) are supported.
+///
+/// [`cgroup_is_frozen`]: fn.cgroup_is_frozen.html
+#[cfg(all(target_os = "linux", feature = "cgroup"))]
+#[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", feature = "cgroup"))))]
+pub fn cgroup_freeze<P: AsRef<std::path::Path>>(path: P) -> io::Result<()> {
+    cgroup::write_state(path.as_ref(), true)
+}
+
+/// Thaws every process in the cgroup at `path` after [`cgroup_freeze`].
+///
+/// [`cgroup_freeze`]: fn.cgroup_freeze.html
+#[cfg(all(target_os = "linux", feature = "cgroup"))]
+#[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", feature = "cgroup"))))]
+pub fn cgroup_thaw<P: AsRef<std::path::Path>>(path: P) -> io::Result<()> {
+    cgroup::write_state(path.as_ref(), false)
+}
+
+/// Returns whether every process in the cgroup at `path` is frozen.
+#[cfg(all(target_os = "linux", feature = "cgroup"))]
+#[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", feature = "cgroup"))))]
+pub fn cgroup_is_frozen<P: AsRef<std::path::Path>>(
+    path: P,
+) -> io::Result<bool> {
+    cgroup::is_frozen(path.as_ref())
+}
+
+/// Requests that the current process receives `signal` when its parent exits.
+///
+/// This is useful for worker processes that should not outlive their
+/// supervisor. The request is cleared upon `fork` in the child, so it must be
+/// made by each process that wants it.
+///
+/// Note that if the parent already exited before calling this, no signal is
+/// delivered. Compare the result of `getppid` from before spawning to detect
+/// this.
+///
+/// # Platform Behavior
+///
+/// This uses `prctl(PR_SET_PDEATHSIG)` on Linux and Android and
+/// `procctl(PROC_PDEATHSIG_CTL)` on FreeBSD. On Linux, the "parent" is the
+/// thread that spawned the process, so a parent that spawns from a short-lived
+/// thread signals its children when that thread exits. Elsewhere, this fails with
+/// [`Unsupported`](https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Unsupported).
+pub fn parent_death(signal: Signal) -> io::Result<()> {
+    parent_death_impl(signal.into_raw())
+}
+
+/// Whether `parent_death` is supported on the target platform.
+const PARENT_DEATH_SUPPORTED: bool = cfg!(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+));
+
+fn kill(pid: pid_t, signal: libc::c_int) -> io::Result<()> {
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn signal_tree_impl(
+    pid: pid_t,
+    signal: libc::c_int,
+    order: TreeOrder,
+) -> io::Result<()> {
+    // Ensure the root exists before walking, so that the error is reported.
+    kill(pid, 0)?;
+
+    let mut tree = vec![pid];
+    let mut i = 0;
+    while i < tree.len() {
+        for child in proc::children(tree[i])? {
+            if !tree.contains(&child) {
+                tree.push(child);
+            }
+        }
+        i += 1;
+    }
+
+    // `tree` is in breadth-first order, so reversing it puts every process
+    // after all of its descendants.
+    if order == TreeOrder::ChildrenFirst {
+        tree.reverse();
+    }
+
+    for pid in tree {
+        match kill(pid, signal) {
+            Err(error) if error.raw_os_error() != Some(libc::ESRCH) => {
+                return Err(error);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn signal_tree_impl(
+    pid: pid_t,
+    signal: libc::c_int,
+    _order: TreeOrder,
+) -> io::Result<()> {
+    let pgid = unsafe { libc::getpgid(pid) };
+    if pgid < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    if pgid == unsafe { libc::getpgrp() } {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "process shares the process group of the current process",
+        ));
+    }
+
+    kill(-pgid, signal)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn parent_death_impl(signal: libc::c_int) -> io::Result<()> {
+    let signal = signal as libc::c_ulong;
+    if unsafe { libc::prctl(libc::PR_SET_PDEATHSIG, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(target_os = "freebsd")]
+fn parent_death_impl(mut signal: libc::c_int) -> io::Result<()> {
+    let result = unsafe {
+        libc::procctl(
+            libc::P_PID,
+            0,
+            libc::PROC_PDEATHSIG_CTL,
+            &mut signal as *mut libc::c_int as *mut libc::c_void,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+)))]
+fn parent_death_impl(_signal: libc::c_int) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "parent death signals are not supported on this platform",
+    ))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod proc {
+    use libc::pid_t;
+    use std::{fs, io};
+
+    /// Returns the direct children of `pid`, or none if it no longer exists.
+    pub fn children(pid: pid_t) -> io::Result<Vec<pid_t>> {
+        if !has_children_files()? {
+            return scan_children(pid);
+        }
+
+        let tasks = match fs::read_dir(format!("/proc/{}/task", pid)) {
+            Ok(tasks) => tasks,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Vec::new());
+            }
+            Err(error) => return Err(error),
+        };
+
+        let mut children = Vec::new();
+
+        for task in tasks {
+            match fs::read_to_string(task?.path().join("children")) {
+                Ok(list) => children.extend(
+                    list.split_whitespace().flat_map(str::parse::<pid_t>),
+                ),
+                // The task exited in the meantime.
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(children)
+    }
+
+    /// Returns whether the kernel provides `/proc/<pid>/task/<tid>/children`,
+    /// which requires `CONFIG_PROC_CHILDREN`.
+    fn has_children_files() -> io::Result<bool> {
+        let path = "/proc/self/task";
+        let task = fs::read_dir(path)?.next().transpose()?;
+        Ok(task.map_or(false, |task| task.path().join("children").exists()))
+    }
+
+    /// Finds the children of `pid` by scanning the parent of every process.
+    fn scan_children(pid: pid_t) -> io::Result<Vec<pid_t>> {
+        let mut children = Vec::new();
+
+        for entry in fs::read_dir("/proc")? {
+            let entry = entry?;
+            let child = match entry.file_name().to_str().map(str::parse) {
+                Some(Ok(child)) => child,
+                _ => continue,
+            };
+
+            let stat = match fs::read_to_string(entry.path().join("stat")) {
+                Ok(stat) => stat,
+                Err(_) => continue,
+            };
+
+            // The command name may contain spaces and parentheses, so parse
+            // the fields after its closing parenthesis: "<state> <ppid> ...".
+            let ppid = stat
+                .rfind(')')
+                .and_then(|end| stat[end + 1..].split_whitespace().nth(1))
+                .and_then(|ppid| ppid.parse::<pid_t>().ok());
+
+            if ppid == Some(pid) {
+                children.push(child);
+            }
+        }
+
+        Ok(children)
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "cgroup"))]
+mod cgroup {
+    use std::{fs, io, path::Path};
+
+    pub fn write_state(path: &Path, frozen: bool) -> io::Result<()> {
+        let v2 = path.join("cgroup.freeze");
+        if v2.exists() {
+            return fs::write(v2, if frozen { "1" } else { "0" });
+        }
+
+        let v1 = path.join("freezer.state");
+        fs::write(v1, if frozen { "FROZEN" } else { "THAWED" })
+    }
+
+    pub fn is_frozen(path: &Path) -> io::Result<bool> {
+        match fs::read_to_string(path.join("cgroup.events")) {
+            Ok(events) => {
+                return Ok(events.lines().any(|line| line == "frozen 1"));
+            }
+            Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                return Err(error);
+            }
+            Err(_) => {}
+        }
+
+        // The v1 state is "FREEZING" while in progress.
+        let state = fs::read_to_string(path.join("freezer.state"))?;
+        Ok(state.trim() == "FROZEN")
+    }
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "android")))]
+mod tests {
+    use super::*;
+    use std::{os::unix::process::ExitStatusExt, process::Command, thread};
+
+    #[test]
+    fn signal_tree_children_first() {
+        // `ChildEvents` tests reap every child.
+        #[cfg(feature = "once-core")]
+        let _children = crate::test_util::children();
+
+        let mut shell = Command::new("sh")
+            .args(["-c", "sleep 30 & sleep 30 & wait"])
+            .spawn()
+            .unwrap();
+        let pid = shell.id() as pid_t;
+
+        while proc::children(pid).unwrap().len() < 2 {
+            thread::yield_now();
+        }
+
+        signal_tree(pid, Signal::Terminate, TreeOrder::ChildrenFirst).unwrap();
+
+        let status = shell.wait().unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGTERM));
+    }
+
+    /// Creates an empty directory standing in for a cgroup, since the tests
+    /// can't rely on having one they may freeze.
+    #[cfg(all(target_os = "linux", feature = "cgroup"))]
+    fn fake_cgroup(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "asygnal-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir(&path).unwrap();
+        path
+    }
+
+    #[cfg(all(target_os = "linux", feature = "cgroup"))]
+    #[test]
+    fn cgroup_v2_freezer() {
+        use std::fs;
+
+        let path = fake_cgroup("cgroup-v2");
+        fs::write(path.join("cgroup.freeze"), "0").unwrap();
+        fs::write(path.join("cgroup.events"), "populated 1\nfrozen 0\n")
+            .unwrap();
+
+        cgroup_freeze(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(path.join("cgroup.freeze")).unwrap(),
+            "1"
+        );
+        assert!(!cgroup_is_frozen(&path).unwrap());
+
+        fs::write(path.join("cgroup.events"), "populated 1\nfrozen 1\n")
+            .unwrap();
+        assert!(cgroup_is_frozen(&path).unwrap());
+
+        cgroup_thaw(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(path.join("cgroup.freeze")).unwrap(),
+            "0"
+        );
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[cfg(all(target_os = "linux", feature = "cgroup"))]
+    #[test]
+    fn cgroup_v1_freezer() {
+        use std::fs;
+
+        let path = fake_cgroup("cgroup-v1");
+        fs::write(path.join("freezer.state"), "THAWED").unwrap();
+
+        cgroup_freeze(&path).unwrap();
+        assert!(cgroup_is_frozen(&path).unwrap());
+
+        // Freezing is still in progress.
+        fs::write(path.join("freezer.state"), "FREEZING\n").unwrap();
+        assert!(!cgroup_is_frozen(&path).unwrap());
+
+        cgroup_thaw(&path).unwrap();
+        assert!(!cgroup_is_frozen(&path).unwrap());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    /// Set for the copy of the test binary that signals its own group, which
+    /// is spawned into a new one so as to not signal the test runner.
+    #[cfg(feature = "once-core")]
+    const OWN_GROUP_VAR: &str = "ASYGNAL_OWN_GROUP_CHILD";
+
+    #[cfg(feature = "once-core")]
+    #[test]
+    fn signal_own_group_in_new_group() {
+        use std::{env, os::unix::process::CommandExt};
+
+        let _children = crate::test_util::children();
+
+        let status = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "process::tests::own_group_child"])
+            .args(["--ignored", "--test-threads=1"])
+            .env(OWN_GROUP_VAR, "1")
+            .process_group(0)
+            .status()
+            .unwrap();
+
+        assert!(status.success());
+    }
+
+    #[cfg(feature = "once-core")]
+    #[test]
+    #[ignore = "run by `signal_own_group_in_new_group`"]
+    fn own_group_child() {
+        use std::time::Duration;
+
+        if std::env::var_os(OWN_GROUP_VAR).is_none() {
+            return;
+        }
+
+        let signal = Signal::Terminate;
+        let mut iter = crate::SignalSet::from(signal).blocking_iter().unwrap();
+        let mut sleep = Command::new("sleep").arg("30").spawn().unwrap();
+
+        signal_own_group(signal).unwrap();
+
+        let timeout = Duration::from_secs(10);
+        assert_eq!(iter.next_timeout(timeout).unwrap(), Some(signal));
+        assert_eq!(sleep.wait().unwrap().signal(), Some(libc::SIGTERM));
+    }
+}
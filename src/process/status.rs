@@ -0,0 +1,213 @@
+use libc::c_int;
+use std::{
+    fmt,
+    os::unix::process::ExitStatusExt,
+    process::{ExitStatus, Output},
+};
+
+use crate::Signal;
+
+/// A raw wait status, as reported by `waitpid`, interpreted in terms of
+/// [`Signal`].
+///
+/// Its [`Display`] implementation formats consistent messages across
+/// platforms, such as "exited with status 1" or "terminated by SIGSEGV (core
+/// dumped)", which suits supervisors reporting why a child stopped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::process::WaitStatus;
+/// use std::process::Command;
+///
+/// let status = Command::new("worker").status()?;
+/// println!("worker {}", WaitStatus::from(status));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`Signal`]:  ../signal/enum.Signal.html
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WaitStatus(c_int);
+
+impl From<ExitStatus> for WaitStatus {
+    #[inline]
+    fn from(status: ExitStatus) -> Self {
+        Self(status.into_raw())
+    }
+}
+
+impl From<&Output> for WaitStatus {
+    #[inline]
+    fn from(output: &Output) -> Self {
+        output.status.into()
+    }
+}
+
+impl From<WaitStatus> for ExitStatus {
+    #[inline]
+    fn from(status: WaitStatus) -> Self {
+        ExitStatus::from_raw(status.0)
+    }
+}
+
+impl fmt::Display for WaitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(code) = self.code() {
+            return write!(f, "exited with status {}", code);
+        }
+
+        if let Some(raw_signal) = self.raw_signal() {
+            f.write_str("terminated by ")?;
+            write_signal(f, raw_signal)?;
+            if self.core_dumped() {
+                f.write_str(" (core dumped)")?;
+            }
+            return Ok(());
+        }
+
+        if let Some(raw_signal) = self.raw_stop_signal() {
+            f.write_str("stopped by ")?;
+            return write_signal(f, raw_signal);
+        }
+
+        if self.continued() {
+            return f.write_str("continued");
+        }
+
+        write!(f, "unknown wait status {:#x}", self.0)
+    }
+}
+
+impl WaitStatus {
+    /// Creates an instance from a raw status as written by `waitpid`.
+    #[inline]
+    pub const fn from_raw(status: c_int) -> Self {
+        Self(status)
+    }
+
+    /// Returns the raw status.
+    #[inline]
+    pub const fn into_raw(self) -> c_int {
+        self.0
+    }
+
+    /// Returns the signal that terminated the process and its exit code, of
+    /// which at most one is `Some`.
+    ///
+    /// The signal is `None` if the process exited normally, or if it was
+    /// terminated by a signal that [`Signal`] cannot represent, such as
+    /// `SIGKILL`. Use [`raw_signal`] for those.
+    ///
+    /// [`Signal`]:     ../signal/enum.Signal.html
+    /// [`raw_signal`]: #method.raw_signal
+    #[inline]
+    pub fn parts(self) -> (Option<Signal>, Option<i32>) {
+        (self.signal(), self.code())
+    }
+
+    /// Returns the exit code if the process exited normally.
+    #[inline]
+    pub fn code(self) -> Option<i32> {
+        if libc::WIFEXITED(self.0) {
+            Some(libc::WEXITSTATUS(self.0))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the signal that terminated the process, if it is one that
+    /// [`Signal`] can represent.
+    ///
+    /// [`Signal`]: ../signal/enum.Signal.html
+    #[inline]
+    pub fn signal(self) -> Option<Signal> {
+        self.raw_signal().and_then(Signal::from_raw)
+    }
+
+    /// Returns the raw signal that terminated the process, if any.
+    #[inline]
+    pub fn raw_signal(self) -> Option<c_int> {
+        if libc::WIFSIGNALED(self.0) {
+            Some(libc::WTERMSIG(self.0))
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the process was terminated by a signal and dumped
+    /// core.
+    #[inline]
+    pub fn core_dumped(self) -> bool {
+        libc::WIFSIGNALED(self.0) && libc::WCOREDUMP(self.0)
+    }
+
+    /// Returns the raw signal that stopped the process, if any.
+    ///
+    /// This is only reported when waiting with `WUNTRACED`.
+    #[inline]
+    pub fn raw_stop_signal(self) -> Option<c_int> {
+        if libc::WIFSTOPPED(self.0) {
+            Some(libc::WSTOPSIG(self.0))
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the process was resumed by `SIGCONT`.
+    ///
+    /// This is only reported when waiting with `WCONTINUED`.
+    #[inline]
+    pub fn continued(self) -> bool {
+        libc::WIFCONTINUED(self.0)
+    }
+}
+
+/// Writes the name of `raw_signal`, falling back to its number.
+fn write_signal(f: &mut fmt::Formatter, raw_signal: c_int) -> fmt::Result {
+    let name = match raw_signal {
+        // These cannot be handled and so are not represented by `Signal`.
+        libc::SIGKILL => Some("SIGKILL"),
+        libc::SIGSTOP => Some("SIGSTOP"),
+        _ => Signal::from_raw(raw_signal).map(Signal::name),
+    };
+
+    match name {
+        Some(name) => f.write_str(name),
+        None => write!(f, "signal {}", raw_signal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn run(script: &str) -> WaitStatus {
+        // `ChildEvents` tests reap every child.
+        #[cfg(feature = "once-core")]
+        let _children = crate::test_util::children();
+
+        Command::new("sh")
+            .args(["-c", script])
+            .status()
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn display() {
+        let exited = run("exit 3");
+        assert_eq!(exited.parts(), (None, Some(3)));
+        assert_eq!(exited.to_string(), "exited with status 3");
+
+        let terminated = run("kill -TERM $$");
+        assert_eq!(terminated.parts(), (Some(Signal::Terminate), None));
+        assert_eq!(terminated.to_string(), "terminated by SIGTERM");
+
+        let killed = run("kill -KILL $$");
+        assert_eq!(killed.parts(), (None, None));
+        assert_eq!(killed.raw_signal(), Some(libc::SIGKILL));
+        assert_eq!(killed.to_string(), "terminated by SIGKILL");
+    }
+}
@@ -0,0 +1,541 @@
+//! Scaffolding for signal-based sampling profilers.
+//!
+//! A [`Profiler`] arms a timer for each chosen thread that delivers `SIGPROF`
+//! to that thread at a fixed frequency, and calls a sampler from within the
+//! signal handler. The sampler can then capture the interrupted thread's
+//! state, such as by unwinding its stack from the provided `ucontext_t`.
+//!
+//! This leaves what to record and where to store it up to the sampler, making
+//! it a foundation for in-process profilers like [`pprof`].
+//!
+//! [`Profiler`]: struct.Profiler.html
+//! [`pprof`]:    https://docs.rs/pprof
+
+use libc::{c_int, c_void, pid_t};
+use std::{
+    io, mem, ptr,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// The sampler called by the signal handler, or null if not profiling.
+static SAMPLER: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Serializes starting and stopping.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// An OS-level thread ID, as returned by `gettid`.
+///
+/// Unlike [`std::thread::ThreadId`], this identifies the thread to the kernel
+/// and so can be targeted by signals.
+///
+/// [`std::thread::ThreadId`]: https://doc.rust-lang.org/std/thread/struct.ThreadId.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ThreadId(pid_t);
+
+impl ThreadId {
+    /// Returns the ID of the calling thread.
+    ///
+    /// This is async-signal-safe.
+    #[inline]
+    pub fn current() -> Self {
+        Self(unsafe { libc::syscall(libc::SYS_gettid) } as pid_t)
+    }
+
+    /// Creates an instance from a raw thread ID.
+    #[inline]
+    pub const fn from_raw(tid: pid_t) -> Self {
+        Self(tid)
+    }
+
+    /// Returns the raw thread ID.
+    #[inline]
+    pub const fn into_raw(self) -> pid_t {
+        self.0
+    }
+}
+
+/// The clock that drives sampling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Clock {
+    /// CPU time consumed by each thread, so that idle threads are not
+    /// sampled. This is what CPU profilers typically use.
+    Cpu,
+    /// Wall-clock time, so that threads are sampled regardless of whether
+    /// they are running, such as to find where they block.
+    Wall,
+}
+
+impl Default for Clock {
+    #[inline]
+    fn default() -> Self {
+        Clock::Cpu
+    }
+}
+
+/// The state of an interrupted thread, passed to the sampler.
+#[derive(Debug)]
+pub struct Sample {
+    thread: ThreadId,
+    ucontext: *mut c_void,
+}
+
+impl Sample {
+    /// Returns the thread that was interrupted.
+    #[inline]
+    pub fn thread(&self) -> ThreadId {
+        self.thread
+    }
+
+    /// Returns a pointer to the `ucontext_t` of the interrupted thread, from
+    /// which its registers can be read for unwinding.
+    #[inline]
+    pub fn ucontext(&self) -> *mut c_void {
+        self.ucontext
+    }
+}
+
+/// A configuration for delivering `SIGPROF` to threads at a fixed frequency.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::profiler::{Profiler, Sample};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static SAMPLES: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn count(_: &Sample) {
+///     SAMPLES.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// // SAFETY: `count` only performs an atomic operation.
+/// let guard = unsafe { Profiler::new(count) }.with_frequency(99).start()?;
+///
+/// // Run the workload...
+///
+/// drop(guard);
+/// println!("{} samples", SAMPLES.load(Ordering::Relaxed));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Profiler {
+    sampler: fn(&Sample),
+    frequency: u32,
+    clock: Clock,
+    threads: Vec<ThreadId>,
+}
+
+impl Profiler {
+    /// Creates a profiler that calls `sampler` upon each sample.
+    ///
+    /// By default, this samples the thread that calls [`start`] at 99 Hz of
+    /// [CPU time](enum.Clock.html#variant.Cpu).
+    ///
+    /// # Safety
+    ///
+    /// `sampler` is called from within a signal handler and so must be
+    /// async-signal-safe. In particular, it must not allocate or take locks.
+    ///
+    /// [`start`]: #method.start
+    #[inline]
+    pub unsafe fn new(sampler: fn(&Sample)) -> Self {
+        Self {
+            sampler,
+            frequency: 99,
+            clock: Clock::Cpu,
+            threads: Vec::new(),
+        }
+    }
+
+    /// Returns `self` with `frequency` samples per second of each thread.
+    ///
+    /// An odd frequency such as 99 avoids sampling in lockstep with periodic
+    /// activity in the program.
+    #[inline]
+    pub fn with_frequency(mut self, frequency: u32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Returns `self` driven by `clock`.
+    #[inline]
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns `self` with `thread` also sampled.
+    ///
+    /// If no threads are added, the thread that calls [`start`] is sampled.
+    ///
+    /// [`start`]: #method.start
+    #[inline]
+    pub fn with_thread(mut self, thread: ThreadId) -> Self {
+        self.threads.push(thread);
+        self
+    }
+
+    /// Returns `self` with `threads` also sampled.
+    #[inline]
+    pub fn with_threads(
+        mut self,
+        threads: impl IntoIterator<Item = ThreadId>,
+    ) -> Self {
+        self.threads.extend(threads);
+        self
+    }
+
+    /// Installs the `SIGPROF` handler and starts sampling until the returned
+    /// guard is dropped.
+    ///
+    /// Only one profiler can run at a time, so this fails with
+    /// [`AlreadyExists`] if another is running. The `SIGPROF` handler replaces
+    /// any other, including one registered through this crate, and is
+    /// restored upon stopping. Registering `SIGPROF` through this crate while
+    /// profiling replaces the profiler's handler instead.
+    ///
+    /// [`AlreadyExists`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.AlreadyExists
+    pub fn start(self) -> io::Result<ProfilerGuard> {
+        if self.frequency == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "sampling frequency must be non-zero",
+            ));
+        }
+
+        let lock = LOCK.lock().unwrap_or_else(|error| error.into_inner());
+
+        if !SAMPLER.load(Ordering::SeqCst).is_null() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "a profiler is already running",
+            ));
+        }
+
+        let threads = if self.threads.is_empty() {
+            vec![ThreadId::current()]
+        } else {
+            self.threads
+        };
+
+        SAMPLER.store(self.sampler as *mut (), Ordering::SeqCst);
+        let (replaced, underlying) = match install_handler() {
+            Ok(actions) => actions,
+            Err(error) => {
+                SAMPLER.store(ptr::null_mut(), Ordering::SeqCst);
+                return Err(error);
+            }
+        };
+
+        let mut guard = ProfilerGuard {
+            timers: Vec::with_capacity(threads.len()),
+            replaced,
+            underlying,
+        };
+
+        let period = Duration::from_secs(1) / self.frequency;
+        for thread in threads {
+            let result = create_timer(thread, self.clock).and_then(|timer| {
+                guard.timers.push(timer);
+                arm_timer(timer, period)
+            });
+
+            if let Err(error) = result {
+                // Dropping `guard` stops the timers created so far, which
+                // requires the lock.
+                drop(lock);
+                drop(guard);
+                return Err(error);
+            }
+        }
+
+        Ok(guard)
+    }
+}
+
+/// Stops sampling when dropped.
+///
+/// This deletes the timers and restores the previous `SIGPROF` action.
+#[derive(Debug)]
+#[must_use = "sampling stops when the guard is dropped"]
+pub struct ProfilerGuard {
+    timers: Vec<libc::timer_t>,
+    /// The action replaced by the profiler's handler.
+    replaced: libc::sigaction,
+    /// The action for when neither the profiler's handler nor the one of this
+    /// crate is installed.
+    underlying: libc::sigaction,
+}
+
+// SAFETY: Timer IDs are process-wide and not tied to the creating thread.
+unsafe impl Send for ProfilerGuard {}
+unsafe impl Sync for ProfilerGuard {}
+
+impl Drop for ProfilerGuard {
+    fn drop(&mut self) {
+        let _lock = LOCK.lock().unwrap_or_else(|error| error.into_inner());
+
+        for &timer in &self.timers {
+            unsafe { libc::timer_delete(timer) };
+        }
+
+        // A signal that was already generated may still be pending, which the
+        // handler ignores once the sampler is cleared.
+        SAMPLER.store(ptr::null_mut(), Ordering::SeqCst);
+
+        with_crate_previous(|crate_previous| match crate_previous {
+            // Our handler is registered and thus stays installed, either
+            // having replaced the profiler's or by putting it back.
+            Some(previous) => {
+                *previous = self.underlying;
+                if handler_installed() {
+                    unsafe {
+                        libc::sigaction(
+                            libc::SIGPROF,
+                            &self.replaced,
+                            ptr::null_mut(),
+                        )
+                    };
+                }
+            }
+            None => unsafe {
+                libc::sigaction(
+                    libc::SIGPROF,
+                    &self.underlying,
+                    ptr::null_mut(),
+                );
+            },
+        });
+    }
+}
+
+/// Runs `f` with the action that this crate restores upon releasing its
+/// `SIGPROF` handler, which is `None` if it isn't installed.
+///
+/// Handlers of this crate are not installed or released in the meantime.
+fn with_crate_previous<R>(
+    f: impl FnOnce(&mut Option<libc::sigaction>) -> R,
+) -> R {
+    #[cfg(feature = "once-core")]
+    return crate::once::signal::with_previous(crate::Signal::Profile, f);
+
+    #[cfg(not(feature = "once-core"))]
+    f(&mut None)
+}
+
+extern "C" fn signal_handler(
+    _: c_int,
+    _: *mut libc::siginfo_t,
+    ucontext: *mut c_void,
+) {
+    let sampler = SAMPLER.load(Ordering::SeqCst);
+    if sampler.is_null() {
+        return;
+    }
+    let sampler: fn(&Sample) = unsafe { mem::transmute(sampler) };
+
+    // The sampler may clobber `errno` of the interrupted code.
+    let errno = errno();
+    let saved = unsafe { *errno };
+    sampler(&Sample {
+        thread: ThreadId::current(),
+        ucontext,
+    });
+    unsafe { *errno = saved };
+}
+
+/// Returns whether the profiler's handler is the current `SIGPROF` action.
+fn handler_installed() -> bool {
+    let mut action: libc::sigaction = unsafe { mem::zeroed() };
+    unsafe { libc::sigaction(libc::SIGPROF, ptr::null(), &mut action) };
+    action.sa_sigaction == signal_handler as *const () as usize
+}
+
+/// Installs the `SIGPROF` handler, returning the action it replaced and the
+/// action to restore once neither it nor the handler of this crate is
+/// installed.
+fn install_handler() -> io::Result<(libc::sigaction, libc::sigaction)> {
+    let mut action: libc::sigaction = unsafe { mem::zeroed() };
+    action.sa_sigaction = signal_handler as *const () as usize;
+    action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+    unsafe { libc::sigemptyset(&mut action.sa_mask) };
+
+    with_crate_previous(|crate_previous| {
+        let mut replaced: libc::sigaction = unsafe { mem::zeroed() };
+        if unsafe { libc::sigaction(libc::SIGPROF, &action, &mut replaced) }
+            != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Releasing the handler of this crate must keep the profiler's.
+        let underlying = match crate_previous {
+            Some(previous) => mem::replace(previous, action),
+            None => replaced,
+        };
+        Ok((replaced, underlying))
+    })
+}
+
+/// Creates a timer that delivers `SIGPROF` to `thread`.
+fn create_timer(thread: ThreadId, clock: Clock) -> io::Result<libc::timer_t> {
+    let clock_id = match clock {
+        // The kernel encodes the CPU clock of a thread in its clock ID, as in
+        // `MAKE_THREAD_CPUCLOCK(tid, CPUCLOCK_SCHED)`.
+        Clock::Cpu => ((!thread.0) << 3) | 4 | 2,
+        Clock::Wall => libc::CLOCK_MONOTONIC,
+    };
+
+    let mut event: libc::sigevent = unsafe { mem::zeroed() };
+    event.sigev_notify = libc::SIGEV_THREAD_ID;
+    event.sigev_signo = libc::SIGPROF;
+    event.sigev_notify_thread_id = thread.0;
+
+    let mut timer: libc::timer_t = ptr::null_mut();
+    match unsafe { libc::timer_create(clock_id, &mut event, &mut timer) } {
+        0 => Ok(timer),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+fn arm_timer(timer: libc::timer_t, period: Duration) -> io::Result<()> {
+    let period = libc::timespec {
+        tv_sec: period.as_secs() as _,
+        tv_nsec: period.subsec_nanos() as _,
+    };
+    let spec = libc::itimerspec {
+        it_interval: period,
+        it_value: period,
+    };
+
+    match unsafe { libc::timer_settime(timer, 0, &spec, ptr::null_mut()) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Returns a pointer to `errno` of the calling thread.
+fn errno() -> *mut c_int {
+    #[cfg(target_os = "linux")]
+    unsafe {
+        libc::__errno_location()
+    }
+    #[cfg(target_os = "android")]
+    unsafe {
+        libc::__errno()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::atomic::AtomicUsize, time::Instant};
+
+    /// Runs `f` while no other test handles or sends `SIGPROF`.
+    fn exclusive(f: impl FnOnce()) {
+        #[cfg(feature = "once-core")]
+        crate::test_util::run_exclusive(f);
+
+        #[cfg(not(feature = "once-core"))]
+        f();
+    }
+
+    #[test]
+    fn samples_thread() {
+        static SAMPLES: AtomicUsize = AtomicUsize::new(0);
+        static THREAD: AtomicUsize = AtomicUsize::new(0);
+
+        fn sample(sample: &Sample) {
+            SAMPLES.fetch_add(1, Ordering::SeqCst);
+            THREAD.store(sample.thread().into_raw() as usize, Ordering::SeqCst);
+        }
+
+        exclusive(|| {
+            // Failing to target a thread stops the profiler.
+            let missing = ThreadId::from_raw(pid_t::MAX);
+            assert!(unsafe { Profiler::new(sample) }
+                .with_thread(missing)
+                .start()
+                .is_err());
+
+            let guard = unsafe { Profiler::new(sample) }
+                .with_frequency(1000)
+                .with_clock(Clock::Wall)
+                .start()
+                .unwrap();
+
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while SAMPLES.load(Ordering::SeqCst) < 5
+                && Instant::now() < deadline
+            {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+
+            assert!(unsafe { Profiler::new(sample) }.start().is_err());
+            drop(guard);
+
+            assert!(SAMPLES.load(Ordering::SeqCst) >= 5);
+            assert_eq!(
+                THREAD.load(Ordering::SeqCst),
+                ThreadId::current().into_raw() as usize,
+            );
+        });
+    }
+
+    #[cfg(feature = "once-core")]
+    #[test]
+    fn coexists_with_registration() {
+        use crate::{once::signal::SignalOnce, Signal};
+
+        fn ignore(_: &Sample) {}
+
+        fn current_action() -> usize {
+            let mut action: libc::sigaction = unsafe { mem::zeroed() };
+            unsafe { libc::sigaction(libc::SIGPROF, ptr::null(), &mut action) };
+            action.sa_sigaction
+        }
+
+        // A long period, so that no samples are taken.
+        let start = || {
+            unsafe { Profiler::new(ignore) }
+                .with_frequency(1)
+                .with_clock(Clock::Cpu)
+                .start()
+                .unwrap()
+        };
+
+        exclusive(|| {
+            let original = current_action();
+
+            // Releasing a registration keeps the running profiler.
+            let once = SignalOnce::register(Signal::Profile).unwrap();
+            let guard = start();
+            drop(once);
+            assert!(handler_installed());
+            drop(guard);
+            assert_eq!(current_action(), original);
+
+            // Stopping the profiler keeps a live registration.
+            let guard = start();
+            let once = SignalOnce::register(Signal::Profile).unwrap();
+            drop(guard);
+            let registered = current_action();
+            assert_ne!(registered, original);
+            assert!(!handler_installed());
+            drop(once);
+            assert_eq!(current_action(), original);
+
+            // Both stay installed until the last one is gone.
+            let once = SignalOnce::register(Signal::Profile).unwrap();
+            let guard = start();
+            drop(guard);
+            assert_eq!(current_action(), registered);
+            drop(once);
+            assert_eq!(current_action(), original);
+        });
+    }
+}
@@ -0,0 +1,291 @@
+//! Recording and deterministic replay of signal sequences.
+//!
+//! A [`Recorder`] notes each signal as it is received along with when it
+//! arrived, producing a [`Recording`] that can be saved to a file. Loading that
+//! file elsewhere, such as in CI, and [replaying] it re-delivers the same
+//! signals in the same order with the same relative timing, which makes races
+//! observed in production (e.g. a second `SIGTERM` arriving mid-shutdown)
+//! reproducible.
+//!
+//! # File Format
+//!
+//! Recordings are plain text with one signal per line, as the offset in
+//! nanoseconds from the start of the recording followed by the signal name:
+//!
+//! ```text
+//! # Lines starting with '#' are ignored.
+//! 0 SIGTERM
+//! 1500000000 SIGTERM
+//! ```
+//!
+//! Names are used rather than numbers so that recordings are portable across
+//! platforms.
+//!
+//! # Examples
+//!
+//! Recording from a stream of signals:
+//!
+//! ```no_run
+//! use asygnal::replay::Recorder;
+//!
+//! # fn example(signals: impl Iterator<Item = asygnal::Signal>) -> std::io::Result<()> {
+//! let mut recorder = Recorder::new();
+//!
+//! for signal in signals {
+//!     recorder.record(signal);
+//!     // Handle `signal`...
+//! }
+//!
+//! recorder.finish().save("signals.replay")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Replaying it into the current process:
+//!
+//! ```no_run
+//! use asygnal::replay::Recording;
+//!
+//! Recording::load("signals.replay")?.replay()?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! [`Recorder`]:  struct.Recorder.html
+//! [`Recording`]: struct.Recording.html
+//! [replaying]:   struct.Recording.html#method.replay
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::Signal;
+
+/// A sequence of signals, each with its offset from the start of the
+/// recording.
+///
+/// Offsets never decrease from one signal to the next.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Recording {
+    events: Vec<(Duration, Signal)>,
+}
+
+impl Recording {
+    /// Creates a new, empty recording.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    /// Appends `signal` at `offset` from the start of the recording.
+    ///
+    /// An `offset` earlier than that of the last signal is raised to it, so
+    /// that order is preserved.
+    pub fn push(&mut self, offset: Duration, signal: Signal) {
+        let offset = match self.events.last() {
+            Some(&(last, _)) => offset.max(last),
+            None => offset,
+        };
+        self.events.push((offset, signal));
+    }
+
+    /// Returns the recorded signals with their offsets, in order.
+    #[inline]
+    pub fn events(&self) -> &[(Duration, Signal)] {
+        &self.events
+    }
+
+    /// Returns the number of recorded signals.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns whether no signals were recorded.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Writes `self` to `writer` in the [file format](index.html#file-format).
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for (offset, signal) in &self.events {
+            writeln!(writer, "{} {}", offset.as_nanos(), signal.name())?;
+        }
+        writer.flush()
+    }
+
+    /// Reads a recording from `reader` in the
+    /// [file format](index.html#file-format).
+    ///
+    /// Malformed lines fail with [`InvalidData`].
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_from<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut recording = Self::new();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |message: &str| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {}", index + 1, message),
+                )
+            };
+
+            let (offset, name) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| invalid("expected an offset and a signal"))?;
+            let offset = offset
+                .parse::<u64>()
+                .map_err(|_| invalid("invalid offset"))?;
+            let signal = name
+                .trim()
+                .parse::<Signal>()
+                .map_err(|error| invalid(&error.to_string()))?;
+
+            recording.push(Duration::from_nanos(offset), signal);
+        }
+
+        Ok(recording)
+    }
+
+    /// Saves `self` to the file at `path`, replacing any existing one.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Loads a recording from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Raises each signal in the current process at its offset from now,
+    /// blocking until done.
+    ///
+    /// This goes through the real delivery path, so the signals must have
+    /// handlers or else they perform their default action. Note that signals
+    /// raised faster than they are handled may be coalesced by the OS. Use
+    /// [`replay_with`](#method.replay_with) to deliver them elsewhere.
+    pub fn replay(&self) -> io::Result<()> {
+        self.replay_with(Signal::raise)
+    }
+
+    /// Calls `deliver` with each signal at its offset from now, blocking until
+    /// done or until `deliver` fails.
+    ///
+    /// Passing [`SignalSource::deliver`] feeds the signals to futures and
+    /// streams directly, without raising them in the process.
+    ///
+    /// [`SignalSource::deliver`]: ../once/signal/struct.SignalSource.html#method.deliver
+    pub fn replay_with<F>(&self, mut deliver: F) -> io::Result<()>
+    where
+        F: FnMut(Signal) -> io::Result<()>,
+    {
+        let start = Instant::now();
+
+        for &(offset, signal) in &self.events {
+            let elapsed = start.elapsed();
+            if offset > elapsed {
+                thread::sleep(offset - elapsed);
+            }
+            deliver(signal)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Records received signals with their arrival time into a [`Recording`].
+///
+/// Offsets are measured from when the recorder was created.
+///
+/// [`Recording`]: struct.Recording.html
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    start: Instant,
+    recording: Recording,
+}
+
+impl Default for Recorder {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recorder {
+    /// Creates a new recorder starting now.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            recording: Recording::new(),
+        }
+    }
+
+    /// Records `signal` as received now.
+    #[inline]
+    pub fn record(&mut self, signal: Signal) {
+        self.recording.push(self.start.elapsed(), signal);
+    }
+
+    /// Returns the signals recorded so far.
+    #[inline]
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// Stops recording, returning the recorded signals.
+    #[inline]
+    pub fn finish(self) -> Recording {
+        self.recording
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut recording = Recording::new();
+        recording.push(Duration::from_millis(0), Signal::Terminate);
+        recording.push(Duration::from_millis(20), Signal::Hangup);
+        recording.push(Duration::from_millis(10), Signal::Terminate);
+
+        let mut bytes = Vec::new();
+        recording.write_to(&mut bytes).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+        assert_eq!(text, "0 SIGTERM\n20000000 SIGHUP\n20000000 SIGTERM\n");
+
+        let read = Recording::read_from(text.as_bytes()).unwrap();
+        assert_eq!(read, recording);
+
+        let error = Recording::read_from(&b"# header\n\n10 SIGNOPE\n"[..]);
+        assert_eq!(error.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let start = Instant::now();
+        let mut delivered = Vec::new();
+        read.replay_with(|signal| {
+            delivered.push((start.elapsed(), signal));
+            Ok(())
+        })
+        .unwrap();
+
+        let signals: Vec<_> = delivered.iter().map(|&(_, s)| s).collect();
+        assert_eq!(
+            signals,
+            [Signal::Terminate, Signal::Hangup, Signal::Terminate]
+        );
+        assert!(delivered[2].0 >= Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,200 @@
+//! Detecting when the process was stopped and resumed.
+//!
+//! Being stopped, such as by `CTRL` + `Z` in a shell until `fg`, freezes the
+//! whole process while the rest of the world keeps going. Timing-sensitive
+//! code such as heartbeats and leases then sees a jump in time and may
+//! immediately declare a failure. [`ResumeEvents`] notifies such code when the
+//! process is resumed, along with an estimate of how long it was stopped, so
+//! that it can re-sync instead.
+//!
+//! [`ResumeEvents`]: struct.ResumeEvents.html
+
+use std::{
+    io,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    once::{signal::listener::Listener, CancelSafe},
+    Signal, SignalSet,
+};
+
+/// The process being resumed after having been stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Resume {
+    stopped_for: Option<Duration>,
+    resumed_at: Instant,
+}
+
+impl Resume {
+    /// Returns how long the process was stopped, if known.
+    ///
+    /// This is only known if the process was stopped via [`TermStop`], such
+    /// as by `CTRL` + `Z`. It is `None` if stopped via `SIGSTOP`, which cannot
+    /// be handled, in which case the process was stopped for an unknown amount
+    /// of time before [`resumed_at`].
+    ///
+    /// [`TermStop`]:   ../signal/enum.Signal.html#variant.TermStop
+    /// [`resumed_at`]: #method.resumed_at
+    #[inline]
+    pub fn stopped_for(&self) -> Option<Duration> {
+        self.stopped_for
+    }
+
+    /// Returns when the resume was observed.
+    #[inline]
+    pub fn resumed_at(&self) -> Instant {
+        self.resumed_at
+    }
+}
+
+/// A source of the times the process was resumed after having been stopped.
+///
+/// This registers handlers for [`Cont`] and [`TermStop`]. Since handling
+/// [`TermStop`] replaces its default action of stopping the process, `self`
+/// stops the process via `SIGSTOP` upon receiving it, measuring how long the
+/// process stays stopped. This happens when `self` is polled, so the process
+/// only stops once the task polling `self` runs.
+///
+/// Resumes are coalesced if the process is stopped and resumed several times
+/// in between polls.
+///
+/// With the `stream` feature, this implements `Stream` and `FusedStream`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::resume::ResumeEvents;
+/// use std::future::poll_fn;
+///
+/// # async fn example() -> std::io::Result<()> {
+/// let mut resumes = ResumeEvents::register()?;
+///
+/// loop {
+///     let resume = poll_fn(|cx| resumes.poll_next(cx)).await?;
+///     if let Some(stopped_for) = resume.stopped_for() {
+///         println!("stopped for {:?}, renewing leases", stopped_for);
+///     }
+/// }
+/// # }
+/// ```
+///
+/// [`Cont`]:     ../signal/enum.Signal.html#variant.Cont
+/// [`TermStop`]: ../signal/enum.Signal.html#variant.TermStop
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct ResumeEvents {
+    listener: Listener,
+    /// Whether `self` stopped the process, in which case the `Cont` that
+    /// resumed it was already reported.
+    self_stopped: bool,
+}
+
+// SAFETY: The process is only stopped in the same poll that returns the
+// resume.
+unsafe impl CancelSafe for ResumeEvents {}
+
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::Stream for ResumeEvents {
+    type Item = io::Result<Resume>;
+
+    #[inline]
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Self::Item>> {
+        ResumeEvents::poll_next(&mut self, cx).map(Some)
+    }
+}
+
+/// Never terminated, since `self` never ends.
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+impl futures_core::stream::FusedStream for ResumeEvents {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
+
+impl ResumeEvents {
+    /// Registers handlers for [`Cont`] and [`TermStop`].
+    ///
+    /// [`Cont`]:     ../signal/enum.Signal.html#variant.Cont
+    /// [`TermStop`]: ../signal/enum.Signal.html#variant.TermStop
+    #[inline]
+    pub fn register() -> io::Result<Self> {
+        let signals = SignalSet::new().cont().term_stop();
+        Ok(Self {
+            listener: Listener::register(signals)?,
+            self_stopped: false,
+        })
+    }
+
+    /// Polls for the next time the process is resumed.
+    pub fn poll_next(&mut self, cx: &mut Context) -> Poll<io::Result<Resume>> {
+        loop {
+            let signal = match self.listener.poll_next(cx) {
+                Poll::Ready(Ok(signal)) => signal,
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            match signal {
+                Signal::TermStop => {
+                    let stopped_at = Instant::now();
+                    if unsafe { libc::raise(libc::SIGSTOP) } != 0 {
+                        return Poll::Ready(Err(io::Error::last_os_error()));
+                    }
+                    let resumed_at = Instant::now();
+
+                    self.self_stopped = true;
+                    return Poll::Ready(Ok(Resume {
+                        stopped_for: Some(resumed_at - stopped_at),
+                        resumed_at,
+                    }));
+                }
+                _ if self.self_stopped => self.self_stopped = false,
+                _ => {
+                    return Poll::Ready(Ok(Resume {
+                        stopped_for: None,
+                        resumed_at: Instant::now(),
+                    }))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn resume_without_stop() {
+        test_util::run_exclusive(|| {
+            let mut resumes = ResumeEvents::register().unwrap();
+            let poll = |resumes: &mut ResumeEvents| {
+                test_util::poll(&mut std::future::poll_fn(|cx| {
+                    resumes.poll_next(cx)
+                }))
+            };
+            assert!(poll(&mut resumes).is_pending());
+
+            // Being resumed by `SIGCONT` alone means the stop was unobserved.
+            let before = Instant::now();
+            Signal::Cont.raise().unwrap();
+            match poll(&mut resumes) {
+                Poll::Ready(Ok(resume)) => {
+                    assert_eq!(resume.stopped_for(), None);
+                    assert!(resume.resumed_at() >= before);
+                }
+                other => panic!("unexpected poll result: {:?}", other),
+            }
+            assert!(poll(&mut resumes).is_pending());
+        });
+    }
+}
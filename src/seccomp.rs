@@ -0,0 +1,269 @@
+//! Handling of system calls trapped by seccomp.
+//!
+//! A seccomp filter returning `SECCOMP_RET_TRAP` makes the kernel skip the
+//! system call and send `SIGSYS` to the calling thread instead. The handler
+//! installed by [`install_trap_handler`] surfaces the denied system call to a
+//! callback, which can log it or emulate it by writing a return value into the
+//! interrupted thread's `ucontext_t`.
+//!
+//! [`install_trap_handler`]: fn.install_trap_handler.html
+
+use libc::{c_int, c_uint, c_void};
+use std::{
+    io, mem, ptr,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Mutex,
+    },
+};
+
+/// The callback called by the signal handler, or null if not installed.
+static CALLBACK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Serializes installing and uninstalling.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// The `si_code` of a `SIGSYS` sent by seccomp.
+const SYS_SECCOMP: c_int = 1;
+
+/// The layout of `siginfo_t` for `SIGSYS`, which `libc` does not expose.
+#[repr(C)]
+struct SigsysInfo {
+    signo: c_int,
+    errno: c_int,
+    code: c_int,
+    call_addr: *mut c_void,
+    syscall: c_int,
+    arch: c_uint,
+}
+
+/// A `SIGSYS` received by the handler, passed to the callback.
+#[derive(Debug)]
+pub struct Trap {
+    seccomp: bool,
+    syscall: c_int,
+    arch: u32,
+    call_addr: *mut c_void,
+    ucontext: *mut c_void,
+}
+
+impl Trap {
+    /// Returns `true` if the signal was sent by a seccomp filter.
+    ///
+    /// Otherwise, such as when the signal was sent with `kill`, the other
+    /// fields are meaningless.
+    #[inline]
+    pub fn is_seccomp(&self) -> bool {
+        self.seccomp
+    }
+
+    /// Returns the number of the denied system call.
+    #[inline]
+    pub fn syscall(&self) -> c_int {
+        self.syscall
+    }
+
+    /// Returns the `AUDIT_ARCH_*` value of the denied system call, which
+    /// determines how to interpret its number.
+    #[inline]
+    pub fn arch(&self) -> u32 {
+        self.arch
+    }
+
+    /// Returns the address of the instruction after the system call.
+    #[inline]
+    pub fn call_addr(&self) -> *mut c_void {
+        self.call_addr
+    }
+
+    /// Returns a pointer to the `ucontext_t` of the interrupted thread, whose
+    /// registers hold the arguments of the system call.
+    ///
+    /// Emulating the system call is done by writing its return value to the
+    /// appropriate register, such as `REG_RAX` on x86-64.
+    #[inline]
+    pub fn ucontext(&self) -> *mut c_void {
+        self.ucontext
+    }
+}
+
+/// Restores the previous `SIGSYS` action when dropped.
+#[derive(Debug)]
+#[must_use = "the handler is uninstalled when the guard is dropped"]
+pub struct TrapGuard {
+    previous: libc::sigaction,
+}
+
+impl Drop for TrapGuard {
+    fn drop(&mut self) {
+        let _lock = LOCK.lock().unwrap_or_else(|error| error.into_inner());
+
+        unsafe {
+            libc::sigaction(libc::SIGSYS, &self.previous, ptr::null_mut());
+        }
+        CALLBACK.store(ptr::null_mut(), Ordering::SeqCst);
+    }
+}
+
+/// Installs a `SIGSYS` handler that calls `callback` upon each trapped system
+/// call, until the returned guard is dropped.
+///
+/// Only one handler can be installed at a time, so this fails with
+/// [`AlreadyExists`] if another is. The handler replaces any other, including
+/// one registered through this crate, and is restored upon dropping the guard.
+///
+/// # Safety
+///
+/// `callback` is called from within a signal handler and so must be
+/// async-signal-safe. In particular, it must not allocate or take locks.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::seccomp::{install_trap_handler, Trap};
+/// use std::sync::atomic::{AtomicI32, Ordering};
+///
+/// static DENIED: AtomicI32 = AtomicI32::new(-1);
+///
+/// fn record(trap: &Trap) {
+///     if trap.is_seccomp() {
+///         DENIED.store(trap.syscall(), Ordering::Relaxed);
+///     }
+/// }
+///
+/// // SAFETY: `record` only performs an atomic operation.
+/// let guard = unsafe { install_trap_handler(record) }?;
+///
+/// // Load a seccomp filter returning `SECCOMP_RET_TRAP`...
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`AlreadyExists`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.AlreadyExists
+pub unsafe fn install_trap_handler(
+    callback: fn(&Trap),
+) -> io::Result<TrapGuard> {
+    extern "C" fn signal_handler(
+        _: c_int,
+        info: *mut libc::siginfo_t,
+        ucontext: *mut c_void,
+    ) {
+        let callback = CALLBACK.load(Ordering::SeqCst);
+        if callback.is_null() {
+            return;
+        }
+        let callback: fn(&Trap) = unsafe { mem::transmute(callback) };
+
+        let info = unsafe { &*(info as *const SigsysInfo) };
+        let seccomp = info.code == SYS_SECCOMP;
+
+        callback(&Trap {
+            seccomp,
+            syscall: if seccomp { info.syscall } else { -1 },
+            arch: if seccomp { info.arch } else { 0 },
+            call_addr: if seccomp {
+                info.call_addr
+            } else {
+                ptr::null_mut()
+            },
+            ucontext,
+        });
+    }
+
+    let _lock = LOCK.lock().unwrap_or_else(|error| error.into_inner());
+
+    if !CALLBACK.load(Ordering::SeqCst).is_null() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "a trap handler is already installed",
+        ));
+    }
+
+    let mut action: libc::sigaction = mem::zeroed();
+    action.sa_sigaction = signal_handler as *const () as usize;
+    action.sa_flags = libc::SA_SIGINFO;
+    libc::sigemptyset(&mut action.sa_mask);
+
+    CALLBACK.store(callback as *mut (), Ordering::SeqCst);
+
+    let mut previous: libc::sigaction = mem::zeroed();
+    if libc::sigaction(libc::SIGSYS, &action, &mut previous) != 0 {
+        CALLBACK.store(ptr::null_mut(), Ordering::SeqCst);
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(TrapGuard { previous })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::{sync::atomic::AtomicI32, thread};
+
+    #[test]
+    fn traps_syscall() {
+        static DENIED: AtomicI32 = AtomicI32::new(-1);
+
+        fn record(trap: &Trap) {
+            if trap.is_seccomp() {
+                DENIED.store(trap.syscall(), Ordering::SeqCst);
+            }
+        }
+
+        let guard = unsafe { install_trap_handler(record) }.unwrap();
+        assert!(unsafe { install_trap_handler(record) }.is_err());
+
+        let denied = libc::SYS_getpgid as u32;
+
+        // Filters apply to the thread that loads them, so load it in another
+        // thread that exits afterwards.
+        thread::spawn(move || unsafe {
+            let mut filter = [
+                libc::sock_filter {
+                    code: (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16,
+                    jt: 0,
+                    jf: 0,
+                    k: 0, // offsetof(struct seccomp_data, nr)
+                },
+                libc::sock_filter {
+                    code: (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16,
+                    jt: 0,
+                    jf: 1,
+                    k: denied,
+                },
+                libc::sock_filter {
+                    code: (libc::BPF_RET | libc::BPF_K) as u16,
+                    jt: 0,
+                    jf: 0,
+                    k: libc::SECCOMP_RET_TRAP,
+                },
+                libc::sock_filter {
+                    code: (libc::BPF_RET | libc::BPF_K) as u16,
+                    jt: 0,
+                    jf: 0,
+                    k: libc::SECCOMP_RET_ALLOW,
+                },
+            ];
+            let program = libc::sock_fprog {
+                len: filter.len() as _,
+                filter: filter.as_mut_ptr(),
+            };
+
+            assert_eq!(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0), 0);
+            assert_eq!(
+                libc::prctl(
+                    libc::PR_SET_SECCOMP,
+                    libc::SECCOMP_MODE_FILTER,
+                    &program as *const libc::sock_fprog,
+                ),
+                0
+            );
+
+            libc::syscall(libc::SYS_getpgid, 0);
+        })
+        .join()
+        .unwrap();
+
+        drop(guard);
+        assert_eq!(DENIED.load(Ordering::SeqCst), denied as c_int);
+    }
+}
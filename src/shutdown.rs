@@ -0,0 +1,388 @@
+//! Graceful shutdown with escalation.
+//!
+//! This implements the common "press `CTRL` + `C` twice to force quit" pattern:
+//! the first termination signal starts a graceful shutdown, and a second one
+//! terminates the process immediately.
+//!
+//! Which signals mean "shut down" can be configured for the whole process via
+//! [`set_default_shutdown`], so that embedders can e.g. add [`Hangup`] without
+//! threading a set through every call site.
+//!
+//! CLI tools whose output is piped into e.g. `head` can opt into treating a
+//! closed pipe as a shutdown request via [`set_shutdown_on_broken_pipe`].
+//!
+//! [`set_default_shutdown`]: fn.set_default_shutdown.html
+//! [`set_shutdown_on_broken_pipe`]: fn.set_shutdown_on_broken_pipe.html
+//! [`Hangup`]: ../signal/enum.Signal.html#variant.Hangup
+
+use std::{
+    future::Future,
+    io, mem,
+    pin::Pin,
+    ptr,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    once::{
+        signal::{
+            force_exit, ErrorPolicy, ForceExit, RegisterOnceError,
+            SignalSetOnce,
+        },
+        CancelSafe,
+    },
+    signal::AtomicSignalSet,
+    Signal, SignalSet,
+};
+
+/// The signals used by [`Shutdown::register`] unless configured otherwise.
+const DEFAULT: SignalSet = SignalSet::new()
+    .with(Signal::Interrupt)
+    .with(Signal::Quit)
+    .with(Signal::Terminate);
+
+static DEFAULT_SHUTDOWN: AtomicSignalSet =
+    AtomicSignalSet::from_signal_set(DEFAULT);
+
+/// Whether `DEFAULT_SHUTDOWN` has been configured.
+static CONFIGURED: AtomicBool = AtomicBool::new(false);
+
+/// Whether `Shutdown` is also fulfilled upon a broken pipe.
+static BROKEN_PIPE: AtomicBool = AtomicBool::new(false);
+
+/// Sets the signals that mean "shut down" for the whole process.
+///
+/// This can only be done once, ideally at startup before any shutdown handler
+/// is registered. Handlers registered earlier keep using the previous set.
+/// If already configured, the current set is returned as the error.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::Signal;
+///
+/// let signals = asygnal::default_shutdown().with(Signal::Hangup);
+/// asygnal::set_default_shutdown(signals).unwrap();
+/// ```
+pub fn set_default_shutdown(signals: SignalSet) -> Result<(), SignalSet> {
+    if CONFIGURED
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err(default_shutdown());
+    }
+    DEFAULT_SHUTDOWN.store(signals, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Returns the signals that mean "shut down" for the whole process.
+///
+/// Unless configured via [`set_default_shutdown`], these are [`Interrupt`],
+/// [`Quit`], and [`Terminate`].
+///
+/// [`set_default_shutdown`]: fn.set_default_shutdown.html
+/// [`Interrupt`]: ../signal/enum.Signal.html#variant.Interrupt
+/// [`Quit`]:      ../signal/enum.Signal.html#variant.Quit
+/// [`Terminate`]: ../signal/enum.Signal.html#variant.Terminate
+#[inline]
+pub fn default_shutdown() -> SignalSet {
+    DEFAULT_SHUTDOWN.load(Ordering::SeqCst)
+}
+
+/// Sets whether handlers registered afterward via [`Shutdown`] are also
+/// fulfilled upon writing to a closed pipe, such as when standard output is
+/// piped into `head`.
+///
+/// Enabling this ignores [`Pipe`] unless it is already handled, so that such
+/// writes fail with `EPIPE` instead of terminating the process. [`Shutdown`]
+/// then handles [`Pipe`] as well and resolves with it, which lets the program
+/// exit cleanly rather than dying mid-write or looping on the error. Unlike
+/// termination signals, repeated broken pipes never force the process to exit.
+///
+/// Disabling this does not stop ignoring [`Pipe`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{shutdown::Shutdown, Signal};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// asygnal::shutdown::set_shutdown_on_broken_pipe(true)?;
+///
+/// if Shutdown::register()?.await == Some(Signal::Pipe) {
+///     // Standard output was closed, so there's nobody left to write to.
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Shutdown`]: struct.Shutdown.html
+/// [`Pipe`]:     ../signal/enum.Signal.html#variant.Pipe
+pub fn set_shutdown_on_broken_pipe(enabled: bool) -> io::Result<()> {
+    if enabled {
+        ignore_default(Signal::Pipe)?;
+    }
+    BROKEN_PIPE.store(enabled, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Ignores `signal` if its action is the default one.
+fn ignore_default(signal: Signal) -> io::Result<()> {
+    let raw_signal = signal.into_raw();
+
+    let mut action: libc::sigaction = unsafe { mem::zeroed() };
+    if unsafe { libc::sigaction(raw_signal, ptr::null(), &mut action) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if action.sa_sigaction != libc::SIG_DFL {
+        return Ok(());
+    }
+
+    action.sa_sigaction = libc::SIG_IGN;
+    match unsafe { libc::sigaction(raw_signal, &action, ptr::null_mut()) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// A future that is fulfilled upon the first termination signal, after which
+/// receiving another one terminates the process.
+///
+/// Escalation happens by restoring the default action of the signal and
+/// raising it again, so that the exit status reflects the signal. It is also
+/// triggered if the process is still running once the [timeout] elapses after
+/// the first signal.
+///
+/// The future resolves with the signal that was received, or with `None` if
+/// an internal error fulfilled it under [`ErrorPolicy::ResolveWithError`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::shutdown::Shutdown;
+/// use std::time::Duration;
+///
+/// # async fn example() -> Result<(), asygnal::once::signal::RegisterOnceError> {
+/// let shutdown = Shutdown::register()?.with_timeout(Duration::from_secs(10));
+///
+/// if let Some(signal) = shutdown.await {
+///     println!("received {:?}, shutting down gracefully", signal);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [timeout]: #method.with_timeout
+/// [`ErrorPolicy::ResolveWithError`]: ../once/signal/enum.ErrorPolicy.html#variant.ResolveWithError
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Shutdown {
+    once: SignalSetOnce,
+    timeout: Option<Duration>,
+    escalated: bool,
+}
+
+impl Future for Shutdown {
+    type Output = Option<Signal>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let signal = match Pin::new(&mut self.once).poll(cx) {
+            Poll::Ready(signal) => signal,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        if !self.escalated {
+            self.escalated = true;
+            if let (Some(signal), Some(timeout)) = (signal, self.timeout) {
+                spawn_timeout(signal, timeout);
+            }
+        }
+
+        Poll::Ready(signal)
+    }
+}
+
+// SAFETY: This only wraps `SignalSetOnce`, and escalation is set up in the
+// same poll that returns the signal.
+unsafe impl CancelSafe for Shutdown {}
+
+impl Shutdown {
+    /// Registers a handler for the [default shutdown signals], which are
+    /// [`interrupt`], [`quit`], and [`terminate`] unless configured otherwise.
+    ///
+    /// Like [`ForceExit`], other signals in [`SignalSet::termination`] such
+    /// as [`hangup`] are left out by default, since they are commonly handled
+    /// repeatedly.
+    ///
+    /// [default shutdown signals]: fn.default_shutdown.html
+    /// [`interrupt`]: ../signal/struct.SignalSet.html#method.interrupt
+    /// [`quit`]:      ../signal/struct.SignalSet.html#method.quit
+    /// [`terminate`]: ../signal/struct.SignalSet.html#method.terminate
+    /// [`hangup`]:    ../signal/struct.SignalSet.html#method.hangup
+    /// [`ForceExit`]: ../once/signal/struct.ForceExit.html
+    /// [`SignalSet::termination`]: ../signal/struct.SignalSet.html#method.termination
+    #[inline]
+    pub fn register() -> Result<Self, RegisterOnceError> {
+        Self::register_set(default_shutdown())
+    }
+
+    /// Registers a handler for `signals`.
+    ///
+    /// Unless a [`ForceExit`] policy is already installed, this installs
+    /// [`ForceExit::immediately`] for `signals` as the process-wide policy.
+    /// Otherwise, the installed policy decides how a second signal escalates.
+    ///
+    /// [`Pipe`] is handled as well if enabled via
+    /// [`set_shutdown_on_broken_pipe`].
+    ///
+    /// [`ForceExit`]: ../once/signal/struct.ForceExit.html
+    /// [`ForceExit::immediately`]: ../once/signal/struct.ForceExit.html#method.immediately
+    /// [`Pipe`]: ../signal/enum.Signal.html#variant.Pipe
+    /// [`set_shutdown_on_broken_pipe`]: fn.set_shutdown_on_broken_pipe.html
+    pub fn register_set(signals: SignalSet) -> Result<Self, RegisterOnceError> {
+        let once = if BROKEN_PIPE.load(Ordering::SeqCst) {
+            SignalSetOnce::register(signals.with(Signal::Pipe))?
+        } else {
+            SignalSetOnce::register(signals)?
+        };
+        ForceExit::immediately()
+            .with_signals(signals)
+            .install_if_absent()?;
+
+        Ok(Self {
+            once,
+            timeout: None,
+            escalated: false,
+        })
+    }
+
+    /// Returns `self` with the process terminated if it is still running once
+    /// `timeout` elapses after the first signal.
+    ///
+    /// This spawns a thread upon receiving the first signal.
+    #[inline]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the signals that `self` is registered for.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.once.signals()
+    }
+
+    /// Returns `self` with `policy` used for handling internal errors instead
+    /// of the [global policy](../once/signal/enum.ErrorPolicy.html#method.global).
+    #[inline]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.once = self.once.with_error_policy(policy);
+        self
+    }
+}
+
+/// Terminates the process with `signal` once `timeout` elapses.
+pub(crate) fn spawn_timeout(signal: Signal, timeout: Duration) {
+    let spawned = thread::Builder::new()
+        .name("asygnal-shutdown".into())
+        .spawn(move || {
+            thread::sleep(timeout);
+            force_exit::terminate(signal);
+        });
+
+    // Without the thread, the process can still be terminated by sending
+    // another signal.
+    drop(spawned);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn set_default_once() {
+        assert_eq!(default_shutdown(), DEFAULT);
+
+        let signals = DEFAULT.with(Signal::Hangup);
+        set_default_shutdown(signals).unwrap();
+        assert_eq!(default_shutdown(), signals);
+        assert_eq!(set_default_shutdown(DEFAULT), Err(signals));
+    }
+
+    #[test]
+    fn keeps_installed_policy() {
+        let signal = Signal::Hangup;
+        let policy = SignalSet::from(Signal::Interrupt);
+
+        // The installed policy applies to the whole process.
+        test_util::run_exclusive(|| {
+            ForceExit::immediately()
+                .with_signals(policy)
+                .install()
+                .unwrap();
+
+            let mut shutdown = Shutdown::register_set(signal.into()).unwrap();
+            assert_eq!(force_exit::installed(), policy);
+            assert!(test_util::poll(&mut shutdown).is_pending());
+
+            signal.raise().unwrap();
+            assert_eq!(
+                test_util::poll(&mut shutdown),
+                Poll::Ready(Some(signal))
+            );
+            drop(shutdown);
+
+            // Registering again waits for the next delivery, which doesn't
+            // escalate since the installed policy ignores it.
+            let mut shutdown = Shutdown::register_set(signal.into()).unwrap();
+            assert!(test_util::poll(&mut shutdown).is_pending());
+
+            signal.raise().unwrap();
+            assert_eq!(
+                test_util::poll(&mut shutdown),
+                Poll::Ready(Some(signal))
+            );
+
+            ForceExit::uninstall();
+        });
+    }
+
+    #[test]
+    fn broken_pipe() {
+        test_util::run_exclusive(|| {
+            set_shutdown_on_broken_pipe(true).unwrap();
+            let mut shutdown =
+                Shutdown::register_set(Signal::Hangup.into()).unwrap();
+            set_shutdown_on_broken_pipe(false).unwrap();
+
+            // Repeated broken pipes must not escalate.
+            assert!(!force_exit::installed().contains(Signal::Pipe));
+            assert!(test_util::poll(&mut shutdown).is_pending());
+
+            let mut fds = [0; 2];
+            assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+            let [reader, writer] = fds;
+            unsafe { libc::close(reader) };
+
+            let buf = [0u8];
+            let n = unsafe { libc::write(writer, buf.as_ptr() as *const _, 1) };
+            assert_eq!(n, -1);
+            assert_eq!(
+                io::Error::last_os_error().raw_os_error(),
+                Some(libc::EPIPE)
+            );
+            unsafe { libc::close(writer) };
+
+            assert_eq!(
+                test_util::poll(&mut shutdown),
+                Poll::Ready(Some(Signal::Pipe))
+            );
+
+            ForceExit::uninstall();
+        });
+    }
+}
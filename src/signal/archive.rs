@@ -0,0 +1,238 @@
+//! [`rkyv`](https://docs.rs/rkyv) support.
+//!
+//! Archived values use the target-independent layout of
+//! [`Signal::stable_bit`] and [`SignalSet::bits`], so that archives can be
+//! shared between targets. Validation rejects values that do not correspond to
+//! a signal supported by the current target. Store
+//! [`SignalSet::LAYOUT_VERSION`] alongside archives to detect layout changes.
+//!
+//! [`Signal::stable_bit`]: enum.Signal.html#method.stable_bit
+//! [`SignalSet::bits`]:    struct.SignalSet.html#method.bits
+//! [`SignalSet::LAYOUT_VERSION`]: struct.SignalSet.html#associatedconstant.LAYOUT_VERSION
+
+use std::fmt;
+
+use rkyv::{
+    bytecheck::CheckBytes,
+    place::Place,
+    rancor::{fail, Fallible, Source},
+    rend::u32_le,
+    traits::NoUndef,
+    Archive, Deserialize, Portable, Serialize,
+};
+
+use super::{Signal, SignalSet};
+
+/// An archived [`Signal`], stored as its [`stable_bit`].
+///
+/// [`Signal`]:     enum.Signal.html
+/// [`stable_bit`]: enum.Signal.html#method.stable_bit
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct ArchivedSignal(u8);
+
+/// An archived [`SignalSet`], stored as its [`bits`].
+///
+/// [`SignalSet`]: struct.SignalSet.html
+/// [`bits`]:      struct.SignalSet.html#method.bits
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct ArchivedSignalSet(u32_le);
+
+// SAFETY: Both types are `repr(transparent)` over types with a fixed layout
+// across targets and endianness.
+unsafe impl Portable for ArchivedSignal {}
+unsafe impl Portable for ArchivedSignalSet {}
+
+// SAFETY: Neither type has padding bytes.
+unsafe impl NoUndef for ArchivedSignal {}
+unsafe impl NoUndef for ArchivedSignalSet {}
+
+impl ArchivedSignal {
+    /// Returns the signal, or `None` if it's not supported by this target.
+    #[inline]
+    pub const fn get(self) -> Option<Signal> {
+        if self.0 as u32 >= u32::BITS {
+            return None;
+        }
+        match SignalSet::from_bits(1 << self.0) {
+            Some(signals) => signals.first(),
+            None => None,
+        }
+    }
+}
+
+impl ArchivedSignalSet {
+    /// Returns the signal set, ignoring signals unsupported by this target.
+    #[inline]
+    pub const fn get(self) -> SignalSet {
+        SignalSet::from_bits_truncate(self.0.to_native())
+    }
+}
+
+impl PartialEq<Signal> for ArchivedSignal {
+    #[inline]
+    fn eq(&self, other: &Signal) -> bool {
+        self.0 as u32 == other.stable_bit()
+    }
+}
+
+impl PartialEq<SignalSet> for ArchivedSignalSet {
+    #[inline]
+    fn eq(&self, other: &SignalSet) -> bool {
+        self.0.to_native() == other.bits()
+    }
+}
+
+impl Archive for Signal {
+    type Archived = ArchivedSignal;
+    type Resolver = ();
+
+    #[inline]
+    fn resolve(&self, _: (), out: Place<ArchivedSignal>) {
+        out.write(ArchivedSignal(self.stable_bit() as u8));
+    }
+}
+
+impl Archive for SignalSet {
+    type Archived = ArchivedSignalSet;
+    type Resolver = ();
+
+    #[inline]
+    fn resolve(&self, _: (), out: Place<ArchivedSignalSet>) {
+        out.write(ArchivedSignalSet(u32_le::from_native(self.bits())));
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for Signal {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<(), S::Error> {
+        Ok(())
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for SignalSet {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<(), S::Error> {
+        Ok(())
+    }
+}
+
+impl<D> Deserialize<Signal, D> for ArchivedSignal
+where
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    #[inline]
+    fn deserialize(&self, _: &mut D) -> Result<Signal, D::Error> {
+        match self.get() {
+            Some(signal) => Ok(signal),
+            None => fail!(InvalidSignal(self.0 as u32)),
+        }
+    }
+}
+
+impl<D> Deserialize<SignalSet, D> for ArchivedSignalSet
+where
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    #[inline]
+    fn deserialize(&self, _: &mut D) -> Result<SignalSet, D::Error> {
+        let bits = self.0.to_native();
+        match SignalSet::from_bits(bits) {
+            Some(signals) => Ok(signals),
+            None => fail!(InvalidSignal(bits)),
+        }
+    }
+}
+
+// SAFETY: `check_bytes` only returns `Ok` if the value is a supported signal.
+unsafe impl<C> CheckBytes<C> for ArchivedSignal
+where
+    C: Fallible + ?Sized,
+    C::Error: Source,
+{
+    #[inline]
+    unsafe fn check_bytes(
+        value: *const Self,
+        _: &mut C,
+    ) -> Result<(), C::Error> {
+        let archived = *value;
+        if archived.get().is_none() {
+            fail!(InvalidSignal(archived.0 as u32));
+        }
+        Ok(())
+    }
+}
+
+// SAFETY: `check_bytes` only returns `Ok` if every bit is a supported signal.
+unsafe impl<C> CheckBytes<C> for ArchivedSignalSet
+where
+    C: Fallible + ?Sized,
+    C::Error: Source,
+{
+    #[inline]
+    unsafe fn check_bytes(
+        value: *const Self,
+        _: &mut C,
+    ) -> Result<(), C::Error> {
+        let bits = (*value).0.to_native();
+        if SignalSet::from_bits(bits).is_none() {
+            fail!(InvalidSignal(bits));
+        }
+        Ok(())
+    }
+}
+
+/// The error for archived bits that do not correspond to supported signals.
+#[derive(Debug)]
+struct InvalidSignal(u32);
+
+impl fmt::Display for InvalidSignal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unsupported signal bits: {:#x}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidSignal {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rkyv::rancor::Error;
+
+    #[test]
+    fn round_trip() {
+        let set = SignalSet::termination();
+        let bytes = rkyv::to_bytes::<Error>(&set).unwrap();
+        let archived =
+            rkyv::access::<ArchivedSignalSet, Error>(&bytes).unwrap();
+        assert_eq!(*archived, set);
+        assert_eq!(bytes[..], set.bits().to_le_bytes());
+        assert_eq!(
+            rkyv::deserialize::<SignalSet, Error>(archived).unwrap(),
+            set
+        );
+
+        for signal in Signal::all() {
+            let bytes = rkyv::to_bytes::<Error>(&signal).unwrap();
+            let archived =
+                rkyv::access::<ArchivedSignal, Error>(&bytes).unwrap();
+            assert_eq!(*archived, signal);
+            assert_eq!(bytes[..], [signal.stable_bit() as u8]);
+            assert_eq!(
+                rkyv::deserialize::<Signal, Error>(archived).unwrap(),
+                signal
+            );
+        }
+    }
+
+    #[test]
+    fn reject_invalid() {
+        let mut bytes = rkyv::util::AlignedVec::<4>::new();
+        bytes.extend_from_slice(&[!0u8; 4]);
+        assert!(rkyv::access::<ArchivedSignalSet, Error>(&bytes).is_err());
+        assert!(rkyv::access::<ArchivedSignal, Error>(&bytes[..1]).is_err());
+    }
+}
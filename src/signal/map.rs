@@ -0,0 +1,195 @@
+use super::{Signal, SignalSet};
+
+/// A table mapping possibly overlapping [`SignalSet`] keys to values, in
+/// insertion order.
+///
+/// Looking up a [`Signal`] finds the entries whose key contains it, with
+/// earlier entries taking precedence in [`first`]. This suits policy tables
+/// such as signal-to-action configurations loaded from files.
+///
+/// # Examples
+///
+/// ```
+/// use asygnal::signal::{Signal, SignalSet, SignalSetMap};
+///
+/// let mut actions = SignalSetMap::new();
+/// actions.insert(Signal::Hangup, "reload");
+/// actions.insert(SignalSet::termination(), "shutdown");
+///
+/// assert_eq!(actions.first(Signal::Hangup), Some(&"reload"));
+/// assert_eq!(actions.first(Signal::Terminate), Some(&"shutdown"));
+///
+/// let all: Vec<_> = actions.matches(Signal::Hangup).collect();
+/// assert_eq!(all, [&"reload", &"shutdown"]);
+/// ```
+///
+/// [`Signal`]:    enum.Signal.html
+/// [`SignalSet`]: struct.SignalSet.html
+/// [`first`]:     #method.first
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SignalSetMap<V> {
+    entries: Vec<(SignalSet, V)>,
+}
+
+impl<V> Default for SignalSetMap<V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, S: Into<SignalSet>> FromIterator<(S, V)> for SignalSetMap<V> {
+    fn from_iter<I: IntoIterator<Item = (S, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<V, S: Into<SignalSet>> Extend<(S, V)> for SignalSetMap<V> {
+    fn extend<I: IntoIterator<Item = (S, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<V> IntoIterator for SignalSetMap<V> {
+    type Item = (SignalSet, V);
+    type IntoIter = std::vec::IntoIter<(SignalSet, V)>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<V> SignalSetMap<V> {
+    /// Creates a new, empty map.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Maps `key` to `value`, returning the previous value if `key` was
+    /// already in the map.
+    ///
+    /// A new key is placed after every existing entry, and so takes the lowest
+    /// precedence. Replacing a value keeps its key's position.
+    pub fn insert<S: Into<SignalSet>>(
+        &mut self,
+        key: S,
+        value: V,
+    ) -> Option<V> {
+        let key = key.into();
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, old)) => Some(std::mem::replace(old, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Removes `key` from the map, returning its value if it was present.
+    pub fn remove<S: Into<SignalSet>>(&mut self, key: S) -> Option<V> {
+        let key = key.into();
+        let index = self.entries.iter().position(|(k, _)| *k == key)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Returns the value of the exact `key`, if present.
+    pub fn get<S: Into<SignalSet>>(&self, key: S) -> Option<&V> {
+        let key = key.into();
+        self.entries
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the value of the first entry whose key contains `signal`.
+    pub fn first(&self, signal: Signal) -> Option<&V> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key.contains(signal))
+            .map(|(_, value)| value)
+    }
+
+    /// Returns a mutable reference to the value of the first entry whose key
+    /// contains `signal`.
+    pub fn first_mut(&mut self, signal: Signal) -> Option<&mut V> {
+        self.entries
+            .iter_mut()
+            .find(|(key, _)| key.contains(signal))
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the values of every entry whose key contains `signal`, in
+    /// order of precedence.
+    pub fn matches(&self, signal: Signal) -> impl Iterator<Item = &V> + '_ {
+        self.entries
+            .iter()
+            .filter(move |(key, _)| key.contains(signal))
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the union of every key, which is the set of signals that have
+    /// a match.
+    pub fn signals(&self) -> SignalSet {
+        self.entries
+            .iter()
+            .fold(SignalSet::new(), |signals, (key, _)| signals.union(*key))
+    }
+
+    /// Returns every entry in order of precedence.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (SignalSet, &V)> + '_ {
+        self.entries.iter().map(|(key, value)| (*key, value))
+    }
+
+    /// Returns the number of entries.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes every entry.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precedence() {
+        let mut map: SignalSetMap<u8> = [
+            (SignalSet::new().interrupt(), 1),
+            (SignalSet::termination(), 2),
+        ]
+        .into_iter()
+        .collect();
+
+        // Replacing keeps the position.
+        assert_eq!(map.insert(Signal::Interrupt, 3), Some(1));
+        assert_eq!(map.first(Signal::Interrupt), Some(&3));
+        assert_eq!(map.get(SignalSet::termination()), Some(&2));
+        assert_eq!(map.signals(), SignalSet::termination());
+
+        assert_eq!(map.remove(Signal::Interrupt), Some(3));
+        assert_eq!(map.first(Signal::Interrupt), Some(&2));
+        assert_eq!(map.matches(Signal::Interrupt).count(), 1);
+        assert_eq!(map.first(Signal::Abort), None);
+    }
+}
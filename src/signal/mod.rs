@@ -25,14 +25,41 @@
 
 #![cfg_attr(not(unix), allow(warnings))]
 
+mod map;
+mod parse;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod realtime;
+mod send;
 mod set;
 
+#[cfg(feature = "rkyv")]
+mod archive;
+#[cfg(feature = "serde")]
+mod serde;
+
 // Declare this after `set` so that `SignalSet` methods inside can come after
 // the initial `impl`.
+#[allow(clippy::module_inception)]
 mod signal;
 
+#[cfg(feature = "rkyv")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rkyv")))]
+pub use archive::{ArchivedSignal, ArchivedSignalSet};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(target_os = "linux", target_os = "android")))
+)]
+pub use realtime::{RealtimeSignal, RealtimeSignalSet};
+
+#[cfg(unix)]
+pub use set::BlockedGuard;
+
 pub use {
-    set::{AtomicSignalSet, SignalSet, SignalSetIter},
+    map::SignalSetMap,
+    parse::ParseSignalError,
+    set::{AtomicSignalSet, SignalSet, SignalSetIter, SignalSetRefIter},
     signal::Signal,
 };
 
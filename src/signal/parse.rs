@@ -0,0 +1,94 @@
+use std::{fmt, str::FromStr};
+
+use super::{Signal, SignalSet};
+
+/// The error returned when parsing a [`Signal`] or [`SignalSet`] from an
+/// unknown name.
+///
+/// [`Signal`]:    enum.Signal.html
+/// [`SignalSet`]: struct.SignalSet.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseSignalError {
+    name: String,
+}
+
+impl fmt::Display for ParseSignalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown signal name: {:?}", self.name)
+    }
+}
+
+impl std::error::Error for ParseSignalError {}
+
+impl ParseSignalError {
+    /// Returns the name that was not recognized.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Parses a name accepted by [`Signal::from_name`].
+///
+/// [`Signal::from_name`]: enum.Signal.html#method.from_name
+impl FromStr for Signal {
+    type Err = ParseSignalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Signal::from_name(s).ok_or_else(|| ParseSignalError { name: s.into() })
+    }
+}
+
+/// Parses a comma-separated list of names accepted by [`Signal::from_name`],
+/// such as `"SIGINT, TERM"`.
+///
+/// Whitespace around each name is ignored. An empty or blank string parses as
+/// the empty set.
+///
+/// [`Signal::from_name`]: enum.Signal.html#method.from_name
+impl FromStr for SignalSet {
+    type Err = ParseSignalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Ok(SignalSet::new());
+        }
+        s.split(',')
+            .map(|name| name.trim().parse::<Signal>())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for signal in Signal::all() {
+            let name = signal.name();
+            assert_eq!(name.parse(), Ok(signal));
+            assert_eq!(name.to_lowercase().parse(), Ok(signal));
+            assert_eq!(name[3..].parse(), Ok(signal));
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for name in ["", "SIG", "sig", "SIGKILL", " SIGINT", "SIGINTX"] {
+            let error = name.parse::<Signal>().unwrap_err();
+            assert_eq!(error.name(), name);
+        }
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!("".parse(), Ok(SignalSet::new()));
+        assert_eq!(
+            " SIGINT,term ".parse(),
+            Ok(SignalSet::new().interrupt().terminate())
+        );
+        assert!("SIGINT,".parse::<SignalSet>().is_err());
+        assert_eq!("INT,KILL".parse::<SignalSet>().unwrap_err().name(), "KILL");
+    }
+}
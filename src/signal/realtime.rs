@@ -0,0 +1,210 @@
+use std::{fmt, io};
+
+/// A realtime signal in `SIGRTMIN..=SIGRTMAX`, identified by its offset from
+/// `SIGRTMIN`.
+///
+/// Unlike [`Signal`], realtime signals are queued rather than coalesced, so
+/// each one sent is received, and may carry a `sigval` payload when sent via
+/// [`queue`]. Their range is only known at runtime, since the C library may
+/// reserve some for itself.
+///
+/// These are registered separately from [`SignalSet`], via
+/// [`RealtimeSignalSet`].
+///
+/// [`Signal`]:            enum.Signal.html
+/// [`SignalSet`]:         struct.SignalSet.html
+/// [`RealtimeSignalSet`]: struct.RealtimeSignalSet.html
+/// [`queue`]:             #method.queue
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RealtimeSignal(u8);
+
+impl fmt::Display for RealtimeSignal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            0 => f.write_str("SIGRTMIN"),
+            offset => write!(f, "SIGRTMIN+{}", offset),
+        }
+    }
+}
+
+impl RealtimeSignal {
+    /// The maximum number of realtime signals supported by this type.
+    pub const MAX_COUNT: usize = 64;
+
+    /// Returns the number of realtime signals available at runtime.
+    #[inline]
+    pub fn count() -> usize {
+        let count = libc::SIGRTMAX() - libc::SIGRTMIN() + 1;
+        (count.max(0) as usize).min(Self::MAX_COUNT)
+    }
+
+    /// Returns the signal at `offset` from `SIGRTMIN`, if it is available.
+    #[inline]
+    pub fn new(offset: u8) -> Option<Self> {
+        if (offset as usize) < Self::count() {
+            Some(Self(offset))
+        } else {
+            None
+        }
+    }
+
+    /// Returns `SIGRTMIN`.
+    #[inline]
+    pub fn min() -> Self {
+        Self(0)
+    }
+
+    /// Returns `SIGRTMAX`.
+    #[inline]
+    pub fn max() -> Self {
+        Self(Self::count().saturating_sub(1) as u8)
+    }
+
+    /// Returns the offset of `self` from `SIGRTMIN`.
+    #[inline]
+    pub const fn offset(self) -> u8 {
+        self.0
+    }
+
+    /// Converts a raw signal number into a realtime signal, if it is one.
+    #[inline]
+    pub fn from_raw(raw_signal: libc::c_int) -> Option<Self> {
+        let offset = raw_signal.checked_sub(libc::SIGRTMIN())?;
+        Self::new(offset.try_into().ok()?)
+    }
+
+    /// Returns the raw signal number.
+    #[inline]
+    pub fn into_raw(self) -> libc::c_int {
+        libc::SIGRTMIN() + self.0 as libc::c_int
+    }
+
+    /// Sends `self` to the process `pid` with `value` as its payload, via
+    /// `sigqueue`.
+    ///
+    /// Without a handler, realtime signals terminate the receiving process.
+    pub fn queue(self, pid: libc::pid_t, value: usize) -> io::Result<()> {
+        let value = libc::sigval {
+            sival_ptr: value as *mut libc::c_void,
+        };
+        match unsafe { libc::sigqueue(pid, self.into_raw(), value) } {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+}
+
+/// A set of [`RealtimeSignal`]s.
+///
+/// [`RealtimeSignal`]: struct.RealtimeSignal.html
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RealtimeSignalSet(pub(crate) u64);
+
+impl fmt::Debug for RealtimeSignalSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl From<RealtimeSignal> for RealtimeSignalSet {
+    #[inline]
+    fn from(signal: RealtimeSignal) -> Self {
+        Self::new().with(signal)
+    }
+}
+
+impl FromIterator<RealtimeSignal> for RealtimeSignalSet {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = RealtimeSignal>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::new(), Self::with)
+    }
+}
+
+impl RealtimeSignalSet {
+    /// Creates a new, empty set.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Returns a set of every realtime signal available at runtime.
+    #[inline]
+    pub fn all() -> Self {
+        match RealtimeSignal::count() {
+            RealtimeSignal::MAX_COUNT => Self(!0),
+            count => Self((1 << count) - 1),
+        }
+    }
+
+    /// Returns `self` with `signal` added.
+    #[inline]
+    #[must_use]
+    pub const fn with(self, signal: RealtimeSignal) -> Self {
+        Self(self.0 | (1 << signal.0))
+    }
+
+    /// Returns `self` with `signal` removed.
+    #[inline]
+    #[must_use]
+    pub const fn without(self, signal: RealtimeSignal) -> Self {
+        Self(self.0 & !(1 << signal.0))
+    }
+
+    /// Inserts `signal` into `self`.
+    #[inline]
+    pub fn insert(&mut self, signal: RealtimeSignal) {
+        *self = self.with(signal);
+    }
+
+    /// Removes `signal` from `self`.
+    #[inline]
+    pub fn remove(&mut self, signal: RealtimeSignal) {
+        *self = self.without(signal);
+    }
+
+    /// Returns whether `self` contains `signal`.
+    #[inline]
+    pub const fn contains(self, signal: RealtimeSignal) -> bool {
+        self.0 & (1 << signal.0) != 0
+    }
+
+    /// Returns the number of signals in `self`.
+    #[inline]
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Returns whether `self` is empty.
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns an iterator over the signals in `self`, in order of offset.
+    #[inline]
+    pub fn iter(self) -> impl Iterator<Item = RealtimeSignal> {
+        (0..RealtimeSignal::MAX_COUNT as u8)
+            .map(RealtimeSignal)
+            .filter(move |&signal| self.contains(signal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_roundtrip() {
+        let count = RealtimeSignal::count();
+        assert!(count > 0);
+
+        let max = RealtimeSignal::max();
+        assert_eq!(max.into_raw(), libc::SIGRTMAX());
+        assert_eq!(RealtimeSignal::from_raw(libc::SIGRTMAX()), Some(max));
+        assert_eq!(RealtimeSignal::new(count as u8), None);
+        assert_eq!(RealtimeSignal::from_raw(libc::SIGINT), None);
+
+        assert_eq!(RealtimeSignalSet::all().len(), count);
+        assert_eq!(RealtimeSignal::min().to_string(), "SIGRTMIN");
+    }
+}
@@ -0,0 +1,152 @@
+use std::io;
+
+use super::Signal;
+
+#[cfg(unix)]
+fn kill(pid: libc::pid_t, signal: Signal) -> io::Result<()> {
+    if unsafe { libc::kill(pid, signal.into_raw()) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(unix)]
+fn positive(id: libc::pid_t, what: &'static str) -> io::Result<libc::pid_t> {
+    if id > 0 {
+        Ok(id)
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidInput, what))
+    }
+}
+
+/// Sends `CTRL_C_EVENT` to the process group `pgid` sharing the console.
+#[cfg(windows)]
+fn generate_ctrl_c(pgid: u32) -> io::Result<()> {
+    use winapi::um::wincon::{GenerateConsoleCtrlEvent, CTRL_C_EVENT};
+
+    if unsafe { GenerateConsoleCtrlEvent(CTRL_C_EVENT, pgid) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+impl Signal {
+    /// Sends `self` to the current thread, like the C `raise` function.
+    ///
+    /// Note that without a handler, `self` performs its default action, which
+    /// often terminates the process.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asygnal::Signal;
+    ///
+    /// Signal::Terminate.raise()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn raise(self) -> io::Result<()> {
+        if unsafe { libc::raise(self.into_raw()) } == 0 {
+            return Ok(());
+        }
+
+        // The C runtime on Windows reports errors via `errno` rather than
+        // `GetLastError`.
+        #[cfg(unix)]
+        return Err(io::Error::last_os_error());
+
+        #[cfg(not(unix))]
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the signal cannot be raised",
+        ));
+    }
+
+    /// Sends `self` to the process `pid`, via `kill`.
+    ///
+    /// Unlike `kill`, a `pid` that is not positive fails with
+    /// [`InvalidInput`] rather than signaling a group of processes. Use
+    /// [`send_group`] for that.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asygnal::Signal;
+    /// use std::process::Command;
+    ///
+    /// let child = Command::new("worker").spawn()?;
+    /// Signal::Hangup.send(child.id() as libc::pid_t)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    /// [`send_group`]:   #method.send_group
+    #[cfg(any(docsrs, unix))]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn send(self, pid: libc::pid_t) -> io::Result<()> {
+        kill(positive(pid, "process ID must be positive")?, self)
+    }
+
+    /// Sends `self` to every process in the process group `pgid`, via `kill`
+    /// with `-pgid`.
+    ///
+    /// A `pgid` that is not positive fails with [`InvalidInput`]. To signal
+    /// the group of the current process, use
+    /// [`process::signal_own_group`](../process/fn.signal_own_group.html).
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    #[cfg(any(docsrs, unix))]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn send_group(self, pgid: libc::pid_t) -> io::Result<()> {
+        kill(-positive(pgid, "process group ID must be positive")?, self)
+    }
+
+    /// Sends `self` to the process group `pid` sharing the current console, via
+    /// `GenerateConsoleCtrlEvent`.
+    ///
+    /// Only [`Interrupt`] can be sent, as `CTRL_C_EVENT`; other signals fail
+    /// with [`Unsupported`]. Windows only delivers `CTRL_C_EVENT` to every
+    /// process on the console, which `pid` 0 denotes.
+    ///
+    /// [`Interrupt`]:   #variant.Interrupt
+    /// [`Unsupported`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Unsupported
+    #[cfg(windows)]
+    pub fn send(self, pid: u32) -> io::Result<()> {
+        self.send_group(pid)
+    }
+
+    /// Sends `self` to the process group `pgid` sharing the current console,
+    /// via `GenerateConsoleCtrlEvent`.
+    ///
+    /// See [`send`](#method.send) for the restrictions on Windows.
+    #[cfg(windows)]
+    pub fn send_group(self, pgid: u32) -> io::Result<()> {
+        match self {
+            Signal::Interrupt => generate_ctrl_c(pgid),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "only SIGINT can be sent on Windows",
+            )),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send() {
+        let invalid = |result: io::Result<()>| {
+            result.unwrap_err().kind() == io::ErrorKind::InvalidInput
+        };
+        assert!(invalid(Signal::Child.send(0)));
+        assert!(invalid(Signal::Child.send(-1)));
+        assert!(invalid(Signal::Child.send_group(0)));
+
+        // Ignored by default, and tolerated by the other tests.
+        let pid = unsafe { libc::getpid() };
+        Signal::Child.send(pid).unwrap();
+        Signal::Child.raise().unwrap();
+    }
+}
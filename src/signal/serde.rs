@@ -0,0 +1,154 @@
+//! [`serde`](https://docs.rs/serde) support.
+//!
+//! A [`Signal`] is represented by its POSIX name, such as `"SIGTERM"`, and a
+//! [`SignalSet`] by a list of names in order of integer value. When
+//! deserializing, names are accepted in any form supported by
+//! [`Signal::from_name`].
+//!
+//! Unlike the integer representation, names are the same across targets, so
+//! numeric values are rejected to keep configurations portable.
+//!
+//! [`Signal`]:    enum.Signal.html
+//! [`SignalSet`]: struct.SignalSet.html
+//! [`Signal::from_name`]: enum.Signal.html#method.from_name
+
+use std::fmt;
+
+use ::serde::{
+    de::{self, Deserialize, Deserializer, SeqAccess, Unexpected, Visitor},
+    ser::{Serialize, SerializeSeq, Serializer},
+};
+
+use super::{Signal, SignalSet};
+
+const EXPECTING: &str = "a signal name such as \"SIGTERM\"";
+
+const NUMERIC: &str =
+    "signal numbers are not portable, expected a name such as \"SIGTERM\"";
+
+const BITS: &str =
+    "signal set bits are not portable, expected a list of signal names";
+
+impl Serialize for Signal {
+    #[inline]
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl Serialize for SignalSet {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for signal in *self {
+            seq.serialize_element(&signal)?;
+        }
+        seq.end()
+    }
+}
+
+struct SignalVisitor;
+
+impl<'de> Visitor<'de> for SignalVisitor {
+    type Value = Signal;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(EXPECTING)
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<Signal, E> {
+        Signal::from_name(name)
+            .ok_or_else(|| E::invalid_value(Unexpected::Str(name), &self))
+    }
+
+    fn visit_bytes<E: de::Error>(self, name: &[u8]) -> Result<Signal, E> {
+        std::str::from_utf8(name)
+            .ok()
+            .and_then(Signal::from_name)
+            .ok_or_else(|| E::invalid_value(Unexpected::Bytes(name), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<Signal, E> {
+        Err(E::custom(NUMERIC))
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<Signal, E> {
+        Err(E::custom(NUMERIC))
+    }
+}
+
+impl<'de> Deserialize<'de> for Signal {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(SignalVisitor)
+    }
+}
+
+struct SignalSetVisitor;
+
+impl<'de> Visitor<'de> for SignalSetVisitor {
+    type Value = SignalSet;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of signal names")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> Result<SignalSet, A::Error> {
+        let mut set = SignalSet::new();
+        while let Some(signal) = seq.next_element::<Signal>()? {
+            set.insert(signal);
+        }
+        Ok(set)
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<SignalSet, E> {
+        Err(E::custom(BITS))
+    }
+}
+
+impl<'de> Deserialize<'de> for SignalSet {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(SignalSetVisitor)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use ::serde::de::{
+        value::{Error, SeqDeserializer, StrDeserializer, U64Deserializer},
+        IntoDeserializer,
+    };
+
+    #[test]
+    fn deserialize_names() {
+        let signal =
+            |name| Signal::deserialize(StrDeserializer::<Error>::new(name));
+        assert_eq!(signal("SIGTERM"), Ok(Signal::Terminate));
+        assert_eq!(signal("hup"), Ok(Signal::Hangup));
+        assert!(signal("SIGNOPE").is_err());
+
+        let number = U64Deserializer::<Error>::new(15);
+        let error = Signal::deserialize(number).unwrap_err();
+        assert_eq!(error.to_string(), NUMERIC);
+
+        let names = vec!["SIGINT", "TERM", "SIGINT"];
+        let set = SignalSet::deserialize(SeqDeserializer::<_, Error>::new(
+            names.into_iter().map(IntoDeserializer::into_deserializer),
+        ));
+        assert_eq!(set, Ok(SignalSet::new().interrupt().terminate()));
+    }
+}
@@ -1,4 +1,7 @@
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::{
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
 
 use super::SignalSet;
 
@@ -6,6 +9,21 @@ use super::SignalSet;
 /// with atomic operations.
 pub struct AtomicSignalSet(AtomicU32);
 
+impl Default for AtomicSignalSet {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for AtomicSignalSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("AtomicSignalSet")
+            .field(&self.load(Ordering::SeqCst))
+            .finish()
+    }
+}
+
 impl From<SignalSet> for AtomicSignalSet {
     #[inline]
     fn from(signals: SignalSet) -> Self {
@@ -58,4 +76,114 @@ impl AtomicSignalSet {
     ) -> SignalSet {
         SignalSet(self.0.fetch_and(!signals.into().0, ordering))
     }
+
+    /// Atomically toggles `signals` in `self` using `ordering`, returning the
+    /// previous value.
+    #[inline]
+    pub fn toggle<S: Into<SignalSet>>(
+        &self,
+        signals: S,
+        ordering: Ordering,
+    ) -> SignalSet {
+        SignalSet(self.0.fetch_xor(signals.into().0, ordering))
+    }
+
+    /// Atomically clears `self` using `ordering`, returning the previous
+    /// value.
+    ///
+    /// This suits a signal handler recording signals that are later taken in
+    /// batches.
+    #[inline]
+    pub fn take(&self, ordering: Ordering) -> SignalSet {
+        SignalSet(self.0.fetch_and(0, ordering))
+    }
+
+    /// Atomically replaces `self` with `signals` using `ordering`, returning
+    /// the previous value.
+    #[inline]
+    pub fn swap<S: Into<SignalSet>>(
+        &self,
+        signals: S,
+        ordering: Ordering,
+    ) -> SignalSet {
+        SignalSet(self.0.swap(signals.into().0, ordering))
+    }
+
+    /// Atomically stores `new` in `self` if it is equal to `current`.
+    ///
+    /// Returns the previous value, wrapped in `Ok` if it was replaced. This
+    /// has the same semantics as [`AtomicU32::compare_exchange`].
+    ///
+    /// [`AtomicU32::compare_exchange`]: https://doc.rust-lang.org/std/sync/atomic/struct.AtomicU32.html#method.compare_exchange
+    #[inline]
+    pub fn compare_exchange(
+        &self,
+        current: SignalSet,
+        new: SignalSet,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<SignalSet, SignalSet> {
+        self.0
+            .compare_exchange(current.0, new.0, success, failure)
+            .map(SignalSet)
+            .map_err(SignalSet)
+    }
+
+    /// Like [`compare_exchange`](#method.compare_exchange), but may spuriously
+    /// fail, which makes for more efficient code in loops on some platforms.
+    #[inline]
+    pub fn compare_exchange_weak(
+        &self,
+        current: SignalSet,
+        new: SignalSet,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<SignalSet, SignalSet> {
+        self.0
+            .compare_exchange_weak(current.0, new.0, success, failure)
+            .map(SignalSet)
+            .map_err(SignalSet)
+    }
+
+    /// Atomically replaces `self` with the result of `f` until it succeeds or
+    /// `f` returns `None`.
+    ///
+    /// Returns the previous value, wrapped in `Ok` if it was replaced. This
+    /// has the same semantics as [`AtomicU32::fetch_update`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asygnal::signal::{AtomicSignalSet, SignalSet};
+    /// use std::sync::atomic::Ordering;
+    ///
+    /// let set = AtomicSignalSet::from(SignalSet::termination());
+    ///
+    /// // Take the first signal only.
+    /// let previous = set.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |set| {
+    ///     set.first().map(|signal| set.without(signal))
+    /// });
+    ///
+    /// let first = previous.unwrap().first().unwrap();
+    /// assert!(!set.load(Ordering::SeqCst).contains(first));
+    /// ```
+    ///
+    /// [`AtomicU32::fetch_update`]: https://doc.rust-lang.org/std/sync/atomic/struct.AtomicU32.html#method.fetch_update
+    #[inline]
+    pub fn fetch_update<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: F,
+    ) -> Result<SignalSet, SignalSet>
+    where
+        F: FnMut(SignalSet) -> Option<SignalSet>,
+    {
+        self.0
+            .fetch_update(set_order, fetch_order, |bits| {
+                f(SignalSet(bits)).map(|signals| signals.0)
+            })
+            .map(SignalSet)
+            .map_err(SignalSet)
+    }
 }
@@ -0,0 +1,157 @@
+use std::{fmt, io, marker::PhantomData, mem, ptr};
+
+use super::SignalSet;
+
+/// Changes the signal mask of the current thread via `how`, returning the
+/// previous mask.
+fn sigmask(
+    how: libc::c_int,
+    signals: Option<SignalSet>,
+) -> io::Result<libc::sigset_t> {
+    let set = match signals {
+        Some(signals) => {
+            Some(signals.into_raw().ok_or_else(io::Error::last_os_error)?)
+        }
+        None => None,
+    };
+    let set_ptr = set.as_ref().map_or(ptr::null(), |set| set as *const _);
+
+    let mut old = mem::MaybeUninit::<libc::sigset_t>::uninit();
+    match unsafe { libc::pthread_sigmask(how, set_ptr, old.as_mut_ptr()) } {
+        0 => Ok(unsafe { old.assume_init() }),
+        error => Err(io::Error::from_raw_os_error(error)),
+    }
+}
+
+/// Converts `set`, dropping any signals not supported by `Signal`.
+fn from_raw_lossy(set: &libc::sigset_t) -> SignalSet {
+    SignalSet::all()
+        .into_iter()
+        .filter(
+            |signal| unsafe { libc::sigismember(set, signal.into_raw()) } == 1,
+        )
+        .collect()
+}
+
+impl SignalSet {
+    /// Returns the signals blocked on the current thread, via
+    /// `pthread_sigmask`.
+    ///
+    /// Signals not supported by [`Signal`](enum.Signal.html), such as
+    /// realtime signals, are left out.
+    pub fn current_mask() -> io::Result<Self> {
+        sigmask(libc::SIG_BLOCK, None).map(|old| from_raw_lossy(&old))
+    }
+
+    /// Adds `self` to the signals blocked on the current thread, returning
+    /// the previous mask.
+    ///
+    /// Blocked signals stay pending until unblocked. Since threads inherit the
+    /// mask of the thread that spawned them, blocking before spawning worker
+    /// threads keeps signals from being delivered to them.
+    ///
+    /// To unblock automatically, use [`block_scoped`](#method.block_scoped).
+    pub fn block(self) -> io::Result<Self> {
+        sigmask(libc::SIG_BLOCK, Some(self)).map(|old| from_raw_lossy(&old))
+    }
+
+    /// Removes `self` from the signals blocked on the current thread,
+    /// returning the previous mask.
+    ///
+    /// Any of `self` that are pending are delivered before this returns.
+    pub fn unblock(self) -> io::Result<Self> {
+        sigmask(libc::SIG_UNBLOCK, Some(self)).map(|old| from_raw_lossy(&old))
+    }
+
+    /// Replaces the signals blocked on the current thread with `self`,
+    /// returning the previous mask.
+    ///
+    /// Note that this unblocks any signals outside of `self`, including ones
+    /// not supported by [`Signal`](enum.Signal.html).
+    pub fn replace_mask(self) -> io::Result<Self> {
+        sigmask(libc::SIG_SETMASK, Some(self)).map(|old| from_raw_lossy(&old))
+    }
+
+    /// Adds `self` to the signals blocked on the current thread until the
+    /// returned guard is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asygnal::SignalSet;
+    /// use std::thread;
+    ///
+    /// let worker = {
+    ///     let _guard = SignalSet::termination().block_scoped()?;
+    ///     thread::spawn(|| {
+    ///         // Termination signals are never delivered to this thread.
+    ///     })
+    /// };
+    /// # worker.join().unwrap();
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn block_scoped(self) -> io::Result<BlockedGuard> {
+        Ok(BlockedGuard {
+            previous: sigmask(libc::SIG_BLOCK, Some(self))?,
+            _not_send: PhantomData,
+        })
+    }
+}
+
+/// Restores the signal mask of the current thread when dropped.
+///
+/// This is returned by [`SignalSet::block_scoped`]. The full previous mask is
+/// restored, including signals not supported by [`Signal`].
+///
+/// [`Signal`]: enum.Signal.html
+/// [`SignalSet::block_scoped`]: struct.SignalSet.html#method.block_scoped
+#[must_use = "the mask is restored when the guard is dropped"]
+pub struct BlockedGuard {
+    previous: libc::sigset_t,
+    /// The mask is per-thread, so it must be restored on the same thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl fmt::Debug for BlockedGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BlockedGuard")
+            .field("previous", &from_raw_lossy(&self.previous))
+            .finish()
+    }
+}
+
+impl Drop for BlockedGuard {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            libc::pthread_sigmask(
+                libc::SIG_SETMASK,
+                &self.previous,
+                ptr::null_mut(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_scoped() {
+        // The mask is per-thread, so this doesn't affect other tests.
+        let signals = SignalSet::new().user_def_1().tt_out();
+
+        let before = SignalSet::current_mask().unwrap();
+        assert!(!before.contains_any(signals));
+
+        let guard = signals.block_scoped().unwrap();
+        assert!(SignalSet::current_mask().unwrap().contains_all(signals));
+
+        assert!(signals.unblock().unwrap().contains_all(signals));
+        assert_eq!(signals.block().unwrap(), before);
+
+        drop(guard);
+        assert_eq!(SignalSet::current_mask().unwrap(), before);
+    }
+}
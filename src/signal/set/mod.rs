@@ -1,9 +1,14 @@
-use std::{fmt, mem};
+use std::{fmt, iter::FusedIterator, marker::PhantomData, mem};
 
 use super::Signal;
 
 mod atomic;
+#[cfg(unix)]
+mod mask;
+mod ops;
 pub use atomic::*;
+#[cfg(unix)]
+pub use mask::BlockedGuard;
 
 /// Collection of signals supported by this library, backed by a cheap bit mask.
 ///
@@ -12,9 +17,22 @@ pub use atomic::*;
 /// Signals that cannot be handled are not listed as methods. Each method has a
 /// configuration appropriate for what platforms it's supported on. For example,
 /// see [`SignalSet::abort`](#method.abort).
+///
+/// # Bit Layout
+///
+/// The in-memory representation depends on which signals the target supports,
+/// so it must not be persisted or shared between processes built for different
+/// targets or crate versions. Use [`bits`] and [`from_bits`] instead, which
+/// place each signal at its fixed [`Signal::stable_bit`]. Store
+/// [`LAYOUT_VERSION`] alongside persisted bits to detect layout changes.
+///
+/// [`bits`]:               #method.bits
+/// [`from_bits`]:          #method.from_bits
+/// [`LAYOUT_VERSION`]:     #associatedconstant.LAYOUT_VERSION
+/// [`Signal::stable_bit`]: enum.Signal.html#method.stable_bit
 #[derive(Clone, Copy, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(transparent)]
-pub struct SignalSet(u32);
+pub struct SignalSet(pub(crate) u32);
 
 impl From<Signal> for SignalSet {
     #[inline]
@@ -31,9 +49,31 @@ impl From<SignalSetIter> for SignalSet {
 }
 
 impl fmt::Debug for SignalSet {
+    #[cfg(not(feature = "min-size"))]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_set().entries(*self).finish()
     }
+
+    // Avoids pulling in the formatting of each signal.
+    #[cfg(feature = "min-size")]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SignalSet({:#x})", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "defmt")))]
+impl defmt::Format for SignalSet {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{{");
+        for (i, signal) in self.into_iter().enumerate() {
+            if i != 0 {
+                defmt::write!(f, ", ");
+            }
+            defmt::write!(f, "{}", signal);
+        }
+        defmt::write!(f, "}}");
+    }
 }
 
 impl IntoIterator for SignalSet {
@@ -46,6 +86,30 @@ impl IntoIterator for SignalSet {
     }
 }
 
+#[cfg(all(unix, any(docsrs, feature = "once-core")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+impl std::future::IntoFuture for SignalSet {
+    type Output = Option<Signal>;
+    type IntoFuture = crate::once::signal::SignalSetFuture;
+
+    /// Returns a future that registers a handler for `self` upon first being
+    /// polled.
+    #[inline]
+    fn into_future(self) -> Self::IntoFuture {
+        crate::once::signal::SignalSetFuture::new(self)
+    }
+}
+
+impl<'a> IntoIterator for &'a SignalSet {
+    type Item = &'a Signal;
+    type IntoIter = SignalSetRefIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> SignalSetRefIter<'a> {
+        SignalSetRefIter::new(self)
+    }
+}
+
 impl FromIterator<Signal> for SignalSet {
     #[inline]
     fn from_iter<I>(iter: I) -> Self
@@ -78,6 +142,16 @@ impl Extend<Signal> for SignalSet {
     }
 }
 
+impl<'a> Extend<&'a Signal> for SignalSet {
+    #[inline]
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = &'a Signal>,
+    {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
 impl Extend<SignalSet> for SignalSet {
     #[inline]
     fn extend<I>(&mut self, iter: I)
@@ -89,6 +163,40 @@ impl Extend<SignalSet> for SignalSet {
 }
 
 impl SignalSet {
+    /// The empty set, equal to [`new`](#method.new).
+    pub const EMPTY: Self = Self::new();
+
+    /// The set of all supported signals, equal to [`all`](#method.all).
+    pub const ALL: Self = Self::all();
+
+    /// The set of signals that result in process termination, equal to
+    /// [`termination`](#method.termination).
+    ///
+    /// Being a constant, this can be used in patterns and as the argument of
+    /// const generics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asygnal::SignalSet;
+    ///
+    /// fn describe(signals: SignalSet) -> &'static str {
+    ///     match signals {
+    ///         SignalSet::EMPTY => "none",
+    ///         SignalSet::TERMINATION => "shutdown",
+    ///         _ => "other",
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(describe(SignalSet::termination()), "shutdown");
+    /// ```
+    pub const TERMINATION: Self = Self::termination();
+
+    /// The set of supported signals that do not result in process termination,
+    /// equal to [`complement_of_termination`](#method.complement_of_termination).
+    pub const COMPLEMENT_OF_TERMINATION: Self =
+        Self::complement_of_termination();
+
     /// Creates a new, empty signal set.
     #[inline]
     #[must_use]
@@ -103,6 +211,68 @@ impl SignalSet {
         Self(!(!0u32 << Signal::NUM))
     }
 
+    /// The version of the layout used by [`bits`](#method.bits).
+    ///
+    /// New signals are given unused bits without changing this. It is only
+    /// incremented if the bit of an existing signal changes, in which case
+    /// bits persisted under a previous version must not be decoded.
+    pub const LAYOUT_VERSION: u32 = 1;
+
+    /// Returns the target-independent bits of `self`, with each signal at its
+    /// [`stable_bit`](enum.Signal.html#method.stable_bit).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asygnal::SignalSet;
+    ///
+    /// let signals = SignalSet::termination();
+    /// assert_eq!(SignalSet::from_bits(signals.bits()), Some(signals));
+    /// ```
+    pub const fn bits(self) -> u32 {
+        let mut bits = 0;
+        let mut i = 0;
+        while i < Signal::NUM {
+            if self.0 & (1 << i) != 0 {
+                // SAFETY: `i` is less than `Signal::NUM`.
+                let signal = unsafe { Signal::from_u8_unchecked(i as u8) };
+                bits |= 1 << signal.stable_bit();
+            }
+            i += 1;
+        }
+        bits
+    }
+
+    /// Creates a set from bits returned by [`bits`](#method.bits).
+    ///
+    /// Returns `None` if `bits` contains a signal that is not supported by this
+    /// target, or a bit not assigned to any signal, such as from a newer
+    /// version of this crate.
+    pub const fn from_bits(bits: u32) -> Option<Self> {
+        let signals = Self::from_bits_truncate(bits);
+        if signals.bits() == bits {
+            Some(signals)
+        } else {
+            None
+        }
+    }
+
+    /// Creates a set from bits returned by [`bits`](#method.bits), ignoring
+    /// bits of signals not supported by this target and unassigned bits.
+    pub const fn from_bits_truncate(bits: u32) -> Self {
+        let mut signals = 0;
+        let mut i = 0;
+        while i < Signal::NUM {
+            // SAFETY: `i` is less than `Signal::NUM`.
+            let signal = unsafe { Signal::from_u8_unchecked(i as u8) };
+            if bits & (1 << signal.stable_bit()) != 0 {
+                signals |= 1 << i;
+            }
+            i += 1;
+        }
+        Self(signals)
+    }
+
     /// Creates a new set with `signal` enabled.
     #[inline]
     pub const fn from_signal(signal: Signal) -> Self {
@@ -164,14 +334,17 @@ impl SignalSet {
                 set = set.alarm().hangup().pipe().quit();
             }
 
-            #[cfg(any(
-                not(target_env = "uclibc"),
-                all(
-                    target_env = "uclibc",
-                    any(
-                        target_arch = "arm",
-                        target_arch = "mips",
-                        target_arch = "mips64",
+            #[cfg(all(
+                not(windows),
+                any(
+                    not(target_env = "uclibc"),
+                    all(
+                        target_env = "uclibc",
+                        any(
+                            target_arch = "arm",
+                            target_arch = "mips",
+                            target_arch = "mips64",
+                        ),
                     ),
                 ),
             ))]
@@ -219,6 +392,18 @@ impl SignalSet {
         set
     }
 
+    /// Creates a new set of every supported signal not in
+    /// [`termination`](#method.termination).
+    ///
+    /// These are signals that either are not sent to request termination, such
+    /// as [`child`](#method.child), or cannot be gracefully handled, such as
+    /// [`seg_violation`](#method.seg_violation).
+    #[inline]
+    #[must_use]
+    pub const fn complement_of_termination() -> Self {
+        Self::all().without_all(Self::termination())
+    }
+
     cfg_docs! {
         /// Converts `self` into a raw signal set, returning [`None`] on error.
         #[cfg(any(
@@ -248,12 +433,50 @@ impl SignalSet {
         }
     }
 
+    /// Sends each signal in `self` to the current process in order of
+    /// integer value, yielding the thread in between.
+    ///
+    /// This is meant for tests that exercise handling multiple signals,
+    /// such as shutdown paths. Sending stops at the first failure.
+    ///
+    /// Note that signals without a handler perform their default action,
+    /// which often terminates the process.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asygnal::SignalSet;
+    ///
+    /// let signals = SignalSet::new().hangup().user_def_1();
+    /// let mut iter = signals.blocking_iter()?;
+    ///
+    /// signals.raise_all()?;
+    /// assert_eq!(iter.next(), signals.first());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(any(docsrs, unix))]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn raise_all(self) -> std::io::Result<()> {
+        let pid = unsafe { libc::getpid() };
+
+        for (i, signal) in self.into_iter().enumerate() {
+            if i != 0 {
+                std::thread::yield_now();
+            }
+            if unsafe { libc::kill(pid, signal.into_raw()) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+
+        Ok(())
+    }
+
     /// Registers a signal handler that will only be fulfilled once.
     ///
     /// After the `SignalSetOnce` is fulfilled, all subsequent polls will return
     /// `Ready`.
-    #[cfg(any(docsrs, feature = "once"))]
-    #[cfg_attr(docsrs, doc(cfg(feature = "once")))]
+    #[cfg(all(unix, any(docsrs, feature = "once-core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
     pub fn register_once(
         self,
     ) -> Result<
@@ -263,6 +486,106 @@ impl SignalSet {
         crate::once::signal::SignalSetOnce::register(self)
     }
 
+    /// Registers a signal handler that will only be fulfilled once, for each
+    /// signal in `self` that can be handled.
+    ///
+    /// See [`SignalSetOnce::register_partial`](../once/signal/struct.SignalSetOnce.html#method.register_partial)
+    /// for more info.
+    #[cfg(all(unix, any(docsrs, feature = "once-core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+    pub fn register_once_partial(
+        self,
+    ) -> Result<
+        (
+            crate::once::signal::SignalSetOnce,
+            Vec<crate::once::signal::RegisterOnceError>,
+        ),
+        crate::once::signal::RegisterOnceError,
+    > {
+        crate::once::signal::SignalSetOnce::register_partial(self)
+    }
+
+    /// Registers a signal handler that will only be fulfilled once, labeled
+    /// with `label` for diagnostics.
+    ///
+    /// See [`SignalSetOnce::register_labeled`](../once/signal/struct.SignalSetOnce.html#method.register_labeled)
+    /// for more info.
+    #[cfg(all(unix, any(docsrs, feature = "once-core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+    pub fn register_once_labeled(
+        self,
+        label: &'static str,
+    ) -> Result<
+        crate::once::signal::SignalSetOnce,
+        crate::once::signal::RegisterOnceError,
+    > {
+        crate::once::signal::SignalSetOnce::register_labeled(self, label)
+    }
+
+    /// Checks that every signal in `self` can be registered right now, without
+    /// installing any handlers.
+    ///
+    /// This allows services to fail fast at startup with an actionable error,
+    /// rather than upon registering later. A signal fails validation if its
+    /// action cannot be changed, or if it is ignored or handled by something
+    /// other than this crate, which registering would override.
+    ///
+    /// Signals are checked in order of integer value, and the first failure is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asygnal::{once::signal::ValidationError, SignalSet};
+    ///
+    /// match SignalSet::termination().validate() {
+    ///     Ok(()) => {}
+    ///     Err(ValidationError::Ignored(signal)) => {
+    ///         eprintln!("{} is ignored, so it will not be handled", signal);
+    ///     }
+    ///     Err(error) => panic!("cannot handle shutdown signals: {}", error),
+    /// }
+    /// ```
+    #[cfg(all(unix, any(docsrs, feature = "once-core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+    pub fn validate(self) -> Result<(), crate::once::signal::ValidationError> {
+        crate::once::signal::validate::validate(self)
+    }
+
+    /// Registers a signal handler and returns an iterator that blocks until
+    /// each next signal in `self` is received.
+    ///
+    /// See [`SignalIter`](../blocking/struct.SignalIter.html) for more info.
+    #[cfg(all(unix, any(docsrs, feature = "once-core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+    pub fn blocking_iter(self) -> std::io::Result<crate::blocking::SignalIter> {
+        crate::blocking::SignalIter::register(self)
+    }
+
+    /// Registers a signal handler and blocks until a signal in `self` is
+    /// received.
+    ///
+    /// See [`blocking::wait`](../blocking/fn.wait.html) for more info.
+    #[cfg(all(unix, any(docsrs, feature = "once-core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+    pub fn wait(self) -> std::io::Result<Signal> {
+        crate::blocking::wait(self)
+    }
+
+    /// Registers a signal handler and blocks until a signal in `self` is
+    /// received or `timeout` elapses.
+    ///
+    /// See [`blocking::wait_timeout`](../blocking/fn.wait_timeout.html) for
+    /// more info.
+    #[cfg(all(unix, any(docsrs, feature = "once-core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+    pub fn wait_timeout(
+        self,
+        timeout: std::time::Duration,
+    ) -> std::io::Result<Option<Signal>> {
+        crate::blocking::wait_timeout(self, timeout)
+    }
+
     /// Returns `self` with `signal` added to or removed from it.
     #[inline]
     #[must_use]
@@ -312,6 +635,43 @@ impl SignalSet {
         Self(self.0 & !signals.0)
     }
 
+    /// Returns the signals in `self` or `other`.
+    ///
+    /// This is the same as [`with_all`](#method.with_all) and `self | other`.
+    #[inline]
+    #[must_use]
+    pub const fn union(self, other: SignalSet) -> Self {
+        self.with_all(other)
+    }
+
+    /// Returns the signals in both `self` and `other`.
+    ///
+    /// This is the same as `self & other`.
+    #[inline]
+    #[must_use]
+    pub const fn intersection(self, other: SignalSet) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Returns the signals in `self` but not in `other`.
+    ///
+    /// This is the same as [`without_all`](#method.without_all) and
+    /// `self - other`.
+    #[inline]
+    #[must_use]
+    pub const fn difference(self, other: SignalSet) -> Self {
+        self.without_all(other)
+    }
+
+    /// Returns the signals in `self` or `other` but not in both.
+    ///
+    /// This is the same as `self ^ other`.
+    #[inline]
+    #[must_use]
+    pub const fn symmetric_difference(self, other: SignalSet) -> Self {
+        Self(self.0 ^ other.0)
+    }
+
     /// Removes `signals` from `self`.
     #[inline]
     pub fn remove<S: Into<SignalSet>>(&mut self, signals: S) {
@@ -330,6 +690,10 @@ impl SignalSet {
 
     /// Returns the least significant signal bit of `self`, assuming `self` is
     /// not empty.
+    ///
+    /// # Safety
+    ///
+    /// `self` must not be empty.
     #[inline]
     pub const unsafe fn first_unchecked(self) -> Signal {
         Signal::from_u8_unchecked(self.0.trailing_zeros() as u8)
@@ -371,6 +735,10 @@ impl SignalSet {
 
     /// Returns the most significant signal bit of `self`, assuming `self` is
     /// not empty.
+    ///
+    /// # Safety
+    ///
+    /// `self` must not be empty.
     #[inline]
     pub const unsafe fn last_unchecked(self) -> Signal {
         let bits = mem::size_of::<Self>() * 8 - 1;
@@ -395,6 +763,19 @@ impl SignalSet {
         let _ = self.pop_last();
     }
 
+    /// Returns the signal in `self` if it is the only one.
+    ///
+    /// This allows for taking a cheaper path when an API that accepts a set is
+    /// given a single signal.
+    #[inline]
+    pub const fn single(self) -> Option<Signal> {
+        if self.len() == 1 {
+            unsafe { Some(self.first_unchecked()) }
+        } else {
+            None
+        }
+    }
+
     /// The number of signals in `self`.
     #[inline]
     pub const fn len(self) -> usize {
@@ -420,6 +801,70 @@ impl SignalSet {
     pub const fn contains_any(self, signals: SignalSet) -> bool {
         self.0 & signals.0 != 0
     }
+
+    /// Returns `true` if every [`Signal`] in `signals` is stored in `self`.
+    ///
+    /// This is the same as [`is_superset`](#method.is_superset).
+    ///
+    /// [`Signal`]: enum.Signal.html
+    #[inline]
+    pub const fn contains_all(self, signals: SignalSet) -> bool {
+        self.0 & signals.0 == signals.0
+    }
+
+    /// Returns `true` if every signal in `self` is also in `other`.
+    #[inline]
+    pub const fn is_subset(self, other: SignalSet) -> bool {
+        other.contains_all(self)
+    }
+
+    /// Returns `true` if every signal in `other` is also in `self`.
+    #[inline]
+    pub const fn is_superset(self, other: SignalSet) -> bool {
+        self.contains_all(other)
+    }
+
+    /// Returns `true` if `self` and `other` have no signals in common.
+    #[inline]
+    pub const fn is_disjoint(self, other: SignalSet) -> bool {
+        !self.contains_any(other)
+    }
+
+    /// Returns the signals in `self` for which `predicate` returns `true`.
+    ///
+    /// Calling this on [`all`](#method.all) builds a set from a predicate over
+    /// every supported signal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asygnal::{Signal, SignalSet};
+    ///
+    /// let low = SignalSet::all().filter(|signal| signal.into_raw() < 4);
+    /// assert!(low.contains(Signal::Interrupt));
+    /// assert!(!low.contains(Signal::Terminate));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn filter<F>(self, mut predicate: F) -> Self
+    where
+        F: FnMut(Signal) -> bool,
+    {
+        self.into_iter()
+            .filter(|&signal| predicate(signal))
+            .collect()
+    }
+
+    /// Retains only the signals in `self` for which `predicate` returns `true`.
+    ///
+    /// This is the in-place counterpart to [`filter`](#method.filter).
+    #[inline]
+    pub fn retain<F>(&mut self, predicate: F)
+    where
+        F: FnMut(Signal) -> bool,
+    {
+        *self = self.filter(predicate);
+    }
 }
 
 /// An iterator over a [`SignalSet`].
@@ -458,6 +903,36 @@ impl Iterator for SignalSetIter {
     fn last(mut self) -> Option<Signal> {
         self.next_back()
     }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Signal> {
+        if n >= self.0.len() {
+            self.0 = SignalSet::new();
+            return None;
+        }
+
+        // Clear every signal below the `n`th at once.
+        let index = select_bit((self.0).0, n as u32);
+        (self.0).0 &= u32::MAX << index;
+        self.next()
+    }
+
+    #[inline]
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Signal) -> B,
+    {
+        let mut signals = self.0;
+        let mut acc = init;
+
+        while !signals.is_empty() {
+            // SAFETY: `signals` was just checked to not be empty.
+            acc = f(acc, unsafe { signals.first_unchecked() });
+            signals.remove_first();
+        }
+
+        acc
+    }
 }
 
 impl DoubleEndedIterator for SignalSetIter {
@@ -465,6 +940,60 @@ impl DoubleEndedIterator for SignalSetIter {
     fn next_back(&mut self) -> Option<Signal> {
         self.0.pop_last()
     }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Signal> {
+        if n >= self.0.len() {
+            self.0 = SignalSet::new();
+            return None;
+        }
+
+        // Clear every signal above the `n`th from the back at once.
+        let index = select_bit((self.0).0, (self.0.len() - 1 - n) as u32);
+        (self.0).0 &= u32::MAX >> (u32::BITS - 1 - index);
+        self.next_back()
+    }
+
+    #[inline]
+    fn rfold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Signal) -> B,
+    {
+        let mut bits = (self.0).0;
+        let mut acc = init;
+
+        while bits != 0 {
+            let index = u32::BITS - 1 - bits.leading_zeros();
+            bits ^= 1 << index;
+
+            // SAFETY: `index` is the position of a bit in the set.
+            acc = f(acc, unsafe { Signal::from_u8_unchecked(index as u8) });
+        }
+
+        acc
+    }
+}
+
+/// Returns the position of the `n`th least significant set bit of `bits`,
+/// assuming it has more than `n` set bits.
+///
+/// This narrows down the position by counting the bits in the lower half of
+/// the remaining range, rather than clearing one bit at a time.
+#[inline]
+const fn select_bit(bits: u32, mut n: u32) -> u32 {
+    let mut index = 0;
+    let mut width = u32::BITS / 2;
+
+    while width != 0 {
+        let lower = ((bits >> index) & ((1 << width) - 1)).count_ones();
+        if n >= lower {
+            n -= lower;
+            index += width;
+        }
+        width /= 2;
+    }
+
+    index
 }
 
 impl ExactSizeIterator for SignalSetIter {
@@ -474,6 +1003,8 @@ impl ExactSizeIterator for SignalSetIter {
     }
 }
 
+impl FusedIterator for SignalSetIter {}
+
 impl SignalSetIter {
     /// Creates a new iterator for `signals`.
     #[inline]
@@ -486,6 +1017,102 @@ impl SignalSetIter {
     pub const fn into_signal_set(self) -> SignalSet {
         self.0
     }
+
+    /// Returns the signal that will be returned by [`next`] without advancing
+    /// the iterator.
+    ///
+    /// This is equivalent to [`SignalSet::first`] on the remaining signals.
+    ///
+    /// [`next`]: #method.next
+    /// [`SignalSet::first`]: struct.SignalSet.html#method.first
+    #[inline]
+    pub const fn peek_first(&self) -> Option<Signal> {
+        self.0.first()
+    }
+
+    /// Returns the signal that will be returned by [`next_back`] without
+    /// advancing the iterator.
+    ///
+    /// This is equivalent to [`SignalSet::last`] on the remaining signals.
+    ///
+    /// [`next_back`]: #method.next_back
+    /// [`SignalSet::last`]: struct.SignalSet.html#method.last
+    #[inline]
+    pub const fn peek_last(&self) -> Option<Signal> {
+        self.0.last()
+    }
+}
+
+/// An iterator over references to the signals in a [`SignalSet`].
+///
+/// This is created by iterating over `&SignalSet`.
+///
+/// [`SignalSet`]: struct.SignalSet.html
+#[derive(Clone, Debug)]
+pub struct SignalSetRefIter<'a> {
+    iter: SignalSetIter,
+    marker: PhantomData<&'a SignalSet>,
+}
+
+impl<'a> Iterator for SignalSetRefIter<'a> {
+    type Item = &'a Signal;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a Signal> {
+        self.iter.next().map(Signal::as_static)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.iter.count()
+    }
+
+    #[inline]
+    fn last(self) -> Option<&'a Signal> {
+        self.iter.last().map(Signal::as_static)
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<&'a Signal> {
+        self.iter.nth(n).map(Signal::as_static)
+    }
+}
+
+impl DoubleEndedIterator for SignalSetRefIter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(Signal::as_static)
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth_back(n).map(Signal::as_static)
+    }
+}
+
+impl ExactSizeIterator for SignalSetRefIter<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl FusedIterator for SignalSetRefIter<'_> {}
+
+impl<'a> SignalSetRefIter<'a> {
+    /// Creates a new iterator over references to the signals in `signals`.
+    #[inline]
+    pub const fn new(signals: &'a SignalSet) -> Self {
+        Self {
+            iter: SignalSetIter::new(*signals),
+            marker: PhantomData,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -510,4 +1137,210 @@ mod tests {
         all.into_iter().for_each(|s| assert(s as u32));
         all.into_iter().rev().for_each(|s| assert(s as u32));
     }
+
+    #[test]
+    fn iter_ref() {
+        let all = SignalSet::all();
+
+        let refs: Vec<&Signal> = (&all).into_iter().collect();
+        let values: Vec<Signal> = all.into_iter().collect();
+        assert!(refs.iter().copied().eq(values.iter()));
+
+        let mut set = SignalSet::new();
+        set.extend(&values);
+        assert_eq!(set, all);
+    }
+
+    #[test]
+    fn iter_nth() {
+        let all = SignalSet::all();
+        let signals: Vec<Signal> = all.into_iter().collect();
+
+        for n in 0..=signals.len() {
+            assert_eq!(all.into_iter().nth(n), signals.get(n).copied());
+            assert_eq!(
+                all.into_iter().nth_back(n),
+                signals.iter().rev().nth(n).copied()
+            );
+        }
+
+        let mut iter = all.into_iter();
+        assert_eq!(iter.nth(signals.len()), None);
+        assert_eq!(iter.next(), None);
+
+        // Skipping leaves exactly the signals past the skipped ones.
+        let sparse = all.filter(|signal| signal as usize % 3 != 1);
+        let signals: Vec<Signal> = sparse.into_iter().collect();
+
+        for n in 0..signals.len() {
+            let mut iter = sparse.into_iter();
+            assert_eq!(iter.nth(n), Some(signals[n]));
+            assert!(iter.eq(signals[n + 1..].iter().copied()));
+
+            let mut iter = sparse.into_iter();
+            assert_eq!(iter.nth_back(n), signals.iter().rev().nth(n).copied());
+            assert!(iter.eq(signals[..signals.len() - 1 - n].iter().copied()));
+        }
+    }
+
+    #[test]
+    fn single() {
+        assert_eq!(SignalSet::new().single(), None);
+        assert_eq!(SignalSet::all().single(), None);
+
+        for signal in SignalSet::all() {
+            assert_eq!(SignalSet::from_signal(signal).single(), Some(signal));
+        }
+    }
+
+    #[test]
+    fn iter_fold() {
+        let all = SignalSet::all();
+        let signals: Vec<Signal> = all.into_iter().collect();
+
+        let folded = all.into_iter().fold(Vec::new(), |mut v, s| {
+            v.push(s);
+            v
+        });
+        assert_eq!(folded, signals);
+
+        let rfolded = all.into_iter().rfold(Vec::new(), |mut v, s| {
+            v.push(s);
+            v
+        });
+        assert_eq!(rfolded, signals.into_iter().rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn ops() {
+        let set = SignalSet::termination() | Signal::Abort;
+        assert_eq!(set, SignalSet::termination().abort());
+
+        assert_eq!(!SignalSet::new(), SignalSet::all());
+        assert_eq!(!SignalSet::all(), SignalSet::new());
+        assert_eq!(!!set, set);
+        assert_eq!(
+            !Signal::Interrupt,
+            SignalSet::all().without(Signal::Interrupt)
+        );
+
+        assert_eq!(set & Signal::Abort, Signal::Abort.into());
+        assert_eq!(set - Signal::Abort, SignalSet::termination());
+        assert_eq!(set ^ set, SignalSet::new());
+        assert_eq!(
+            Signal::Interrupt | Signal::Terminate,
+            SignalSet::new().interrupt().terminate()
+        );
+
+        let mut assigned = SignalSet::new();
+        assigned |= Signal::Interrupt;
+        assigned ^= SignalSet::new().interrupt().terminate();
+        assert_eq!(assigned, Signal::Terminate.into());
+        assigned &= Signal::Interrupt;
+        assert!(assigned.is_empty());
+        assigned |= set;
+        assigned -= SignalSet::termination();
+        assert_eq!(assigned, Signal::Abort.into());
+    }
+
+    #[test]
+    fn relations() {
+        let termination = SignalSet::termination();
+        let interrupt = SignalSet::from(Signal::Interrupt);
+        let abort = SignalSet::from(Signal::Abort);
+
+        assert!(interrupt.is_subset(termination));
+        assert!(termination.is_superset(interrupt));
+        assert!(termination.contains_all(interrupt));
+        assert!(!interrupt.contains_all(termination));
+        assert!(SignalSet::new().is_subset(interrupt));
+        assert!(termination.is_subset(termination));
+
+        assert!(termination.is_disjoint(abort));
+        assert!(!termination.is_disjoint(interrupt));
+        assert!(SignalSet::new().is_disjoint(SignalSet::new()));
+
+        let both = termination.union(abort);
+        assert_eq!(both.intersection(abort), abort);
+        assert_eq!(both.difference(abort), termination);
+        assert_eq!(
+            termination.symmetric_difference(interrupt.union(abort)),
+            both.without(Signal::Interrupt)
+        );
+    }
+
+    #[test]
+    fn stable_bits() {
+        // Changing any of these requires incrementing `LAYOUT_VERSION`.
+        let layout = [
+            ("SIGABRT", 0),
+            ("SIGALRM", 1),
+            ("SIGBUS", 2),
+            ("SIGCHLD", 3),
+            ("SIGCONT", 4),
+            ("SIGFPE", 5),
+            ("SIGHUP", 6),
+            ("SIGILL", 7),
+            ("SIGINFO", 8),
+            ("SIGINT", 9),
+            ("SIGIO", 10),
+            ("SIGPIPE", 11),
+            ("SIGPOLL", 12),
+            ("SIGPROF", 13),
+            ("SIGQUIT", 14),
+            ("SIGSEGV", 15),
+            ("SIGTSTP", 16),
+            ("SIGSYS", 17),
+            ("SIGTERM", 18),
+            ("SIGTRAP", 19),
+            ("SIGTTIN", 20),
+            ("SIGTTOU", 21),
+            ("SIGURG", 22),
+            ("SIGVTALRM", 23),
+            ("SIGUSR1", 24),
+            ("SIGUSR2", 25),
+            ("SIGXCPU", 26),
+            ("SIGXFSZ", 27),
+            ("SIGWINCH", 28),
+        ];
+        assert_eq!(SignalSet::LAYOUT_VERSION, 1);
+
+        for signal in SignalSet::all() {
+            let bit = layout
+                .iter()
+                .find(|(name, _)| *name == signal.name())
+                .map(|&(_, bit)| bit);
+            assert_eq!(Some(signal.stable_bit()), bit, "{}", signal.name());
+            assert_eq!(SignalSet::from(signal).bits(), 1 << bit.unwrap());
+        }
+
+        let all = SignalSet::all();
+        assert_eq!(SignalSet::from_bits(all.bits()), Some(all));
+        assert_eq!(SignalSet::from_bits(1 << 31), None);
+        assert_eq!(SignalSet::from_bits_truncate(!0), all);
+    }
+
+    #[cfg(all(unix, feature = "once-core"))]
+    #[test]
+    fn raise_all() {
+        use std::time::Duration;
+
+        let signals = SignalSet::new().urgent().window_change();
+
+        crate::test_util::run_exclusive(|| {
+            let mut iter = signals.blocking_iter().unwrap();
+            signals.raise_all().unwrap();
+
+            // Signals sent to the process may be handled on another thread, so
+            // they aren't necessarily both caught by the first call.
+            let mut received = SignalSet::new();
+            while received != signals {
+                let timeout = Duration::from_secs(10);
+                match iter.next_timeout(timeout).unwrap() {
+                    Some(signal) => received.insert(signal),
+                    None => panic!("only received {:?}", received),
+                }
+            }
+        });
+    }
 }
@@ -0,0 +1,79 @@
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Sub,
+    SubAssign,
+};
+
+use super::{Signal, SignalSet};
+
+macro_rules! impl_ops {
+    ($(
+        $(#[doc = $doc:literal])+
+        $op:ident, $method:ident, $assign_op:ident, $assign_method:ident,
+        |$a:ident, $b:ident| $expr:expr;
+    )+) => {
+        $(
+            $(#[doc = $doc])+
+            impl<S: Into<SignalSet>> $op<S> for SignalSet {
+                type Output = SignalSet;
+
+                #[inline]
+                fn $method(self, other: S) -> SignalSet {
+                    let ($a, $b) = (self.0, other.into().0);
+                    SignalSet($expr)
+                }
+            }
+
+            $(#[doc = $doc])+
+            impl<S: Into<SignalSet>> $op<S> for Signal {
+                type Output = SignalSet;
+
+                #[inline]
+                fn $method(self, other: S) -> SignalSet {
+                    SignalSet::from_signal(self).$method(other)
+                }
+            }
+
+            $(#[doc = $doc])+
+            impl<S: Into<SignalSet>> $assign_op<S> for SignalSet {
+                #[inline]
+                fn $assign_method(&mut self, other: S) {
+                    *self = self.$method(other);
+                }
+            }
+        )+
+    };
+}
+
+impl_ops! {
+    /// Returns the union of the signals.
+    BitOr, bitor, BitOrAssign, bitor_assign, |a, b| a | b;
+
+    /// Returns the intersection of the signals.
+    BitAnd, bitand, BitAndAssign, bitand_assign, |a, b| a & b;
+
+    /// Returns the signals in exactly one of the operands.
+    BitXor, bitxor, BitXorAssign, bitxor_assign, |a, b| a ^ b;
+
+    /// Returns the signals on the left that are not on the right.
+    Sub, sub, SubAssign, sub_assign, |a, b| a & !b;
+}
+
+/// Returns every supported signal not in `self`.
+impl Not for SignalSet {
+    type Output = SignalSet;
+
+    #[inline]
+    fn not(self) -> SignalSet {
+        SignalSet::all().without_all(self)
+    }
+}
+
+/// Returns every supported signal other than `self`.
+impl Not for Signal {
+    type Output = SignalSet;
+
+    #[inline]
+    fn not(self) -> SignalSet {
+        !SignalSet::from_signal(self)
+    }
+}
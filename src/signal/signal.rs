@@ -1,13 +1,18 @@
 use super::{SignalArray, SignalSet};
-use std::mem;
+use std::{cmp::Ordering, mem};
 
 use libc::c_int;
 
+// Defines `probed_signal!`, which maps a `libc` constant name to the value
+// found in the target's C headers, if any.
+#[cfg(asygnal_probe)]
+include!(concat!(env!("OUT_DIR"), "/probed_signals.rs"));
+
 macro_rules! signals {
     ($(
         $(#[doc = $doc:literal])+
         $(#[cfg($cfg:meta)])?
-        $variant:ident, $method:ident, $libc:ident;
+        $variant:ident, $method:ident, $libc:ident = $bit:literal;
     )+) => {
         /// POSIX-style signals.
         ///
@@ -49,7 +54,9 @@ macro_rules! signals {
         /// [`Signal::Abort`]:    #variant.Abort
         /// [`SignalSet::abort`]: struct.SignalSet.html#method.abort
         /// [`SignalSet`]:        struct.SignalSet.html
-        #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+        #[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+        #[cfg_attr(not(feature = "min-size"), derive(Debug))]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
         #[non_exhaustive]
         pub enum Signal {
             $(
@@ -81,10 +88,21 @@ macro_rules! signals {
             };
         }
 
+        /// Every variant, indexed by its integer value.
+        static VARIANTS: SignalArray<Signal> = [
+            $(
+                $(#[cfg($cfg)])?
+                Signal::$variant,
+            )+
+        ];
+
         /// Handling of raw signal values from `libc`.
         impl Signal {
             /// Attempts to create an instance if `signal` is known.
             pub const fn from_raw(signal: c_int) -> Option<Self> {
+                // Some targets alias signals (e.g. `SIGPOLL` and `SIGIO` on
+                // Linux), in which case the first variant wins.
+                #[allow(unreachable_patterns)]
                 match signal {
                     $(
                         $(#[cfg($cfg)])?
@@ -97,17 +115,103 @@ macro_rules! signals {
             /// Returns the raw signal value.
             #[inline]
             pub const fn into_raw(self) -> c_int {
-                const VALUES: SignalArray<c_int> = [
+                Self::RAW_TABLE[self as usize]
+            }
+
+            /// The raw value of every variant, indexed by its integer value.
+            const RAW_TABLE: SignalArray<c_int> = [
+                $(
+                    $(#[cfg($cfg)])?
+                    libc::$libc,
+                )+
+            ];
+
+            /// The name of every variant, indexed by its integer value.
+            const NAME_TABLE: SignalArray<&'static str> = [
+                $(
+                    $(#[cfg($cfg)])?
+                    stringify!($libc),
+                )+
+            ];
+
+            /// Every variant, sorted by name for `from_name`.
+            const BY_NAME: SignalArray<Signal> = {
+                let mut sorted = [
                     $(
                         $(#[cfg($cfg)])?
-                        libc::$libc,
+                        Signal::$variant,
                     )+
                 ];
 
-                VALUES[self as usize]
-            }
+                // Insertion sort, since `const` cannot call `sort`.
+                let mut i = 1;
+                while i < sorted.len() {
+                    let mut j = i;
+                    while j > 0 {
+                        let prev = sorted[j - 1].name().as_bytes();
+                        let next = sorted[j].name().as_bytes();
+                        match compare_name(next, 3, prev) {
+                            Ordering::Less => {
+                                let signal = sorted[j];
+                                sorted[j] = sorted[j - 1];
+                                sorted[j - 1] = signal;
+                            }
+                            Ordering::Equal => panic!("duplicate signal name"),
+                            Ordering::Greater => break,
+                        }
+                        j -= 1;
+                    }
+                    i += 1;
+                }
+                sorted
+            };
+        }
+
+        /// Handling of the target-independent bit layout.
+        impl Signal {
+            /// The stable bit of every variant, indexed by its integer value.
+            const BIT_TABLE: SignalArray<u8> = [
+                $(
+                    $(#[cfg($cfg)])?
+                    $bit,
+                )+
+            ];
         }
 
+        // Fails to compile if stable bits overlap or do not fit in a `u32`.
+        const _: () = {
+            let bits: &[u32] = &[$($bit),+];
+            let mut i = 0;
+            while i < bits.len() {
+                assert!(bits[i] < 32, "stable bit does not fit in a `u32`");
+                let mut j = i + 1;
+                while j < bits.len() {
+                    assert!(bits[i] != bits[j], "stable bits overlap");
+                    j += 1;
+                }
+                i += 1;
+            }
+        };
+
+        // Fails to compile if a `libc` constant differs from the C headers.
+        #[cfg(asygnal_probe)]
+        const _: () = {
+            $(
+                $(#[cfg($cfg)])?
+                let _ = match probed_signal!($libc) {
+                    Some(value) => assert!(
+                        value == libc::$libc,
+                        concat!(
+                            "`libc::",
+                            stringify!($libc),
+                            "` does not match the C headers",
+                        ),
+                    ),
+                    None => {}
+                };
+            )+
+        };
+
         /// # Convenience Methods
         ///
         /// Builder pattern insertion of [`Signal` variants][variants].
@@ -139,6 +243,10 @@ signals! {
     //
     // This library uses fixed-size tables based on the number of signals below.
     // Including such signals would be a waste of space.
+    //
+    // The number after each is its stable bit, as used by `SignalSet::bits`.
+    // New signals must take an unused bit. Changing an existing bit requires
+    // incrementing `SignalSet::LAYOUT_VERSION`.
 
     /// The `SIGABRT` signal; sent when the process calls `abort()`.
     ///
@@ -171,7 +279,7 @@ signals! {
         target_os = "vxworks",
         target_env = "uclibc",
     ))]
-    Abort, abort, SIGABRT;
+    Abort, abort, SIGABRT = 0;
 
     /// The `SIGALRM` signal; sent when a real-time timer expires.
     ///
@@ -200,7 +308,7 @@ signals! {
         target_os = "vxworks",
         target_env = "uclibc",
     ))]
-    Alarm, alarm, SIGALRM;
+    Alarm, alarm, SIGALRM = 1;
 
     /// The `SIGBUS` signal; sent when the process causes a [bus error], e.g.
     /// due to incorrect memory access alignment or non-existent physical
@@ -241,7 +349,7 @@ signals! {
             ),
         ),
     ))]
-    Bus, bus, SIGBUS;
+    Bus, bus, SIGBUS = 2;
 
     /// The `SIGCHLD` signal; sent when the status of a child process changes.
     ///
@@ -278,7 +386,7 @@ signals! {
             ),
         ),
     ))]
-    Child, child, SIGCHLD;
+    Child, child, SIGCHLD = 3;
 
     /// The `SIGCONT` signal; sent when the process is **continued** after being
     /// previously paused by the `SIGSTOP` or `SIGTSTP` signal.
@@ -319,7 +427,7 @@ signals! {
             ),
         ),
     ))]
-    Cont, cont, SIGCONT;
+    Cont, cont, SIGCONT = 4;
 
     /// The `SIGFPE` ("**float**ing point **exc**eption") signal; sent when the
     /// process executes an erroneous arithmetic operation, such as division by
@@ -351,7 +459,7 @@ signals! {
         target_os = "vxworks",
         target_env = "uclibc",
     ))]
-    FloatExc, float_exc, SIGFPE;
+    FloatExc, float_exc, SIGFPE = 5;
 
     /// The `SIGHUP` signal; sent when the terminal is disconnected.
     ///
@@ -380,7 +488,7 @@ signals! {
         target_os = "vxworks",
         target_env = "uclibc",
     ))]
-    Hangup, hangup, SIGHUP;
+    Hangup, hangup, SIGHUP = 6;
 
     /// The `SIGILL` signal; sent when the process attempts to execute an
     /// **illegal**, malformed, unknown, or privileged instruction.
@@ -415,7 +523,7 @@ signals! {
         target_os = "vxworks",
         target_env = "uclibc",
     ))]
-    IllInstr, ill_instr, SIGILL;
+    IllInstr, ill_instr, SIGILL = 7;
 
     /// The `SIGINFO` signal; sent to request a status update from the process.
     ///
@@ -435,7 +543,7 @@ signals! {
         target_os = "solaris",
         target_os = "illumos",
     ))]
-    Info, info, SIGINFO;
+    Info, info, SIGINFO = 8;
 
     /// The `SIGINT` signal; sent to interrupt a program.
     ///
@@ -467,7 +575,7 @@ signals! {
         target_os = "vxworks",
         target_env = "uclibc",
     ))]
-    Interrupt, interrupt, SIGINT;
+    Interrupt, interrupt, SIGINT = 9;
 
     /// The `SIGIO` signal; sent when I/O operations are possible on some file
     /// descriptor.
@@ -503,7 +611,7 @@ signals! {
             ),
         ),
     ))]
-    Io, io, SIGIO;
+    Io, io, SIGIO = 10;
 
     /// The `SIGPIPE` signal; sent when the process attempts to write to a pipe
     /// which has no reader.
@@ -533,7 +641,7 @@ signals! {
         target_os = "vxworks",
         target_env = "uclibc",
     ))]
-    Pipe, pipe, SIGPIPE;
+    Pipe, pipe, SIGPIPE = 11;
 
     /// The `SIGPOLL` signal; sent when an event occurred on an explicitly
     /// watched file descriptor.
@@ -559,7 +667,7 @@ signals! {
             ),
         ),
     ))]
-    Poll, poll, SIGPOLL;
+    Poll, poll, SIGPOLL = 12;
 
     /// The `SIGPROF` signal; sent when the limit for CPU time used by the
     /// process and by the system on behalf of the process elapses.
@@ -596,7 +704,7 @@ signals! {
             ),
         ),
     ))]
-    Profile, profile, SIGPROF;
+    Profile, profile, SIGPROF = 13;
 
     /// The `SIGQUIT` signal; sent to issue a shutdown of the process, after
     /// which the OS will dump the process core.
@@ -628,7 +736,7 @@ signals! {
         target_os = "vxworks",
         target_env = "uclibc",
     ))]
-    Quit, quit, SIGQUIT;
+    Quit, quit, SIGQUIT = 14;
 
     /// The `SIGSEGV` signal; sent when the process has attempted to access a
     /// restricted area of memory.
@@ -663,7 +771,7 @@ signals! {
         target_os = "vxworks",
         target_env = "uclibc",
     ))]
-    SegViolation, seg_violation, SIGSEGV;
+    SegViolation, seg_violation, SIGSEGV = 15;
 
     /// The `SIGTSTP` signal; sent when the terminal requests the process to
     /// stop.
@@ -703,7 +811,7 @@ signals! {
             ),
         ),
     ))]
-    TermStop, term_stop, SIGTSTP;
+    TermStop, term_stop, SIGTSTP = 16;
 
     /// The `SIGSYS` signal; sent when a non-existent system call is invoked.
     ///
@@ -739,7 +847,7 @@ signals! {
             ),
         ),
     ))]
-    System, system, SIGSYS;
+    System, system, SIGSYS = 17;
 
     /// The `SIGTERM` signal; sent to issue a shutdown of the process.
     ///
@@ -769,7 +877,7 @@ signals! {
         target_os = "vxworks",
         target_env = "uclibc",
     ))]
-    Terminate, terminate, SIGTERM;
+    Terminate, terminate, SIGTERM = 18;
 
     /// The `SIGTRAP` signal; sent when an exception (or **trap**) occurs.
     ///
@@ -797,7 +905,7 @@ signals! {
         target_os = "vxworks",
         target_env = "uclibc",
     ))]
-    Trap, trap, SIGTRAP;
+    Trap, trap, SIGTRAP = 19;
 
     /// The `SIGTTIN` signal; sent when the process attempts to read **in** from
     /// the [tty] when in the [background].
@@ -838,7 +946,7 @@ signals! {
             ),
         ),
     ))]
-    TtIn, tt_in, SIGTTIN;
+    TtIn, tt_in, SIGTTIN = 20;
 
     /// The `SIGTTOU` signal; sent when the process attempts to write **out** to
     /// the [tty] when in the [background].
@@ -879,7 +987,7 @@ signals! {
             ),
         ),
     ))]
-    TtOut, tt_out, SIGTTOU;
+    TtOut, tt_out, SIGTTOU = 21;
 
     /// The `SIGURG` signal; sent when a [socket] has **urgent** or
     /// [out-of-band data] available to read.
@@ -919,7 +1027,7 @@ signals! {
             ),
         ),
     ))]
-    Urgent, urgent, SIGURG;
+    Urgent, urgent, SIGURG = 22;
 
     /// The `SIGVTALRM` signal; sent when the limit for CPU time used by the
     /// process elapses.
@@ -956,7 +1064,7 @@ signals! {
             ),
         ),
     ))]
-    VtAlarm, vt_alarm, SIGVTALRM;
+    VtAlarm, vt_alarm, SIGVTALRM = 23;
 
     /// The `SIGUSR1` signal; a user defined signal.
     ///
@@ -992,7 +1100,7 @@ signals! {
             ),
         ),
     ))]
-    UserDef1, user_def_1, SIGUSR1;
+    UserDef1, user_def_1, SIGUSR1 = 24;
 
     /// The `SIGUSR2` signal; a user defined signal.
     ///
@@ -1028,7 +1136,7 @@ signals! {
             ),
         ),
     ))]
-    UserDef2, user_def_2, SIGUSR2;
+    UserDef2, user_def_2, SIGUSR2 = 25;
 
     /// The `SIGXCPU` signal; sent when the process has used up the CPU for a
     /// duration that **exceeds** a certain predetermined user-settable value.
@@ -1074,7 +1182,7 @@ signals! {
             ),
         ),
     ))]
-    XCpu, x_cpu, SIGXCPU;
+    XCpu, x_cpu, SIGXCPU = 26;
 
     /// The `SIGXFSZ` signal; sent when the process grows a file that
     /// **exceeds** the maximum allowed size.
@@ -1115,7 +1223,7 @@ signals! {
             ),
         ),
     ))]
-    XFileSize, x_file_size, SIGXFSZ;
+    XFileSize, x_file_size, SIGXFSZ = 27;
 
     /// The `SIGWINCH` signal; sent when the terminal window is resized.
     ///
@@ -1151,7 +1259,24 @@ signals! {
             ),
         ),
     ))]
-    WindowChange, window_change, SIGWINCH;
+    WindowChange, window_change, SIGWINCH = 28;
+}
+
+// Formats the raw value instead of the variant name so that the names are not
+// included in the binary.
+#[cfg(feature = "min-size")]
+impl std::fmt::Debug for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Signal({})", self.into_raw())
+    }
+}
+
+/// Formats the POSIX name of the signal, such as `SIGTERM`.
+impl std::fmt::Display for Signal {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 impl Signal {
@@ -1167,17 +1292,135 @@ impl Signal {
         SignalSet::all()
     }
 
+    /// Returns the raw value of every variant, indexed by its integer value.
+    ///
+    /// This is useful for building raw-indexed structures of your own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asygnal::Signal;
+    ///
+    /// let table = Signal::raw_table();
+    /// assert_eq!(table[Signal::Interrupt as usize], libc::SIGINT);
+    /// ```
+    #[inline]
+    pub const fn raw_table() -> SignalArray<c_int> {
+        Self::RAW_TABLE
+    }
+
+    /// Returns the POSIX name of the signal, such as `"SIGTERM"`.
+    ///
+    /// Unlike the [`Debug`] output, this is unaffected by the `min-size`
+    /// feature, so calling it includes every name in the binary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asygnal::Signal;
+    ///
+    /// assert_eq!(Signal::Terminate.name(), "SIGTERM");
+    /// assert_eq!(Signal::Interrupt.to_string(), "SIGINT");
+    /// ```
+    ///
+    /// [`Debug`]: https://doc.rust-lang.org/std/fmt/trait.Debug.html
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        Self::NAME_TABLE[self as usize]
+    }
+
+    /// Returns the signal named `name`, with or without the `SIG` prefix and
+    /// ignoring case, such as `"SIGINT"` or `"int"`.
+    ///
+    /// This is also available through [`FromStr`](#impl-FromStr).
+    ///
+    /// Names are looked up by binary search in a table sorted at compile time,
+    /// so this neither allocates nor compares against every name, and can be
+    /// used in `const` contexts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asygnal::Signal;
+    ///
+    /// const RELOAD: Option<Signal> = Signal::from_name("SIGHUP");
+    /// assert_eq!(RELOAD, Some(Signal::Hangup));
+    ///
+    /// assert_eq!(Signal::from_name("SIGUSR2"), Some(Signal::UserDef2));
+    /// assert_eq!(Signal::from_name("term"), Some(Signal::Terminate));
+    /// assert_eq!(Signal::from_name("SIGKILL"), None);
+    /// ```
+    pub const fn from_name(name: &str) -> Option<Self> {
+        let name = name.as_bytes();
+        let start = if name.len() >= 3
+            && name[0].eq_ignore_ascii_case(&b'S')
+            && name[1].eq_ignore_ascii_case(&b'I')
+            && name[2].eq_ignore_ascii_case(&b'G')
+        {
+            3
+        } else {
+            0
+        };
+
+        // Binary search over the names sorted at compile time.
+        let mut low = 0;
+        let mut high = Self::NUM;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let signal = Self::BY_NAME[mid];
+            match compare_name(name, start, signal.name().as_bytes()) {
+                Ordering::Less => high = mid,
+                Ordering::Equal => return Some(signal),
+                Ordering::Greater => low = mid + 1,
+            }
+        }
+        None
+    }
+
+    /// Returns the position of `self` in the bits of
+    /// [`SignalSet::bits`](struct.SignalSet.html#method.bits).
+    ///
+    /// Unlike the integer value of `self`, this is the same on every target,
+    /// and only changes along with
+    /// [`SignalSet::LAYOUT_VERSION`](struct.SignalSet.html#associatedconstant.LAYOUT_VERSION).
+    #[inline]
+    pub const fn stable_bit(self) -> u32 {
+        Self::BIT_TABLE[self as usize] as u32
+    }
+
+    /// Returns the largest raw value of any variant.
+    ///
+    /// A structure keyed by raw signal values, such as an eBPF map, needs
+    /// `max_raw() + 1` entries to fit every signal.
+    pub const fn max_raw() -> c_int {
+        let mut max = 0;
+        let mut i = 0;
+        while i < Self::NUM {
+            if Self::RAW_TABLE[i] > max {
+                max = Self::RAW_TABLE[i];
+            }
+            i += 1;
+        }
+        max
+    }
+
     #[inline]
     pub(crate) const unsafe fn from_u8_unchecked(signal: u8) -> Self {
         mem::transmute(signal)
     }
 
+    /// Returns a reference to `self` that lives for the rest of the program.
+    #[inline]
+    pub(crate) fn as_static(self) -> &'static Self {
+        &VARIANTS[self as usize]
+    }
+
     /// Registers a signal handler that will only be fulfilled once.
     ///
     /// After the `SignalOnce` is fulfilled, all subsequent polls will return
     /// `Ready`.
-    #[cfg(any(docsrs, feature = "once"))]
-    #[cfg_attr(docsrs, doc(cfg(feature = "once")))]
+    #[cfg(all(unix, any(docsrs, feature = "once-core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
     pub fn register_once(
         self,
     ) -> Result<
@@ -1186,6 +1429,66 @@ impl Signal {
     > {
         crate::once::signal::SignalOnce::register(self)
     }
+
+    /// Registers a signal handler that will only be fulfilled once, labeled
+    /// with `label` for diagnostics.
+    ///
+    /// See [`SignalOnce::register_labeled`](../once/signal/struct.SignalOnce.html#method.register_labeled)
+    /// for more info.
+    #[cfg(all(unix, any(docsrs, feature = "once-core")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+    pub fn register_once_labeled(
+        self,
+        label: &'static str,
+    ) -> Result<
+        crate::once::signal::SignalOnce,
+        crate::once::signal::RegisterOnceError,
+    > {
+        crate::once::signal::SignalOnce::register_labeled(self, label)
+    }
+}
+
+#[cfg(all(unix, any(docsrs, feature = "once-core")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+impl std::future::IntoFuture for Signal {
+    type Output = Option<Signal>;
+    type IntoFuture = crate::once::signal::SignalSetFuture;
+
+    /// Returns a future that registers a handler for `self` upon first being
+    /// polled.
+    #[inline]
+    fn into_future(self) -> Self::IntoFuture {
+        crate::once::signal::SignalSetFuture::new(self.into())
+    }
+}
+
+/// Compares `name[start..]`, ignoring ASCII case, with the part of the
+/// uppercase `signal_name` after its `SIG` prefix.
+const fn compare_name(
+    name: &[u8],
+    start: usize,
+    signal_name: &[u8],
+) -> Ordering {
+    let mut i = start;
+    let mut j = 3;
+    while i < name.len() && j < signal_name.len() {
+        let a = name[i].to_ascii_uppercase();
+        let b = signal_name[j];
+        if a < b {
+            return Ordering::Less;
+        } else if a > b {
+            return Ordering::Greater;
+        }
+        i += 1;
+        j += 1;
+    }
+    if i < name.len() {
+        Ordering::Greater
+    } else if j < signal_name.len() {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }
 }
 
 macro_rules! from_int {
@@ -1238,3 +1541,31 @@ from_int! {
     /// Creates an instance from a signed pointer-sized integer.
     from_isize, isize;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn by_name_sorted() {
+        let names = Signal::BY_NAME.map(Signal::name);
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn from_name() {
+        for &signal in Signal::BY_NAME.iter() {
+            let name = signal.name();
+            let short = &name[3..];
+
+            assert_eq!(Signal::from_name(name), Some(signal));
+            assert_eq!(Signal::from_name(short), Some(signal));
+            assert_eq!(Signal::from_name(&name.to_lowercase()), Some(signal));
+            assert_eq!(Signal::from_name(&short.to_lowercase()), Some(signal));
+        }
+
+        for name in ["", "SIG", "SIGKILL", "SIGHU", "SIGHUPP", "SIG SIGHUP"] {
+            assert_eq!(Signal::from_name(name), None, "{}", name);
+        }
+    }
+}
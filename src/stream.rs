@@ -0,0 +1,763 @@
+//! Streams that are fulfilled repeatedly.
+//!
+//! Unlike the futures in [`once`], these yield every time a signal is received,
+//! which suits reacting to e.g. [`Hangup`] for reloading configuration.
+//!
+//! Signals received in between polls are coalesced, with each pending signal
+//! yielded once in order of its integer value.
+//!
+//! A stream ends once [closed] and drained of the signals it received before
+//! being closed, or upon an internal error under
+//! [`ErrorPolicy::ResolveWithError`]. This lets a consumer tell "no more
+//! signals will be observed" apart from "no signals right now".
+//!
+//! Every stream here implements [`FusedStream`], so it can be used directly in
+//! `futures::select!`. A stream is only terminated once it has ended.
+//!
+//! [closed]: struct.SignalSetStream.html#method.close
+//! [`ErrorPolicy::ResolveWithError`]: ../once/signal/enum.ErrorPolicy.html#variant.ResolveWithError
+//! [`FusedStream`]: https://docs.rs/futures-core/0.3/futures_core/stream/trait.FusedStream.html
+//! [`once`]:   ../once/index.html
+//! [`Hangup`]: ../signal/enum.Signal.html#variant.Hangup
+
+use futures_core::{stream::FusedStream, Stream};
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    once::{
+        signal::{listener::Listener, ErrorPolicy, SignalInfo},
+        CancelSafe,
+    },
+    Signal, SignalSet,
+};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::{
+    once::signal::{realtime::RealtimeListener, RealtimeEvent},
+    signal::RealtimeSignalSet,
+};
+
+/// A stream that yields each time a [`Signal`] in a [`SignalSet`] is received.
+///
+/// Dropping the stream restores the previous action of each signal that has
+/// no other listeners.
+///
+/// The stream only ends once [closed](#method.close), or if an internal error
+/// occurs under [`ErrorPolicy::ResolveWithError`], in which case the error can
+/// be retrieved via [`take_error`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{stream::SignalSetStream, Signal, SignalSet};
+/// use futures_core::Stream;
+/// use std::{future::poll_fn, pin::Pin};
+///
+/// # async fn example() -> std::io::Result<()> {
+/// let signals = SignalSet::new().hangup().terminate();
+/// let mut stream = SignalSetStream::register(signals)?;
+///
+/// while let Some(signal) =
+///     poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await
+/// {
+///     if signal == Signal::Terminate {
+///         break;
+///     }
+///     println!("reloading config");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Signal`]:    ../signal/enum.Signal.html
+/// [`SignalSet`]: ../signal/struct.SignalSet.html
+/// [`ErrorPolicy::ResolveWithError`]: ../once/signal/enum.ErrorPolicy.html#variant.ResolveWithError
+/// [`take_error`]: #method.take_error
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct SignalSetStream {
+    listener: Listener,
+    done: bool,
+    closed: bool,
+    /// The waker notified of signals instead of that of the polling task.
+    target: Option<Waker>,
+}
+
+impl Stream for SignalSetStream {
+    type Item = Signal;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Signal>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        if self.closed {
+            let signal = self.listener.pop_pending();
+            self.done = signal.is_none();
+            return Poll::Ready(signal);
+        }
+
+        let this = &mut *self;
+        let result = match &this.target {
+            Some(target) => {
+                this.listener.poll_next(&mut Context::from_waker(target))
+            }
+            None => this.listener.poll_next(cx),
+        };
+
+        match result {
+            Poll::Ready(Ok(signal)) => Poll::Ready(Some(signal)),
+            Poll::Ready(Err(error)) => {
+                match self.listener.handle_error(error) {
+                    Poll::Ready(()) => {
+                        self.done = true;
+                        Poll::Ready(None)
+                    }
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl FusedStream for SignalSetStream {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+// SAFETY: Each listener tracks caught signals separately, and keeps those not
+// yet yielded within itself.
+unsafe impl CancelSafe for SignalSetStream {}
+
+impl SignalSetStream {
+    /// Registers a handler for `signals` and returns a stream over them.
+    pub fn register(signals: SignalSet) -> io::Result<Self> {
+        let listener = Listener::register(signals)?;
+        Ok(Self {
+            listener,
+            done: false,
+            closed: false,
+            target: None,
+        })
+    }
+
+    /// Returns the signals that `self` is registered for.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.listener.signals()
+    }
+
+    /// Stops receiving signals, after which `self` yields the signals received
+    /// before closing and then ends.
+    ///
+    /// This restores the previous action of each signal that has no other
+    /// listeners. Closing `self` again does nothing.
+    pub fn close(&mut self) -> io::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        self.closed = true;
+        self.listener.close()
+    }
+
+    /// Returns whether `self` was [closed](#method.close).
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns `self` with `policy` used for handling internal errors instead
+    /// of the [global policy](../once/signal/enum.ErrorPolicy.html#method.global).
+    #[inline]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.listener.set_error_policy(policy);
+        self
+    }
+
+    /// Returns `self` with received signals waking `target` rather than the
+    /// task that last polled it.
+    ///
+    /// This delivers wakeups to a chosen task, such as one on a specific
+    /// runtime worker or a `!Send` task on a local executor that owns state
+    /// like a terminal UI, even when `self` is polled from elsewhere.
+    #[inline]
+    pub fn with_target(mut self, target: Waker) -> Self {
+        self.set_target(Some(target));
+        self
+    }
+
+    /// Sets the waker notified of received signals, or `None` to notify the
+    /// task that last polled `self`.
+    ///
+    /// The new target takes effect upon the next poll.
+    #[inline]
+    pub fn set_target(&mut self, target: Option<Waker>) {
+        self.target = target;
+    }
+
+    /// Returns the waker notified of received signals, if set via
+    /// [`with_target`](#method.with_target).
+    #[inline]
+    pub fn target(&self) -> Option<&Waker> {
+        self.target.as_ref()
+    }
+
+    /// Takes the internal error that ended `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: ../once/signal/enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.listener.take_error()
+    }
+}
+
+/// A stream that yields each time a [`Signal`] is received.
+///
+/// See [`SignalSetStream`] for more info.
+///
+/// [`Signal`]:          ../signal/enum.Signal.html
+/// [`SignalSetStream`]: struct.SignalSetStream.html
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct SignalStream {
+    signal: Signal,
+    inner: SignalSetStream,
+}
+
+impl Stream for SignalStream {
+    type Item = Signal;
+
+    #[inline]
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Signal>> {
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
+
+impl FusedStream for SignalStream {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
+
+// SAFETY: This only wraps `SignalSetStream`.
+unsafe impl CancelSafe for SignalStream {}
+
+impl From<SignalStream> for SignalSetStream {
+    #[inline]
+    fn from(stream: SignalStream) -> Self {
+        stream.inner
+    }
+}
+
+impl SignalStream {
+    /// Registers a handler for `signal` and returns a stream over it.
+    pub fn register(signal: Signal) -> io::Result<Self> {
+        let inner = SignalSetStream::register(signal.into())?;
+        Ok(Self { signal, inner })
+    }
+
+    /// Returns the signal that `self` is registered for.
+    #[inline]
+    pub fn signal(&self) -> Signal {
+        self.signal
+    }
+
+    /// Stops receiving signals, after which `self` yields the signals received
+    /// before closing and then ends.
+    ///
+    /// See [`SignalSetStream::close`] for more info.
+    ///
+    /// [`SignalSetStream::close`]: struct.SignalSetStream.html#method.close
+    #[inline]
+    pub fn close(&mut self) -> io::Result<()> {
+        self.inner.close()
+    }
+
+    /// Returns whether `self` was [closed](#method.close).
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Returns `self` with `policy` used for handling internal errors instead
+    /// of the [global policy](../once/signal/enum.ErrorPolicy.html#method.global).
+    #[inline]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.inner = self.inner.with_error_policy(policy);
+        self
+    }
+
+    /// Returns `self` with received signals waking `target` rather than the
+    /// task that last polled it.
+    ///
+    /// See [`SignalSetStream::with_target`] for more info.
+    ///
+    /// [`SignalSetStream::with_target`]: struct.SignalSetStream.html#method.with_target
+    #[inline]
+    pub fn with_target(mut self, target: Waker) -> Self {
+        self.inner.set_target(Some(target));
+        self
+    }
+
+    /// Sets the waker notified of received signals, or `None` to notify the
+    /// task that last polled `self`.
+    #[inline]
+    pub fn set_target(&mut self, target: Option<Waker>) {
+        self.inner.set_target(target);
+    }
+
+    /// Takes the internal error that ended `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: ../once/signal/enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.inner.take_error()
+    }
+}
+
+/// A stream that yields details about who sent each [`Signal`] received in a
+/// [`SignalSet`].
+///
+/// This behaves like [`SignalSetStream`], except that handlers are installed
+/// with `SA_SIGINFO` for recording each sender. Since signals received in
+/// between polls are coalesced, each [`SignalInfo`] describes the most recent
+/// delivery of its signal.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{stream::SignalInfoStream, SignalSet};
+/// use futures_core::Stream;
+/// use std::{future::poll_fn, pin::Pin};
+///
+/// # async fn example() -> std::io::Result<()> {
+/// let mut stream = SignalInfoStream::register(SignalSet::new().user_def_1())?;
+///
+/// while let Some(info) =
+///     poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await
+/// {
+///     println!("sent by UID {}", info.uid());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Signal`]:          ../signal/enum.Signal.html
+/// [`SignalSet`]:       ../signal/struct.SignalSet.html
+/// [`SignalSetStream`]: struct.SignalSetStream.html
+/// [`SignalInfo`]:      ../once/signal/struct.SignalInfo.html
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct SignalInfoStream {
+    inner: SignalSetStream,
+}
+
+impl Stream for SignalInfoStream {
+    type Item = SignalInfo;
+
+    #[inline]
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<SignalInfo>> {
+        Pin::new(&mut self.inner)
+            .poll_next(cx)
+            .map(|signal| signal.map(SignalInfo::last))
+    }
+}
+
+impl FusedStream for SignalInfoStream {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
+
+// SAFETY: This only wraps `SignalSetStream`.
+unsafe impl CancelSafe for SignalInfoStream {}
+
+impl SignalInfoStream {
+    /// Registers a handler for `signals` that records each sender and returns
+    /// a stream over them.
+    pub fn register(signals: SignalSet) -> io::Result<Self> {
+        let listener = Listener::register_with_info(signals)?;
+        Ok(Self {
+            inner: SignalSetStream {
+                listener,
+                done: false,
+                closed: false,
+                target: None,
+            },
+        })
+    }
+
+    /// Registers a handler for `signals` like [`register`], also recording the
+    /// wall-clock time of each delivery.
+    ///
+    /// See [`SignalInfo`](../once/signal/struct.SignalInfo.html#wall-clock-time)
+    /// for the trade-offs of this time.
+    ///
+    /// [`register`]: #method.register
+    pub fn register_timestamped(signals: SignalSet) -> io::Result<Self> {
+        let stream = Self::register(signals)?;
+        crate::once::signal::record_wall_clock(signals);
+        Ok(stream)
+    }
+
+    /// Returns the signals that `self` is registered for.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.inner.signals()
+    }
+
+    /// Stops receiving signals, after which `self` yields the signals received
+    /// before closing and then ends.
+    ///
+    /// See [`SignalSetStream::close`] for more info.
+    ///
+    /// [`SignalSetStream::close`]: struct.SignalSetStream.html#method.close
+    #[inline]
+    pub fn close(&mut self) -> io::Result<()> {
+        self.inner.close()
+    }
+
+    /// Returns whether `self` was [closed](#method.close).
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Returns `self` with `policy` used for handling internal errors instead
+    /// of the [global policy](../once/signal/enum.ErrorPolicy.html#method.global).
+    #[inline]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.inner = self.inner.with_error_policy(policy);
+        self
+    }
+
+    /// Takes the internal error that ended `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: ../once/signal/enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.inner.take_error()
+    }
+}
+
+/// A stream that yields each time a [`RealtimeSignal`] in a
+/// [`RealtimeSignalSet`] is received.
+///
+/// Unlike [`SignalSetStream`], deliveries are not coalesced: each one is
+/// yielded in the order received, along with its `sigval` payload.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::{
+///     signal::{RealtimeSignal, RealtimeSignalSet},
+///     stream::RealtimeSignalStream,
+/// };
+/// use futures_core::Stream;
+/// use std::{future::poll_fn, pin::Pin};
+///
+/// # async fn example() -> std::io::Result<()> {
+/// let signals = RealtimeSignalSet::from(RealtimeSignal::min());
+/// let mut stream = RealtimeSignalStream::register(signals)?;
+///
+/// while let Some(event) =
+///     poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await
+/// {
+///     println!("job {:?} finished", event.value());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`RealtimeSignal`]:    ../signal/struct.RealtimeSignal.html
+/// [`RealtimeSignalSet`]: ../signal/struct.RealtimeSignalSet.html
+/// [`SignalSetStream`]:   struct.SignalSetStream.html
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg_attr(docsrs, doc(cfg(any(target_os = "linux", target_os = "android"))))]
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct RealtimeSignalStream {
+    listener: RealtimeListener,
+    done: bool,
+    closed: bool,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Stream for RealtimeSignalStream {
+    type Item = RealtimeEvent;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<RealtimeEvent>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        let result = if self.closed {
+            match self.listener.read_pending() {
+                Ok(Some(event)) => Poll::Ready(Ok(event)),
+                Ok(None) => {
+                    self.done = true;
+                    return Poll::Ready(None);
+                }
+                Err(error) => Poll::Ready(Err(error)),
+            }
+        } else {
+            self.listener.poll_next(cx)
+        };
+
+        match result {
+            Poll::Ready(Ok(event)) => Poll::Ready(Some(event)),
+            Poll::Ready(Err(error)) => {
+                match self.listener.handle_error(error) {
+                    Poll::Ready(()) => {
+                        self.done = true;
+                        Poll::Ready(None)
+                    }
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl FusedStream for RealtimeSignalStream {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+// SAFETY: Each listener receives its own copy of every delivery, and only
+// reads one in the same poll that yields it.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+unsafe impl CancelSafe for RealtimeSignalStream {}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl RealtimeSignalStream {
+    /// Registers a handler for `signals` and returns a stream over them.
+    pub fn register(signals: RealtimeSignalSet) -> io::Result<Self> {
+        Ok(Self {
+            listener: RealtimeListener::register(signals)?,
+            done: false,
+            closed: false,
+        })
+    }
+
+    /// Returns the signals that `self` is registered for.
+    #[inline]
+    pub fn signals(&self) -> RealtimeSignalSet {
+        self.listener.signals()
+    }
+
+    /// Stops receiving signals, after which `self` yields the deliveries
+    /// received before closing and then ends.
+    ///
+    /// This restores the previous action of each signal that has no other
+    /// listeners. Closing `self` again does nothing.
+    pub fn close(&mut self) -> io::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        self.closed = true;
+        self.listener.close()
+    }
+
+    /// Returns whether `self` was [closed](#method.close).
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns `self` with `policy` used for handling internal errors instead
+    /// of the [global policy](../once/signal/enum.ErrorPolicy.html#method.global).
+    #[inline]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.listener.set_error_policy(policy);
+        self
+    }
+
+    /// Takes the internal error that ended `self`, if any.
+    ///
+    /// This is only ever `Some` under [`ErrorPolicy::ResolveWithError`].
+    ///
+    /// [`ErrorPolicy::ResolveWithError`]: ../once/signal/enum.ErrorPolicy.html#variant.ResolveWithError
+    #[inline]
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.listener.take_error()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::future::poll_fn;
+
+    fn next<S: Stream + Unpin>(stream: &mut S) -> Poll<Option<S::Item>> {
+        test_util::poll(&mut poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)))
+    }
+
+    #[test]
+    fn set_stream() {
+        let signal = Signal::IllInstr;
+
+        test_util::run_exclusive(|| {
+            let mut a = SignalSetStream::register(signal.into()).unwrap();
+            let mut b = SignalSetStream::register(signal.into()).unwrap();
+            assert!(next(&mut a).is_pending());
+
+            signal.raise().unwrap();
+            assert_eq!(next(&mut a), Poll::Ready(Some(signal)));
+            assert!(next(&mut a).is_pending());
+
+            // Each stream observes every delivery regardless of the others.
+            signal.raise().unwrap();
+            assert_eq!(next(&mut a), Poll::Ready(Some(signal)));
+            assert_eq!(next(&mut b), Poll::Ready(Some(signal)));
+            assert!(next(&mut b).is_pending());
+
+            // Closing keeps signals received before it.
+            signal.raise().unwrap();
+            a.close().unwrap();
+            assert!(a.is_closed() && !a.is_terminated());
+            assert_eq!(next(&mut a), Poll::Ready(Some(signal)));
+            assert_eq!(next(&mut a), Poll::Ready(None));
+            assert!(a.is_terminated());
+            assert_eq!(next(&mut a), Poll::Ready(None));
+
+            assert_eq!(next(&mut b), Poll::Ready(Some(signal)));
+        });
+    }
+
+    #[test]
+    fn fused_until_ended() {
+        let signal = Signal::IllInstr;
+
+        test_util::run_exclusive(|| {
+            let mut stream = SignalSetStream::register(signal.into()).unwrap();
+            assert!(next(&mut stream).is_pending());
+            assert!(!stream.is_terminated());
+
+            // Keeps the signal handled once `stream` is closed.
+            let _handled = SignalStream::register(signal).unwrap();
+
+            signal.raise().unwrap();
+            assert_eq!(next(&mut stream), Poll::Ready(Some(signal)));
+            assert!(!stream.is_terminated());
+
+            // Once ended, the stream stays ended even upon more deliveries.
+            stream.close().unwrap();
+            assert_eq!(next(&mut stream), Poll::Ready(None));
+            assert!(stream.is_terminated());
+
+            signal.raise().unwrap();
+            assert_eq!(next(&mut stream), Poll::Ready(None));
+            assert!(stream.is_terminated());
+        });
+    }
+
+    #[test]
+    fn signal_stream() {
+        let signal = Signal::TermStop;
+
+        test_util::run_exclusive(|| {
+            let mut stream = SignalStream::register(signal).unwrap();
+            assert_eq!(stream.signal(), signal);
+            assert!(next(&mut stream).is_pending());
+
+            // Deliveries in between polls are coalesced.
+            signal.raise().unwrap();
+            signal.raise().unwrap();
+            assert_eq!(next(&mut stream), Poll::Ready(Some(signal)));
+            assert!(next(&mut stream).is_pending());
+
+            stream.close().unwrap();
+            assert_eq!(next(&mut stream), Poll::Ready(None));
+            assert!(stream.is_terminated());
+        });
+    }
+
+    #[test]
+    fn info_stream() {
+        let signal = Signal::Abort;
+
+        test_util::run(|| {
+            let mut stream = SignalInfoStream::register(signal.into()).unwrap();
+            assert!(next(&mut stream).is_pending());
+
+            signal.raise().unwrap();
+            let info = match next(&mut stream) {
+                Poll::Ready(Some(info)) => info,
+                poll => panic!("unexpected poll: {:?}", poll),
+            };
+            assert_eq!(info.signal(), signal);
+            assert_eq!(info.pid(), unsafe { libc::getpid() });
+
+            stream.close().unwrap();
+            assert!(next(&mut stream).is_ready());
+            assert!(stream.is_terminated());
+        });
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn realtime_stream() {
+        use crate::signal::RealtimeSignal;
+
+        let signal = RealtimeSignal::new(1).unwrap();
+        let pid = unsafe { libc::getpid() };
+
+        async fn values(
+            stream: &mut RealtimeSignalStream,
+        ) -> Option<Option<usize>> {
+            poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx))
+                .await
+                .map(|event| event.value())
+        }
+
+        test_util::block_on(async {
+            let mut a = RealtimeSignalStream::register(signal.into()).unwrap();
+            let mut b = RealtimeSignalStream::register(signal.into()).unwrap();
+
+            // Each stream receives its own copy of every delivery.
+            signal.queue(pid, 1).unwrap();
+            signal.queue(pid, 2).unwrap();
+            for stream in [&mut a, &mut b] {
+                assert_eq!(values(stream).await, Some(Some(1)));
+                assert_eq!(values(stream).await, Some(Some(2)));
+            }
+
+            a.close().unwrap();
+            assert_eq!(values(&mut a).await, None);
+            assert!(a.is_terminated());
+        });
+    }
+}
@@ -0,0 +1,338 @@
+//! Terminal state restoration upon fatal signals.
+//!
+//! Programs that put the terminal into raw mode or an alternate screen leave
+//! it unusable if they crash without undoing that. [`TerminalRestore`] installs
+//! handlers for fatal signals that restore the terminal before letting the
+//! signal take its course.
+//!
+//! [`TerminalRestore`]: struct.TerminalRestore.html
+
+use std::{
+    io, mem,
+    os::unix::io::RawFd,
+    ptr,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{
+    signal::{Signal, SignalArray},
+    SignalSet,
+};
+
+/// The state used by the signal handler, or null if not installed.
+///
+/// Replaced states are leaked, since a handler may be using them at any time.
+static STATE: AtomicPtr<State> = AtomicPtr::new(ptr::null_mut());
+
+/// Serializes installing and uninstalling.
+static LOCK: Mutex<()> = Mutex::new(());
+
+struct State {
+    tty: RawFd,
+    sequence: Box<[u8]>,
+    termios: Option<libc::termios>,
+    signals: SignalSet,
+    /// The actions replaced by our handler, to which signals are forwarded.
+    previous: SignalArray<Option<libc::sigaction>>,
+}
+
+/// A process-wide action that restores the terminal when the process receives
+/// a fatal signal.
+///
+/// Upon a signal, the escape sequence is written to the terminal and the
+/// termios snapshot is applied. Then the action that was in place before
+/// installing is restored and the signal is raised again. Only async-signal-safe
+/// operations are performed, since everything is prepared ahead of time.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::terminal::TerminalRestore;
+///
+/// // Leave the alternate screen and show the cursor.
+/// TerminalRestore::new(libc::STDOUT_FILENO)
+///     .with_sequence(&b"\x1b[?1049l\x1b[?25h"[..])
+///     .install()?;
+///
+/// // Enter raw mode...
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct TerminalRestore {
+    tty: RawFd,
+    sequence: Vec<u8>,
+    termios: bool,
+    signals: SignalSet,
+}
+
+impl TerminalRestore {
+    /// Creates an action that restores the terminal `tty`.
+    ///
+    /// By default, the action snapshots the termios settings of `tty` when
+    /// installed, writes no escape sequence, and is triggered by signals whose
+    /// default action is to dump core: `SIGABRT`, `SIGBUS`, `SIGFPE`, `SIGILL`,
+    /// `SIGQUIT`, `SIGSEGV`, `SIGSYS`, and `SIGTRAP`.
+    pub fn new(tty: RawFd) -> Self {
+        let mut signals = SignalSet::new();
+        for signal in SignalSet::all() {
+            let fatal = matches!(
+                signal.into_raw(),
+                libc::SIGABRT
+                    | libc::SIGBUS
+                    | libc::SIGFPE
+                    | libc::SIGILL
+                    | libc::SIGQUIT
+                    | libc::SIGSEGV
+                    | libc::SIGSYS
+                    | libc::SIGTRAP
+            );
+            if fatal {
+                signals.insert(signal);
+            }
+        }
+
+        Self {
+            tty,
+            sequence: Vec::new(),
+            termios: true,
+            signals,
+        }
+    }
+
+    /// Returns `self` with `sequence` written to the terminal upon a signal,
+    /// such as to leave the alternate screen or show the cursor.
+    #[inline]
+    pub fn with_sequence(mut self, sequence: impl Into<Vec<u8>>) -> Self {
+        self.sequence = sequence.into();
+        self
+    }
+
+    /// Returns `self` with whether to snapshot and restore termios settings.
+    #[inline]
+    pub fn with_termios(mut self, termios: bool) -> Self {
+        self.termios = termios;
+        self
+    }
+
+    /// Returns `self` triggered by `signals` instead of the defaults.
+    ///
+    /// Signals that are also registered through this crate should be left out,
+    /// since installing replaces their handlers.
+    #[inline]
+    pub fn with_signals(mut self, signals: SignalSet) -> Self {
+        self.signals = signals;
+        self
+    }
+
+    /// Installs `self` as the process-wide action, replacing any previous one.
+    ///
+    /// This should be called before changing the terminal's settings, since
+    /// that's when the termios snapshot is taken.
+    pub fn install(self) -> io::Result<()> {
+        let _lock = LOCK.lock().unwrap_or_else(|error| error.into_inner());
+
+        let termios = if self.termios {
+            let mut termios = mem::MaybeUninit::<libc::termios>::uninit();
+            if unsafe { libc::tcgetattr(self.tty, termios.as_mut_ptr()) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Some(unsafe { termios.assume_init() })
+        } else {
+            None
+        };
+
+        // Keep forwarding to the original actions if already installed.
+        let mut previous =
+            match unsafe { STATE.load(Ordering::SeqCst).as_ref() } {
+                Some(state) => state.previous,
+                None => [None; Signal::NUM],
+            };
+
+        for signal in self.signals {
+            if previous[signal as usize].is_some() {
+                continue;
+            }
+            let mut action: libc::sigaction = unsafe { mem::zeroed() };
+            let raw_signal = signal.into_raw();
+            if unsafe { libc::sigaction(raw_signal, ptr::null(), &mut action) }
+                != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+            previous[signal as usize] = Some(action);
+        }
+
+        let state = Box::new(State {
+            tty: self.tty,
+            sequence: self.sequence.into_boxed_slice(),
+            termios,
+            signals: self.signals,
+            previous,
+        });
+        let old = STATE.swap(Box::into_raw(state), Ordering::SeqCst);
+
+        // Stop handling signals that are no longer in the set.
+        if let Some(old) = unsafe { old.as_ref() } {
+            restore_actions(old, old.signals.without_all(self.signals));
+        }
+
+        for signal in self.signals {
+            install_handler(signal)?;
+        }
+
+        Ok(())
+    }
+
+    /// Uninstalls the process-wide action, if any, restoring the actions that
+    /// were in place before it was installed.
+    pub fn uninstall() {
+        let _lock = LOCK.lock().unwrap_or_else(|error| error.into_inner());
+
+        let old = STATE.swap(ptr::null_mut(), Ordering::SeqCst);
+        if let Some(old) = unsafe { old.as_ref() } {
+            restore_actions(old, old.signals);
+        }
+    }
+}
+
+/// Returns `true` if an installed action restores termios settings.
+pub(crate) fn restores_termios() -> bool {
+    match unsafe { STATE.load(Ordering::SeqCst).as_ref() } {
+        Some(state) => state.termios.is_some(),
+        None => false,
+    }
+}
+
+fn restore_actions(state: &State, signals: SignalSet) {
+    for signal in signals {
+        if let Some(action) = &state.previous[signal as usize] {
+            unsafe {
+                libc::sigaction(signal.into_raw(), action, ptr::null_mut());
+            }
+        }
+    }
+}
+
+fn install_handler(signal: Signal) -> io::Result<()> {
+    extern "C" fn signal_handler(raw_signal: libc::c_int) {
+        let state = match unsafe { STATE.load(Ordering::SeqCst).as_ref() } {
+            Some(state) => state,
+            None => return,
+        };
+
+        if !state.sequence.is_empty() {
+            unsafe {
+                libc::write(
+                    state.tty,
+                    state.sequence.as_ptr() as *const _,
+                    state.sequence.len(),
+                );
+            }
+        }
+
+        if let Some(termios) = &state.termios {
+            unsafe {
+                libc::tcsetattr(state.tty, libc::TCSANOW, termios);
+            }
+        }
+
+        // Forward to the previous action. For faults, returning re-executes
+        // the faulting instruction, which is then handled by that action.
+        let previous = Signal::from_raw(raw_signal)
+            .and_then(|signal| state.previous[signal as usize]);
+        unsafe {
+            match previous {
+                Some(action) => {
+                    libc::sigaction(raw_signal, &action, ptr::null_mut());
+                }
+                None => {
+                    libc::signal(raw_signal, libc::SIG_DFL);
+                }
+            }
+            libc::raise(raw_signal);
+        }
+    }
+
+    // See `once::signal::register_signal` for why this union is used.
+    let action = {
+        #[allow(non_camel_case_types)]
+        union sigaction {
+            sa_handler: Option<extern "C" fn(signal: libc::c_int)>,
+            libc: libc::sigaction,
+        }
+
+        unsafe {
+            let mut action: sigaction = mem::zeroed();
+            action.sa_handler = Some(signal_handler);
+            action.libc.sa_flags = libc::SA_NODEFER;
+            action.libc
+        }
+    };
+
+    match unsafe {
+        libc::sigaction(signal.into_raw(), &action, ptr::null_mut())
+    } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+#[cfg(all(test, feature = "once-core"))]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::sync::atomic::AtomicBool;
+
+    #[test]
+    fn restores_then_forwards() {
+        static FORWARDED: AtomicBool = AtomicBool::new(false);
+
+        extern "C" fn previous(_: libc::c_int) {
+            FORWARDED.store(true, Ordering::SeqCst);
+        }
+
+        let signal = Signal::Alarm;
+        let raw_signal = signal.into_raw();
+
+        test_util::run_exclusive(|| {
+            let mut fds = [0; 2];
+            assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+            let [reader, writer] = fds;
+
+            let mut original: libc::sigaction = unsafe { mem::zeroed() };
+            let mut action: libc::sigaction = unsafe { mem::zeroed() };
+            action.sa_sigaction = previous as *const () as usize;
+            unsafe { libc::sigaction(raw_signal, &action, &mut original) };
+
+            TerminalRestore::new(writer)
+                .with_sequence(&b"restore"[..])
+                .with_termios(false)
+                .with_signals(signal.into())
+                .install()
+                .unwrap();
+            signal.raise().unwrap();
+
+            let mut buf = [0u8; 16];
+            let n = unsafe {
+                libc::read(reader, buf.as_mut_ptr() as *mut _, buf.len())
+            };
+            assert_eq!(&buf[..n as usize], b"restore");
+            assert!(FORWARDED.load(Ordering::SeqCst));
+
+            // The previous action stays in place after forwarding.
+            let mut current: libc::sigaction = unsafe { mem::zeroed() };
+            unsafe { libc::sigaction(raw_signal, ptr::null(), &mut current) };
+            assert_eq!(current.sa_sigaction, action.sa_sigaction);
+
+            TerminalRestore::uninstall();
+            unsafe {
+                libc::sigaction(raw_signal, &original, ptr::null_mut());
+                libc::close(reader);
+                libc::close(writer);
+            }
+        });
+    }
+}
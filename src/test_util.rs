@@ -0,0 +1,117 @@
+//! Helpers shared by tests that raise signals and wait on their delivery.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, RwLock, RwLockReadGuard},
+    task::{Context, Poll, Wake, Waker},
+};
+
+/// Serializes tests that pause delivery, which is process-wide, with those
+/// that wait on it.
+static DELIVERY: RwLock<()> = RwLock::new(());
+
+/// Serializes tests that wait on their own children with those that reap every
+/// child of the process.
+static CHILDREN: RwLock<()> = RwLock::new(());
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Keeps other tests from pausing delivery until dropped.
+pub fn delivery() -> RwLockReadGuard<'static, ()> {
+    DELIVERY.read().unwrap_or_else(|error| error.into_inner())
+}
+
+/// Runs `f` while delivery is not paused by another test.
+///
+/// Polling requires a reactor when using tokio, so `f` is run within one.
+pub fn run<R>(f: impl FnOnce() -> R) -> R {
+    let _delivery = delivery();
+    in_reactor(f)
+}
+
+/// Runs `f` while no other test waits on delivery, so that it may pause it or
+/// raise signals that other tests handle.
+pub fn run_exclusive<R>(f: impl FnOnce() -> R) -> R {
+    let _delivery = DELIVERY.write().unwrap_or_else(|error| error.into_inner());
+    in_reactor(f)
+}
+
+/// Keeps other tests from reaping the children of the current test until
+/// dropped.
+///
+/// This must be taken before any delivery lock.
+pub fn children() -> RwLockReadGuard<'static, ()> {
+    CHILDREN.read().unwrap_or_else(|error| error.into_inner())
+}
+
+/// Runs `f` like [`run_exclusive`], while no other test waits on its own
+/// children, so that it may reap any child.
+pub fn run_reaping<R>(f: impl FnOnce() -> R) -> R {
+    let _children = CHILDREN.write().unwrap_or_else(|error| error.into_inner());
+    run_exclusive(f)
+}
+
+fn in_reactor<R>(f: impl FnOnce() -> R) -> R {
+    #[cfg(feature = "once-tokio")]
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+        let _guard = runtime.enter();
+        f()
+    }
+
+    #[cfg(not(feature = "once-tokio"))]
+    f()
+}
+
+/// Polls `future` once with a waker that does nothing.
+pub fn poll<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    Pin::new(future).poll(&mut Context::from_waker(&waker))
+}
+
+/// Runs `future` to completion while delivery is not paused by another test.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let _delivery = delivery();
+
+    // Only a running reactor drives readiness when using tokio.
+    #[cfg(feature = "once-tokio")]
+    {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[cfg(not(feature = "once-tokio"))]
+    {
+        use std::thread::{self, Thread};
+
+        struct ThreadWaker(Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    mem, ptr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+/// Set by the handler upon receiving the trap.
+static RECEIVED: AtomicBool = AtomicBool::new(false);
+
+/// Serializes probes, since they share `RECEIVED` and the process-wide action.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Returns `true` if a debugger appears to be attached to the current process.
+///
+/// This briefly installs a `SIGTRAP` handler and raises the signal. Debuggers
+/// typically intercept traps and don't pass them on, in which case the handler
+/// never runs. The previous action for `SIGTRAP` is restored afterwards.
+///
+/// This is a heuristic: a debugger configured to pass `SIGTRAP` through will
+/// not be detected. It is useful for e.g. not installing a [`ForceExit`]
+/// policy while debugging, since breakpoints would otherwise count towards it.
+///
+/// If a debugger is attached, it will stop the process at the trap, just like
+/// at a breakpoint.
+///
+/// [`ForceExit`]: ../struct.ForceExit.html
+pub fn debugger_attached() -> bool {
+    extern "C" fn signal_handler(_: libc::c_int) {
+        RECEIVED.store(true, Ordering::SeqCst);
+    }
+
+    let _lock = LOCK.lock().unwrap_or_else(|error| error.into_inner());
+
+    // Keep handlers of this crate from being installed or released while
+    // the action is swapped.
+    #[cfg(feature = "once-core")]
+    let _registry = crate::once::signal::lock_registry();
+
+    // See `once::signal::register_signal` for why this union is used.
+    let action = {
+        #[allow(non_camel_case_types)]
+        union sigaction {
+            sa_handler: Option<extern "C" fn(signal: libc::c_int)>,
+            libc: libc::sigaction,
+        }
+
+        unsafe {
+            let mut action: sigaction = mem::zeroed();
+            action.sa_handler = Some(signal_handler);
+            action.libc
+        }
+    };
+
+    let mut old_action: libc::sigaction = unsafe { mem::zeroed() };
+    if unsafe { libc::sigaction(libc::SIGTRAP, &action, &mut old_action) } != 0
+    {
+        // Without our handler, raising would take the default action.
+        return false;
+    }
+
+    RECEIVED.store(false, Ordering::SeqCst);
+
+    // The handler runs on this thread before `raise` returns.
+    unsafe {
+        libc::raise(libc::SIGTRAP);
+        libc::sigaction(libc::SIGTRAP, &old_action, ptr::null_mut());
+    }
+
+    !RECEIVED.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn not_attached() {
+        // Other tests handle and raise `SIGTRAP`.
+        #[cfg(feature = "once-core")]
+        crate::test_util::run_exclusive(|| {
+            assert!(!super::debugger_attached());
+        });
+
+        #[cfg(not(feature = "once-core"))]
+        assert!(!super::debugger_attached());
+    }
+}
@@ -0,0 +1,240 @@
+use std::{fmt, io, mem, ptr};
+
+use crate::signal::{Signal, SignalArray};
+
+/// A snapshot of the action for every [`Signal`], which can be restored later.
+///
+/// This allows for reliably putting global signal state back after mutating
+/// it, such as in test frameworks or when embedding code that installs its own
+/// handlers.
+///
+/// Handlers registered through this crate are part of the snapshot. Restoring
+/// also restores the crate's record of which signals are registered, so later
+/// registrations behave as they would have at the time of capture. Signals
+/// that have listeners registered through this crate when restoring are left
+/// as they are, since those listeners rely on the current handler.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::unix::DispositionSnapshot;
+///
+/// let snapshot = DispositionSnapshot::capture()?;
+///
+/// // Run code that changes signal handlers...
+///
+/// snapshot.restore()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`Signal`]: ../signal/enum.Signal.html
+#[derive(Clone)]
+pub struct DispositionSnapshot {
+    actions: SignalArray<libc::sigaction>,
+    #[cfg(feature = "once-core")]
+    registered: crate::SignalSet,
+    #[cfg(feature = "once-core")]
+    with_info: crate::SignalSet,
+    #[cfg(feature = "once-core")]
+    previous: SignalArray<Option<libc::sigaction>>,
+}
+
+impl fmt::Debug for DispositionSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DispositionSnapshot")
+            .finish_non_exhaustive()
+    }
+}
+
+impl DispositionSnapshot {
+    /// Captures the current action for every signal.
+    pub fn capture() -> io::Result<Self> {
+        // Keep handlers from being installed or released while capturing.
+        #[cfg(feature = "once-core")]
+        let _registry = crate::once::signal::lock_registry();
+
+        #[cfg(feature = "once-core")]
+        let (registered, with_info, previous) = {
+            use std::sync::atomic::Ordering;
+
+            let table = crate::once::signal::table::Table::global();
+            let previous = table
+                .previous
+                .lock()
+                .unwrap_or_else(|error| error.into_inner());
+            (
+                table.registered.load(Ordering::SeqCst),
+                table.with_info.load(Ordering::SeqCst),
+                *previous,
+            )
+        };
+
+        let mut actions: SignalArray<libc::sigaction> =
+            unsafe { mem::zeroed() };
+
+        for signal in Signal::all() {
+            let action = &mut actions[signal as usize];
+            let raw_signal = signal.into_raw();
+            if unsafe { libc::sigaction(raw_signal, ptr::null(), action) } != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(Self {
+            actions,
+            #[cfg(feature = "once-core")]
+            registered,
+            #[cfg(feature = "once-core")]
+            with_info,
+            #[cfg(feature = "once-core")]
+            previous,
+        })
+    }
+
+    /// Restores the action for every signal to what it was when `self` was
+    /// captured.
+    ///
+    /// If restoring fails for any signal, the remaining signals are still
+    /// restored and the first error is returned.
+    pub fn restore(&self) -> io::Result<()> {
+        // Keep listeners from being added or removed while restoring.
+        #[cfg(feature = "once-core")]
+        let _registry = crate::once::signal::lock_registry();
+
+        let live = live_signals();
+        let mut result = Ok(());
+
+        for signal in Signal::all().without_all(live) {
+            let action = &self.actions[signal as usize];
+            let raw_signal = signal.into_raw();
+            if unsafe { libc::sigaction(raw_signal, action, ptr::null_mut()) }
+                != 0
+                && result.is_ok()
+            {
+                result = Err(io::Error::last_os_error());
+            }
+        }
+
+        #[cfg(feature = "once-core")]
+        {
+            use std::sync::atomic::Ordering;
+
+            let table = crate::once::signal::table::Table::global();
+            let mut previous = table
+                .previous
+                .lock()
+                .unwrap_or_else(|error| error.into_inner());
+
+            for signal in Signal::all().without_all(live) {
+                previous[signal as usize] = self.previous[signal as usize];
+            }
+
+            // The state of live signals stays as is.
+            table.registered.store(
+                merge(
+                    table.registered.load(Ordering::SeqCst),
+                    self.registered,
+                    live,
+                ),
+                Ordering::SeqCst,
+            );
+            table.with_info.store(
+                merge(
+                    table.with_info.load(Ordering::SeqCst),
+                    self.with_info,
+                    live,
+                ),
+                Ordering::SeqCst,
+            );
+        }
+
+        result
+    }
+}
+
+/// Returns the signals that have listeners registered through this crate.
+fn live_signals() -> crate::SignalSet {
+    #[cfg(feature = "once-core")]
+    {
+        use std::sync::atomic::Ordering;
+
+        let table = crate::once::signal::table::Table::global();
+        Signal::all().filter(|signal| {
+            table.entry(signal).listeners.load(Ordering::SeqCst) != 0
+        })
+    }
+
+    #[cfg(not(feature = "once-core"))]
+    crate::SignalSet::new()
+}
+
+/// Returns the signals of `captured`, except that those in `live` are taken
+/// from `current`.
+#[cfg(feature = "once-core")]
+fn merge(
+    current: crate::SignalSet,
+    captured: crate::SignalSet,
+    live: crate::SignalSet,
+) -> crate::SignalSet {
+    captured.without_all(live) | current.intersection(live)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `f` while no other test relies on the actions being restored.
+    fn exclusive(f: impl FnOnce()) {
+        #[cfg(feature = "once-core")]
+        crate::test_util::run_exclusive(f);
+
+        #[cfg(not(feature = "once-core"))]
+        f();
+    }
+
+    fn handler(signal: Signal) -> usize {
+        let mut action: libc::sigaction = unsafe { mem::zeroed() };
+        unsafe { libc::sigaction(signal.into_raw(), ptr::null(), &mut action) };
+        action.sa_sigaction
+    }
+
+    #[test]
+    fn restore() {
+        let signal = Signal::UserDef2;
+
+        exclusive(|| {
+            let snapshot = DispositionSnapshot::capture().unwrap();
+            let original = handler(signal);
+
+            unsafe { libc::signal(signal.into_raw(), libc::SIG_IGN) };
+            assert_eq!(handler(signal), libc::SIG_IGN);
+
+            snapshot.restore().unwrap();
+            assert_eq!(handler(signal), original);
+        });
+    }
+
+    #[cfg(feature = "once-core")]
+    #[test]
+    fn keeps_live_registrations() {
+        let signal = Signal::UserDef2;
+
+        exclusive(|| {
+            let snapshot = DispositionSnapshot::capture().unwrap();
+            let original = handler(signal);
+
+            let once =
+                crate::once::signal::SignalOnce::register(signal).unwrap();
+            let registered = handler(signal);
+            assert_ne!(registered, original);
+
+            snapshot.restore().unwrap();
+            assert_eq!(handler(signal), registered);
+
+            // Releasing the registration still restores the original action.
+            drop(once);
+            assert_eq!(handler(signal), original);
+        });
+    }
+}
@@ -1 +1,58 @@
-pub mod pipe;
+//! Unix-specific functionality.
+//!
+//! Signals here are the portable [`Signal`] and [`SignalSet`] types, which are
+//! re-exported for convenience.
+//!
+//! # OpenBSD `pledge`
+//!
+//! Once registered, signal handling only performs system calls permitted by
+//! the `"stdio"` promise: the handler writes to a pipe, and listeners poll and
+//! read from it. This means a daemon can register its handlers and then call
+//! `pledge("stdio", ...)` (plus whatever else it needs) without signal handling
+//! being killed for a violation.
+//!
+//! Registering new handlers after pledging additionally requires nothing but
+//! `"stdio"`, since `pipe2` and `sigaction` are covered by it.
+//!
+//! The exceptions are:
+//!
+//! - [`TerminalRestore`] with termios restoration enabled, which calls
+//!   `tcsetattr` from within its handler and thus requires `"tty"`.
+//!
+//! - [`process`] and [`jobctl`], which are only called directly and require
+//!   `"proc"` and `"tty"` respectively.
+//!
+//! Use [`pledge_compatible`] to check the current state before pledging.
+//!
+//! [`Signal`]:            ../signal/enum.Signal.html
+//! [`SignalSet`]:         ../signal/struct.SignalSet.html
+//! [`TerminalRestore`]:   ../terminal/struct.TerminalRestore.html
+//! [`process`]:           ../process/index.html
+//! [`jobctl`]:            ../jobctl/index.html
+//! [`pledge_compatible`]: fn.pledge_compatible.html
+
+mod debugger;
+mod disposition;
+#[cfg(feature = "once-core")]
+pub(crate) mod pipe;
+mod signal_kind;
+#[cfg(any(docsrs, feature = "once-core"))]
+mod waitable;
+
+pub use crate::signal::{Signal, SignalSet};
+pub use {
+    debugger::debugger_attached, disposition::DispositionSnapshot,
+    signal_kind::SignalKind,
+};
+
+#[cfg(any(docsrs, feature = "once-core"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+pub use waitable::Waitable;
+
+/// Returns `true` if the signal handling set up so far keeps working under
+/// `pledge("stdio", ...)`.
+///
+/// See [the module documentation](index.html#openbsd-pledge) for details.
+pub fn pledge_compatible() -> bool {
+    !crate::terminal::restores_termios()
+}
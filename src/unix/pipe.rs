@@ -1,5 +1,9 @@
-use mio::{unix::EventedFd, Evented};
-use std::{io, mem::MaybeUninit, os::unix::io::RawFd};
+use std::{
+    io,
+    mem::MaybeUninit,
+    os::unix::io::{AsRawFd, RawFd},
+    time::Duration,
+};
 
 /// A pipe suitable for signal handling.
 ///
@@ -11,7 +15,7 @@ pub(crate) fn pipe() -> io::Result<(Reader, Writer)> {
 
 /// The sending end of the pipe.
 #[repr(transparent)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub(crate) struct Writer(pub RawFd);
 
 impl Writer {
@@ -35,32 +39,60 @@ impl Writer {
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct Reader(pub RawFd);
 
-impl Evented for Reader {
-    #[inline]
-    fn register(
-        &self,
-        poll: &mio::Poll,
-        token: mio::Token,
-        interest: mio::Ready,
-        opts: mio::PollOpt,
-    ) -> io::Result<()> {
-        EventedFd(&self.0).register(poll, token, interest, opts)
+impl Reader {
+    /// Blocks until the pipe is readable or `timeout` elapses, returning
+    /// whether it is readable.
+    ///
+    /// Interruptions by signals are reported as `false` rather than retried, so
+    /// that the caller can check whether the interrupting signal is relevant.
+    pub fn wait_readable(self, timeout: Option<Duration>) -> io::Result<bool> {
+        let timeout: libc::c_int = match timeout {
+            Some(timeout) => {
+                timeout.as_millis().try_into().unwrap_or(libc::c_int::MAX)
+            }
+            None => -1,
+        };
+
+        let mut fd = libc::pollfd {
+            fd: self.0,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        match unsafe { libc::poll(&mut fd, 1, timeout) } {
+            -1 => {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    Ok(false)
+                } else {
+                    Err(error)
+                }
+            }
+            0 => Ok(false),
+            _ => Ok(true),
+        }
     }
 
-    #[inline]
-    fn reregister(
-        &self,
-        poll: &mio::Poll,
-        token: mio::Token,
-        interest: mio::Ready,
-        opts: mio::PollOpt,
-    ) -> io::Result<()> {
-        EventedFd(&self.0).reregister(poll, token, interest, opts)
+    /// Reads all pending bytes from the pipe without blocking.
+    pub fn drain(self) {
+        let mut buf = [0u8; 64];
+        loop {
+            let len = buf.len();
+            let n =
+                unsafe { libc::read(self.0, buf.as_mut_ptr() as *mut _, len) };
+            if n <= 0 {
+                // Either the pipe is empty or there's no reasonable way to
+                // recover, in which case the next wait reports the error.
+                break;
+            }
+        }
     }
+}
 
+impl AsRawFd for Reader {
     #[inline]
-    fn deregister(&self, poll: &mio::Poll) -> std::io::Result<()> {
-        EventedFd(&self.0).deregister(poll)
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
     }
 }
 
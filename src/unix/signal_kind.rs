@@ -0,0 +1,71 @@
+use libc::c_int;
+
+use crate::Signal;
+
+/// A raw signal number, which may be one not modeled by [`Signal`], such as
+/// `SIGPWR` or a vendor extension.
+///
+/// The caller is responsible for the number being a valid signal on the
+/// target. Invalid numbers fail to register.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asygnal::unix::SignalKind;
+///
+/// # #[cfg(any(target_os = "linux", target_os = "android"))]
+/// # async fn example() -> Result<(), asygnal::once::signal::RegisterOnceError> {
+/// SignalKind::from_raw(libc::SIGPWR).register_once()?.await;
+/// println!("power failure");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Signal`]: ../signal/enum.Signal.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SignalKind(c_int);
+
+impl From<Signal> for SignalKind {
+    #[inline]
+    fn from(signal: Signal) -> Self {
+        Self(signal.into_raw())
+    }
+}
+
+impl SignalKind {
+    /// Creates an instance from a raw signal number.
+    #[inline]
+    pub const fn from_raw(raw_signal: c_int) -> Self {
+        Self(raw_signal)
+    }
+
+    /// Returns the raw signal number.
+    #[inline]
+    pub const fn into_raw(self) -> c_int {
+        self.0
+    }
+
+    /// Returns the [`Signal`] for `self`, if it is modeled by one.
+    ///
+    /// [`Signal`]: ../signal/enum.Signal.html
+    #[inline]
+    pub const fn signal(self) -> Option<Signal> {
+        Signal::from_raw(self.0)
+    }
+
+    /// Registers a signal handler that will only be fulfilled once.
+    ///
+    /// See [`SignalKindOnce`](../once/signal/struct.SignalKindOnce.html) for
+    /// more info.
+    #[cfg(any(docsrs, feature = "once-core"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "once-core")))]
+    #[inline]
+    pub fn register_once(
+        self,
+    ) -> Result<
+        crate::once::signal::SignalKindOnce,
+        crate::once::signal::RegisterOnceError,
+    > {
+        crate::once::signal::SignalKindOnce::register(self)
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+use super::pipe;
+use crate::{
+    once::signal::table::Table,
+    signal::{Signal, SignalArray, SignalSet},
+};
+
+/// A handle for consuming signals from a foreign event loop, such as GLib,
+/// libuv, or libevent.
+///
+/// The file descriptor returned by [`as_raw_fd`] is level-triggered: it stays
+/// readable from when a signal is received until [`ack`] is called. Register it
+/// for read readiness with the event loop, and call [`ack`] from the callback
+/// to take the signals received since the last call.
+///
+/// The handle must outlive its registration with the event loop, since
+/// dropping it closes the file descriptor.
+///
+/// # Examples
+///
+/// With GLib via the `glib` crate:
+///
+/// ```ignore
+/// use asygnal::{unix::Waitable, SignalSet};
+/// use std::os::unix::io::AsRawFd;
+///
+/// let mut waitable = Waitable::register(SignalSet::new().hangup().terminate())?;
+/// let fd = waitable.as_raw_fd();
+///
+/// glib::unix_fd_add_local(fd, glib::IOCondition::IN, move |_, _| {
+///     for signal in waitable.ack() {
+///         println!("received {:?}", signal);
+///     }
+///     glib::ControlFlow::Continue
+/// });
+/// ```
+///
+/// From C with libuv, given the file descriptor and a binding for [`ack`]:
+///
+/// ```c
+/// static void on_signal(uv_poll_t *handle, int status, int events) {
+///     uint32_t mask = waitable_ack(handle->data);
+///     /* ... */
+/// }
+///
+/// uv_poll_init(loop, &poll, waitable_fd);
+/// uv_poll_start(&poll, UV_READABLE, on_signal);
+/// ```
+///
+/// [`as_raw_fd`]: #method.as_raw_fd
+/// [`ack`]:       #method.ack
+#[derive(Debug)]
+pub struct Waitable {
+    signals: SignalSet,
+    reader: pipe::Reader,
+    writer: pipe::Writer,
+    /// The catch counts last observed for each signal.
+    counts: SignalArray<usize>,
+}
+
+impl Drop for Waitable {
+    fn drop(&mut self) {
+        // There's no reasonable way to report failing to restore an action.
+        let _ = crate::once::signal::unregister_set(self.signals, self.writer);
+
+        unsafe {
+            libc::close(self.reader.0);
+            libc::close(self.writer.0);
+        }
+    }
+}
+
+impl AsRawFd for Waitable {
+    /// Returns the file descriptor that is readable while signals are pending.
+    ///
+    /// It must not be read from or closed.
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.reader.0
+    }
+}
+
+impl Waitable {
+    /// Registers a handler for `signals`.
+    pub fn register(signals: SignalSet) -> io::Result<Self> {
+        let (reader, writer) = pipe::pipe()?;
+
+        let mut waitable = Self {
+            signals,
+            reader,
+            writer,
+            counts: [0; Signal::NUM],
+        };
+
+        // Signals caught before registering are not returned.
+        Table::global().take_caught(signals, &mut waitable.counts);
+
+        // Dropping `waitable` upon failure closes the pipe.
+        crate::once::signal::register_set(signals, writer)?;
+        Ok(waitable)
+    }
+
+    /// Returns the signals handled by `self`.
+    #[inline]
+    pub fn signals(&self) -> SignalSet {
+        self.signals
+    }
+
+    /// Acknowledges readiness, returning the signals received since the last
+    /// call.
+    ///
+    /// The file descriptor stops being readable until the next signal is
+    /// received. While delivery is [paused], this returns no signals.
+    ///
+    /// [paused]: ../fn.pause.html
+    pub fn ack(&mut self) -> SignalSet {
+        let table = Table::global();
+
+        // Drain before taking so that a signal caught in between leaves the
+        // pipe readable.
+        table.drain(self.reader, self.writer);
+
+        if table.is_paused() {
+            // `resume` writes to the pipe if a relevant signal was caught.
+            return SignalSet::new();
+        }
+
+        table.take_caught(self.signals, &mut self.counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::time::Duration;
+
+    #[test]
+    fn level_triggered() {
+        let _delivery = test_util::delivery();
+        let signal = Signal::WindowChange;
+        let mut waitable = Waitable::register(signal.into()).unwrap();
+
+        unsafe { libc::raise(signal.into_raw()) };
+
+        let readable = || waitable.reader.wait_readable(Some(Duration::ZERO));
+        assert!(readable().unwrap());
+        assert!(readable().unwrap());
+
+        assert_eq!(waitable.ack(), SignalSet::from(signal));
+        assert!(!waitable.reader.wait_readable(Some(Duration::ZERO)).unwrap());
+        assert!(waitable.ack().is_empty());
+    }
+}
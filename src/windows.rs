@@ -1 +1,185 @@
+//! Windows console control event handling.
 
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+use winapi::{
+    shared::minwindef::{BOOL, DWORD, FALSE, TRUE},
+    um::{
+        consoleapi::SetConsoleCtrlHandler,
+        wincon::{CTRL_BREAK_EVENT, CTRL_C_EVENT},
+    },
+};
+
+/// The bit for `CTRL_C_EVENT`.
+pub(crate) const CTRL_C: u8 = 1;
+
+/// The bit for `CTRL_BREAK_EVENT`.
+pub(crate) const CTRL_BREAK: u8 = 2;
+
+/// The number of times each event has been received, indexed by the position
+/// of its bit.
+static COUNTS: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+
+/// Tasks waiting for an event, along with the ID of the future they poll.
+static WAKERS: Mutex<Vec<(usize, Waker)>> = Mutex::new(Vec::new());
+
+/// The number of live registrations. Our handler is installed while nonzero.
+static REGISTRATIONS: Mutex<usize> = Mutex::new(0);
+
+/// The ID of the next future, for finding its waker.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Called on the thread that the system creates for the event.
+pub(crate) unsafe extern "system" fn handler(ctrl_type: DWORD) -> BOOL {
+    let event = match ctrl_type {
+        CTRL_C_EVENT => CTRL_C,
+        CTRL_BREAK_EVENT => CTRL_BREAK,
+        // Let the next handler deal with it.
+        _ => return FALSE,
+    };
+
+    // Count before taking the wakers. A task that checks the count after this
+    // either sees it or has its waker taken below.
+    COUNTS[event.trailing_zeros() as usize].fetch_add(1, Ordering::SeqCst);
+
+    // Unlike signal handlers, console handlers run on their own thread, so
+    // locking and waking here is fine.
+    let wakers = {
+        let mut wakers =
+            WAKERS.lock().unwrap_or_else(|error| error.into_inner());
+        std::mem::take(&mut *wakers)
+    };
+    wakers.into_iter().for_each(|(_, waker)| waker.wake());
+
+    TRUE
+}
+
+/// Returns the current count of each event.
+fn counts() -> [usize; 2] {
+    [
+        COUNTS[0].load(Ordering::SeqCst),
+        COUNTS[1].load(Ordering::SeqCst),
+    ]
+}
+
+/// Keeps our handler installed until the last clone is dropped.
+#[derive(Debug)]
+struct Registration;
+
+impl Registration {
+    fn new() -> io::Result<Self> {
+        let mut registrations = REGISTRATIONS
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+
+        if *registrations == 0
+            && unsafe { SetConsoleCtrlHandler(Some(handler), TRUE) } == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        *registrations += 1;
+
+        Ok(Self)
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let mut registrations = REGISTRATIONS
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+
+        *registrations -= 1;
+
+        // Restore the default of terminating the process. This can only fail
+        // if the handler isn't installed.
+        if *registrations == 0 {
+            unsafe { SetConsoleCtrlHandler(Some(handler), FALSE) };
+        }
+    }
+}
+
+/// A future that is fulfilled once upon receiving any of a set of console
+/// control events after being registered.
+#[derive(Debug)]
+pub(crate) struct CtrlOnce {
+    events: u8,
+    /// The event counts when `self` was registered.
+    counts: [usize; 2],
+    id: usize,
+    registration: Arc<Registration>,
+}
+
+impl Clone for CtrlOnce {
+    fn clone(&self) -> Self {
+        Self {
+            events: self.events,
+            counts: self.counts,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            registration: self.registration.clone(),
+        }
+    }
+}
+
+impl Drop for CtrlOnce {
+    fn drop(&mut self) {
+        let mut wakers =
+            WAKERS.lock().unwrap_or_else(|error| error.into_inner());
+        wakers.retain(|(id, _)| *id != self.id);
+    }
+}
+
+impl Future for CtrlOnce {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.is_caught() {
+            return Poll::Ready(());
+        }
+
+        let mut wakers =
+            WAKERS.lock().unwrap_or_else(|error| error.into_inner());
+
+        // Check again now that the handler can't take the wakers.
+        if self.is_caught() {
+            return Poll::Ready(());
+        }
+
+        match wakers.iter_mut().find(|(id, _)| *id == self.id) {
+            Some((_, waker)) if waker.will_wake(cx.waker()) => {}
+            Some((_, waker)) => *waker = cx.waker().clone(),
+            None => wakers.push((self.id, cx.waker().clone())),
+        }
+
+        Poll::Pending
+    }
+}
+
+impl CtrlOnce {
+    /// Installs the handler for `events`, a combination of `CTRL_C` and
+    /// `CTRL_BREAK`.
+    pub fn register(events: u8) -> io::Result<Self> {
+        let counts = counts();
+        Ok(Self {
+            events,
+            counts,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            registration: Arc::new(Registration::new()?),
+        })
+    }
+
+    /// Returns whether any of `self.events` was received since registering.
+    fn is_caught(&self) -> bool {
+        let counts = counts();
+        (0..counts.len())
+            .any(|i| self.events & (1 << i) != 0 && counts[i] != self.counts[i])
+    }
+}